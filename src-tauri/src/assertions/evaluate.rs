@@ -0,0 +1,271 @@
+//! Assertion evaluation against a received response
+
+use std::cell::OnceCell;
+
+use regex::Regex;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+use crate::http::HttpResponse;
+
+use super::types::{Assertion, AssertionCheck, AssertionResult};
+
+/// Schema errors listed in a failure message before the rest are summarised
+const MAX_SCHEMA_ERRORS: usize = 3;
+
+/// Evaluate the enabled assertions in order
+pub fn evaluate(assertions: &[Assertion], response: &HttpResponse) -> Vec<AssertionResult> {
+    let body = ResponseJson::new(&response.body);
+    assertions
+        .iter()
+        .filter(|a| a.enabled)
+        .map(|a| {
+            let (name, outcome) = check(&a.check, response, &body);
+            AssertionResult {
+                name,
+                passed: outcome.is_ok(),
+                message: outcome.err(),
+            }
+        })
+        .collect()
+}
+
+/// Response body parsed as JSON on first use
+struct ResponseJson<'a> {
+    text: &'a str,
+    parsed: OnceCell<Option<Value>>,
+}
+
+impl<'a> ResponseJson<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            parsed: OnceCell::new(),
+        }
+    }
+
+    fn value(&self) -> Result<&Value, String> {
+        self.parsed
+            .get_or_init(|| serde_json::from_str(self.text).ok())
+            .as_ref()
+            .ok_or_else(|| "Response body is not valid JSON".to_string())
+    }
+
+    /// First node matched by `path`
+    fn query(&self, path: &str) -> Result<Option<&Value>, String> {
+        let path = JsonPath::parse(path).map_err(|e| format!("Invalid JSONPath: {}", e))?;
+        Ok(path.query(self.value()?).first())
+    }
+}
+
+fn check(
+    check: &AssertionCheck,
+    response: &HttpResponse,
+    body: &ResponseJson,
+) -> (String, Result<(), String>) {
+    match check {
+        AssertionCheck::StatusEquals { status } => (
+            format!("Status is {}", status),
+            expect(response.status == *status, || received(response)),
+        ),
+        AssertionCheck::StatusInRange { min, max } => (
+            format!("Status is between {} and {}", min, max),
+            expect((*min..=*max).contains(&response.status), || received(response)),
+        ),
+        AssertionCheck::HeaderExists { name } => (
+            format!("Header {} is present", name),
+            expect(header_values(response, name).next().is_some(), || {
+                "Header is missing".to_string()
+            }),
+        ),
+        AssertionCheck::HeaderMatches { name, pattern } => (
+            format!("Header {} matches {}", name, pattern),
+            regex(pattern).and_then(|re| {
+                let values: Vec<&str> = header_values(response, name).collect();
+                if values.is_empty() {
+                    Err("Header is missing".to_string())
+                } else {
+                    expect(values.iter().any(|v| re.is_match(v)), || {
+                        format!("Got \"{}\"", values.join(", "))
+                    })
+                }
+            }),
+        ),
+        AssertionCheck::JsonPathEquals { path, value } => (
+            format!("{} equals {}", path, value),
+            body.query(path).and_then(|found| match found {
+                Some(found) => expect(found == value, || format!("Got {}", found)),
+                None => Err(format!("No value at {}", path)),
+            }),
+        ),
+        AssertionCheck::JsonPathExists { path } => (
+            format!("{} exists", path),
+            body.query(path)
+                .and_then(|found| expect(found.is_some(), || format!("No value at {}", path))),
+        ),
+        AssertionCheck::JsonPathType { path, json_type } => (
+            format!("{} is {}", path, json_type.as_str()),
+            body.query(path).and_then(|found| match found {
+                Some(found) => expect(json_type.matches(found), || {
+                    format!("Got {}", type_name(found))
+                }),
+                None => Err(format!("No value at {}", path)),
+            }),
+        ),
+        AssertionCheck::BodyContains { text } => (
+            format!("Body contains \"{}\"", text),
+            expect(response.body.contains(text.as_str()), || {
+                "Text not found in body".to_string()
+            }),
+        ),
+        AssertionCheck::BodyMatches { pattern } => (
+            format!("Body matches {}", pattern),
+            regex(pattern).and_then(|re| {
+                expect(re.is_match(&response.body), || "No match in body".to_string())
+            }),
+        ),
+        AssertionCheck::ResponseTimeBelow { max_ms } => (
+            format!("Response time is below {} ms", max_ms),
+            expect(response.time_ms < *max_ms, || {
+                format!("Took {} ms", response.time_ms)
+            }),
+        ),
+        AssertionCheck::JsonSchema { schema } => (
+            "Body matches JSON Schema".to_string(),
+            body.value().and_then(|instance| validate_schema(schema, instance)),
+        ),
+    }
+}
+
+fn validate_schema(schema: &Value, instance: &Value) -> Result<(), String> {
+    let validator =
+        jsonschema::validator_for(schema).map_err(|e| format!("Invalid JSON Schema: {}", e))?;
+
+    let errors: Vec<String> = validator
+        .iter_errors(instance)
+        .map(|e| {
+            let location = e.instance_path().to_string();
+            if location.is_empty() {
+                e.to_string()
+            } else {
+                format!("{}: {}", location, e)
+            }
+        })
+        .collect();
+
+    match errors.len() {
+        0 => Ok(()),
+        n if n <= MAX_SCHEMA_ERRORS => Err(errors.join("; ")),
+        n => Err(format!(
+            "{} (and {} more)",
+            errors[..MAX_SCHEMA_ERRORS].join("; "),
+            n - MAX_SCHEMA_ERRORS
+        )),
+    }
+}
+
+fn expect(condition: bool, message: impl FnOnce() -> String) -> Result<(), String> {
+    if condition {
+        Ok(())
+    } else {
+        Err(message())
+    }
+}
+
+fn received(response: &HttpResponse) -> String {
+    format!("Received {} {}", response.status, response.status_text)
+}
+
+fn regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e))
+}
+
+/// Values of every header named `name`, case-insensitively
+fn header_values<'a>(response: &'a HttpResponse, name: &'a str) -> impl Iterator<Item = &'a str> {
+    response
+        .headers
+        .iter()
+        .filter(move |h| h.key.eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str())
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assertions::JsonType;
+    use crate::http::HttpResponseHeader;
+    use serde_json::json;
+
+    fn response() -> HttpResponse {
+        HttpResponse {
+            status: 201,
+            status_text: "Created".to_string(),
+            headers: vec![HttpResponseHeader {
+                key: "Content-Type".to_string(),
+                value: "application/json; charset=utf-8".to_string(),
+            }],
+            http_version: "HTTP/1.1".to_string(),
+            body: r#"{"data": {"id": 42, "tags": ["a", "b"], "name": "Widget"}}"#.to_string(),
+            time_ms: 120,
+            size_bytes: 58,
+            assertions: Vec::new(),
+            extractions: Vec::new(),
+            console: Vec::new(),
+        }
+    }
+
+    fn assertion(check: AssertionCheck) -> Assertion {
+        Assertion {
+            enabled: true,
+            check,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_assertions() {
+        let assertions = vec![
+            assertion(AssertionCheck::StatusInRange { min: 200, max: 299 }),
+            assertion(AssertionCheck::HeaderMatches {
+                name: "content-type".to_string(),
+                pattern: "^application/json".to_string(),
+            }),
+            assertion(AssertionCheck::JsonPathEquals {
+                path: "$.data.id".to_string(),
+                value: json!(42),
+            }),
+            assertion(AssertionCheck::JsonPathType {
+                path: "$.data.tags".to_string(),
+                json_type: JsonType::Array,
+            }),
+            assertion(AssertionCheck::ResponseTimeBelow { max_ms: 100 }),
+            assertion(AssertionCheck::JsonSchema {
+                schema: json!({
+                    "type": "object",
+                    "required": ["data"],
+                    "properties": {"data": {"properties": {"name": {"type": "integer"}}}}
+                }),
+            }),
+            Assertion {
+                enabled: false,
+                check: AssertionCheck::StatusEquals { status: 500 },
+            },
+        ];
+
+        let results = evaluate(&assertions, &response());
+        let passed: Vec<bool> = results.iter().map(|r| r.passed).collect();
+        assert_eq!(passed, vec![true, true, true, true, false, false]);
+        assert_eq!(results[4].message.as_deref(), Some("Took 120 ms"));
+        assert!(results[5].message.as_deref().unwrap().contains("/data/name"));
+    }
+}
@@ -0,0 +1,10 @@
+//! Response assertions
+//!
+//! Declarative checks stored on a request and evaluated after it is sent.
+
+mod evaluate;
+mod types;
+
+pub use evaluate::evaluate;
+#[allow(unused_imports)]
+pub use types::{Assertion, AssertionCheck, AssertionResult, JsonType};
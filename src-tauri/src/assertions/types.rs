@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assertion {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub check: AssertionCheck,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A single check against a response. Paths are RFC 9535 JSONPath expressions
+/// (e.g. `$.data.items[0].id`) and patterns are regular expressions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum AssertionCheck {
+    StatusEquals { status: u16 },
+    /// Inclusive range, e.g. 200..=299
+    StatusInRange { min: u16, max: u16 },
+    HeaderExists { name: String },
+    HeaderMatches { name: String, pattern: String },
+    JsonPathEquals { path: String, value: serde_json::Value },
+    JsonPathExists { path: String },
+    JsonPathType { path: String, json_type: JsonType },
+    BodyContains { text: String },
+    BodyMatches { pattern: String },
+    ResponseTimeBelow { max_ms: u64 },
+    JsonSchema { schema: serde_json::Value },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Object,
+    Array,
+    Null,
+}
+
+impl JsonType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Integer => "integer",
+            Self::Boolean => "boolean",
+            Self::Object => "object",
+            Self::Array => "array",
+            Self::Null => "null",
+        }
+    }
+
+    pub fn matches(&self, value: &serde_json::Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Number => value.is_number(),
+            Self::Integer => value.is_i64() || value.is_u64(),
+            Self::Boolean => value.is_boolean(),
+            Self::Object => value.is_object(),
+            Self::Array => value.is_array(),
+            Self::Null => value.is_null(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertionResult {
+    pub name: String,
+    pub passed: bool,
+    pub message: Option<String>,
+}
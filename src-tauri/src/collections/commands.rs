@@ -44,6 +44,12 @@ pub fn update_collection(
     service.update(input).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn reorder_collections(db: State<Database>, ids: Vec<String>) -> Result<(), String> {
+    let service = CollectionService::new(db.inner().clone());
+    service.reorder(&ids).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn delete_collection(db: State<Database>, id: String) -> Result<(), String> {
     let service = CollectionService::new(db.inner().clone());
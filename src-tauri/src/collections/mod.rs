@@ -4,5 +4,7 @@ mod types;
 
 pub use commands::*;
 #[allow(unused_imports)]
-pub use types::{Collection, CreateCollectionInput, UpdateCollectionInput};
+pub use types::{
+    is_absolute_url, Collection, CollectionDefaults, CreateCollectionInput, UpdateCollectionInput,
+};
 pub(crate) use service::CollectionService;
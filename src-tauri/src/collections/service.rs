@@ -1,9 +1,13 @@
 use chrono::Utc;
-use redb::ReadableTable;
+use redb::{ReadableTable, WriteTransaction};
 
+use crate::db::helpers::{get_row, index_clear, index_get, put_row, remove_row};
 use crate::db::{
-    Database, DbError, DbResult, COLLECTIONS, COLLECTIONS_BY_WORKSPACE,
+    Database, DbError, DbResult, COLLECTIONS, COLLECTIONS_BY_WORKSPACE, COLLECTION_RUNS, FOLDERS,
+    FOLDERS_BY_COLLECTION, REQUESTS, REQUESTS_BY_COLLECTION, RUNS_BY_COLLECTION,
 };
+use crate::folders::{Folder, FolderService};
+use crate::requests::{Request, RequestService};
 
 use super::types::{Collection, CreateCollectionInput, UpdateCollectionInput};
 
@@ -28,7 +32,11 @@ impl CollectionService {
             collections.push(collection);
         }
 
-        collections.sort_by(|a, b| a.name.cmp(&b.name));
+        collections.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
         Ok(collections)
     }
 
@@ -66,18 +74,27 @@ impl CollectionService {
             }
         }
 
-        collections.sort_by(|a, b| a.name.cmp(&b.name));
+        collections.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
         Ok(collections)
     }
 
     pub fn create(&self, input: CreateCollectionInput) -> DbResult<Collection> {
-        let collection = Collection::new(input.name, input.description, input.workspace_id.clone());
+        let mut collection =
+            Collection::new(input.name, input.description, input.workspace_id.clone());
+        collection.defaults = input.defaults;
+        collection.scripts = input.scripts;
+
+        let write_txn = self.db.begin_write()?;
+
+        collection.sort_order = Self::next_sort_order_in(&write_txn, &input.workspace_id)?;
 
         let json = serde_json::to_string(&collection)
             .map_err(|e| DbError::Serialization(e.to_string()))?;
 
-        let write_txn = self.db.begin_write()?;
-
         {
             let mut table = write_txn.open_table(COLLECTIONS)?;
             table.insert(collection.id.as_str(), json.as_str())?;
@@ -116,6 +133,12 @@ impl CollectionService {
         if let Some(description) = input.description {
             collection.description = description;
         }
+        if let Some(defaults) = input.defaults {
+            collection.defaults = defaults;
+        }
+        if let Some(scripts) = input.scripts {
+            collection.scripts = scripts;
+        }
         collection.updated_at = Utc::now();
 
         let json = serde_json::to_string(&collection)
@@ -131,11 +154,61 @@ impl CollectionService {
         Ok(collection)
     }
 
+    /// Set the position of each collection to its index in `ids`, in one transaction
+    pub fn reorder(&self, ids: &[String]) -> DbResult<()> {
+        let write_txn = self.db.begin_write()?;
+        let now = Utc::now();
+
+        let mut collections = Vec::new();
+        for id in ids {
+            let collection: Collection = get_row(&write_txn, COLLECTIONS, id)?
+                .ok_or_else(|| DbError::NotFound(format!("Collection not found: {}", id)))?;
+            collections.push(collection);
+        }
+        if collections.windows(2).any(|pair| pair[0].workspace_id != pair[1].workspace_id) {
+            return Err(DbError::InvalidInput(
+                "Only collections of the same workspace can be reordered together".to_string(),
+            ));
+        }
+
+        for (position, mut collection) in collections.into_iter().enumerate() {
+            collection.sort_order = position as i64;
+            collection.updated_at = now;
+            put_row(&write_txn, COLLECTIONS, &collection.id, &collection)?;
+        }
+
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Delete a collection along with its folders, requests and run history
     pub fn delete(&self, id: &str) -> DbResult<()> {
         let collection = self.get(id)?;
 
         let write_txn = self.db.begin_write()?;
 
+        // Cascade to folders (which removes the requests inside them), then to
+        // the requests left at the collection root
+        for folder_id in index_get(&write_txn, FOLDERS_BY_COLLECTION, id)? {
+            if let Some(folder) = get_row::<Folder>(&write_txn, FOLDERS, &folder_id)? {
+                if folder.parent_id.is_none() {
+                    FolderService::delete_tree_in(&write_txn, &folder)?;
+                }
+            }
+        }
+        for request_id in index_get(&write_txn, REQUESTS_BY_COLLECTION, id)? {
+            if let Some(request) = get_row::<Request>(&write_txn, REQUESTS, &request_id)? {
+                RequestService::delete_in(&write_txn, &request)?;
+            }
+        }
+        index_clear(&write_txn, FOLDERS_BY_COLLECTION, id)?;
+        index_clear(&write_txn, REQUESTS_BY_COLLECTION, id)?;
+        for run_id in index_get(&write_txn, RUNS_BY_COLLECTION, id)? {
+            remove_row(&write_txn, COLLECTION_RUNS, &run_id)?;
+        }
+        index_clear(&write_txn, RUNS_BY_COLLECTION, id)?;
+
         // Remove from collections table
         {
             let mut table = write_txn.open_table(COLLECTIONS)?;
@@ -161,4 +234,18 @@ impl CollectionService {
 
         Ok(())
     }
+
+    /// A position after every collection already in the workspace
+    pub(crate) fn next_sort_order_in(
+        write_txn: &WriteTransaction,
+        workspace_id: &str,
+    ) -> DbResult<i64> {
+        let mut next = 0;
+        for id in index_get(write_txn, COLLECTIONS_BY_WORKSPACE, workspace_id)? {
+            if let Some(collection) = get_row::<Collection>(write_txn, COLLECTIONS, &id)? {
+                next = next.max(collection.sort_order + 1);
+            }
+        }
+        Ok(next)
+    }
 }
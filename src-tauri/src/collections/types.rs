@@ -2,12 +2,24 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::requests::{Request, RequestAuth, RequestHeader, RequestParam};
+use crate::scripts::Scripts;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Collection {
     pub id: String,
     pub name: String,
     pub description: String,
     pub workspace_id: String,
+    #[serde(default)]
+    pub defaults: CollectionDefaults,
+    /// Run around every request in the collection, before the request's own scripts
+    #[serde(default)]
+    pub scripts: Scripts,
+    /// Position within the workspace; equal positions (e.g. collections created
+    /// before manual ordering existed) fall back to name order
+    #[serde(default)]
+    pub sort_order: i64,
     #[serde(default = "Utc::now")]
     pub created_at: DateTime<Utc>,
     #[serde(default = "Utc::now")]
@@ -22,17 +34,101 @@ impl Collection {
             name,
             description,
             workspace_id,
+            defaults: CollectionDefaults::default(),
+            scripts: Scripts::default(),
+            sort_order: 0,
             created_at: now,
             updated_at: now,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Settings inherited by every request in a collection unless the request overrides them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollectionDefaults {
+    /// Prefixed to request URLs that are relative
+    #[serde(default)]
+    pub base_url: String,
+    #[serde(default)]
+    pub headers: Vec<RequestHeader>,
+    #[serde(default)]
+    pub params: Vec<RequestParam>,
+    /// Used by requests whose auth is `Inherit`
+    #[serde(default)]
+    pub auth: RequestAuth,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub validate_ssl: Option<bool>,
+}
+
+impl CollectionDefaults {
+    /// Merge the defaults into a request. Request values win: headers and params
+    /// are only added for keys the request does not enable itself.
+    pub fn apply(&self, mut request: Request) -> Request {
+        if !self.base_url.is_empty() && !is_absolute_url(&request.url) {
+            request.url = if request.url.is_empty() {
+                self.base_url.clone()
+            } else {
+                format!(
+                    "{}/{}",
+                    self.base_url.trim_end_matches('/'),
+                    request.url.trim_start_matches('/')
+                )
+            };
+        }
+
+        let mut headers: Vec<RequestHeader> = self
+            .headers
+            .iter()
+            .filter(|d| {
+                !request
+                    .headers
+                    .iter()
+                    .any(|h| h.enabled && h.key.eq_ignore_ascii_case(&d.key))
+            })
+            .cloned()
+            .collect();
+        headers.append(&mut request.headers);
+        request.headers = headers;
+
+        let mut params: Vec<RequestParam> = self
+            .params
+            .iter()
+            .filter(|d| !request.params.iter().any(|p| p.enabled && p.key == d.key))
+            .cloned()
+            .collect();
+        params.append(&mut request.params);
+        request.params = params;
+
+        if request.auth == RequestAuth::Inherit {
+            request.auth = match self.auth {
+                RequestAuth::Inherit => RequestAuth::None,
+                ref auth => auth.clone(),
+            };
+        }
+        request.timeout_ms = request.timeout_ms.or(self.timeout_ms);
+        request.validate_ssl = request.validate_ssl.or(self.validate_ssl);
+
+        request
+    }
+}
+
+/// URLs with a scheme, or starting with a variable (e.g. `{{host}}/users`), are not
+/// prefixed with the base URL
+pub fn is_absolute_url(url: &str) -> bool {
+    url.contains("://") || url.starts_with("{{")
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateCollectionInput {
     pub name: String,
     pub description: String,
     pub workspace_id: String,
+    #[serde(default)]
+    pub defaults: CollectionDefaults,
+    #[serde(default)]
+    pub scripts: Scripts,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,4 +136,6 @@ pub struct UpdateCollectionInput {
     pub id: String,
     pub name: Option<String>,
     pub description: Option<String>,
+    pub defaults: Option<CollectionDefaults>,
+    pub scripts: Option<Scripts>,
 }
@@ -1,7 +1,7 @@
 //! Database initialization and management
 
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use directories::ProjectDirs;
 use redb::{Database as RedbDatabase, ReadableDatabase};
@@ -12,6 +12,9 @@ use super::tables::*;
 /// Main database wrapper
 pub struct Database {
     db: Arc<RedbDatabase>,
+    /// Key that decrypts secret variables; only ever held in memory, and only
+    /// while the vault is unlocked
+    vault_key: Arc<RwLock<Option<[u8; 32]>>>,
 }
 
 impl Database {
@@ -25,7 +28,10 @@ impl Database {
         }
 
         let db = RedbDatabase::create(&path)?;
-        let database = Self { db: Arc::new(db) };
+        let database = Self {
+            db: Arc::new(db),
+            vault_key: Arc::default(),
+        };
 
         // Initialize tables
         database.init_tables()?;
@@ -41,7 +47,10 @@ impl Database {
         }
 
         let db = RedbDatabase::create(&path)?;
-        let database = Self { db: Arc::new(db) };
+        let database = Self {
+            db: Arc::new(db),
+            vault_key: Arc::default(),
+        };
         database.init_tables()?;
 
         Ok(database)
@@ -66,16 +75,24 @@ impl Database {
         write_txn.open_table(WORKSPACES)?;
         write_txn.open_table(WORKSPACE_SYNC_GROUPS)?;
         write_txn.open_table(COLLECTIONS)?;
+        write_txn.open_table(FOLDERS)?;
         write_txn.open_table(REQUESTS)?;
         write_txn.open_table(VARIABLES)?;
+        write_txn.open_table(ENVIRONMENTS)?;
+        write_txn.open_table(COLLECTION_RUNS)?;
         write_txn.open_table(APP_SETTINGS)?;
+        write_txn.open_table(VAULT)?;
 
         // Create index tables
         write_txn.open_table(COLLECTIONS_BY_WORKSPACE)?;
+        write_txn.open_table(FOLDERS_BY_COLLECTION)?;
         write_txn.open_table(REQUESTS_BY_COLLECTION)?;
+        write_txn.open_table(REQUESTS_BY_FOLDER)?;
         write_txn.open_table(REQUESTS_BY_WORKSPACE)?;
         write_txn.open_table(VARIABLES_BY_SCOPE)?;
         write_txn.open_table(WORKSPACES_BY_SYNC_GROUP)?;
+        write_txn.open_table(RUNS_BY_COLLECTION)?;
+        write_txn.open_table(ENVIRONMENTS_BY_WORKSPACE)?;
 
         write_txn.commit()?;
 
@@ -97,12 +114,23 @@ impl Database {
     pub fn begin_write(&self) -> DbResult<redb::WriteTransaction> {
         Ok(self.db.begin_write()?)
     }
+
+    /// The unlocked vault key, if any
+    pub fn vault_key(&self) -> Option<[u8; 32]> {
+        *self.vault_key.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Hold (or with `None`, forget) the vault key for this session
+    pub fn set_vault_key(&self, key: Option<[u8; 32]>) {
+        *self.vault_key.write().unwrap_or_else(|e| e.into_inner()) = key;
+    }
 }
 
 impl Clone for Database {
     fn clone(&self) -> Self {
         Self {
             db: Arc::clone(&self.db),
+            vault_key: Arc::clone(&self.vault_key),
         }
     }
 }
@@ -22,6 +22,15 @@ pub enum DbError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Script error: {0}")]
+    Script(String),
+
+    #[error("Vault error: {0}")]
+    Vault(String),
+
     #[error("Serialization error: {0}")]
     Serialization(String),
 
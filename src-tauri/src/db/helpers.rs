@@ -0,0 +1,112 @@
+//! Helpers for working inside a write transaction
+//!
+//! Rows are stored as JSON strings and index tables map a key to a JSON array of IDs.
+//! Operations that touch several tables (moves, cascading deletes) use these so that
+//! every change lands in the same transaction.
+
+use redb::{ReadableTable, TableDefinition, WriteTransaction};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::error::{DbError, DbResult};
+
+/// A table of `&str` keys to JSON `&str` values
+pub type JsonTable = TableDefinition<'static, &'static str, &'static str>;
+
+/// Read and deserialize a row, returning `None` if it does not exist
+pub fn get_row<T: DeserializeOwned>(
+    write_txn: &WriteTransaction,
+    table: JsonTable,
+    id: &str,
+) -> DbResult<Option<T>> {
+    let table = write_txn.open_table(table)?;
+    let row = match table.get(id)? {
+        Some(value) => Some(
+            serde_json::from_str(value.value())
+                .map_err(|e| DbError::Serialization(e.to_string()))?,
+        ),
+        None => None,
+    };
+    Ok(row)
+}
+
+/// Serialize and write a row
+pub fn put_row<T: Serialize>(
+    write_txn: &WriteTransaction,
+    table: JsonTable,
+    id: &str,
+    row: &T,
+) -> DbResult<()> {
+    let json = serde_json::to_string(row).map_err(|e| DbError::Serialization(e.to_string()))?;
+    let mut table = write_txn.open_table(table)?;
+    table.insert(id, json.as_str())?;
+    Ok(())
+}
+
+/// Remove a row if it exists
+pub fn remove_row(write_txn: &WriteTransaction, table: JsonTable, id: &str) -> DbResult<()> {
+    let mut table = write_txn.open_table(table)?;
+    table.remove(id)?;
+    Ok(())
+}
+
+/// Read the IDs stored under `key` in an index table
+pub fn index_get(
+    write_txn: &WriteTransaction,
+    table: JsonTable,
+    key: &str,
+) -> DbResult<Vec<String>> {
+    let idx_table = write_txn.open_table(table)?;
+    let ids = match idx_table.get(key)? {
+        Some(value) => serde_json::from_str(value.value())
+            .map_err(|e| DbError::Serialization(e.to_string()))?,
+        None => Vec::new(),
+    };
+    Ok(ids)
+}
+
+/// Replace the IDs stored under `key` in an index table
+pub fn index_set(
+    write_txn: &WriteTransaction,
+    table: JsonTable,
+    key: &str,
+    ids: &[String],
+) -> DbResult<()> {
+    let json = serde_json::to_string(ids).map_err(|e| DbError::Serialization(e.to_string()))?;
+    let mut idx_table = write_txn.open_table(table)?;
+    idx_table.insert(key, json.as_str())?;
+    Ok(())
+}
+
+/// Append an ID under `key` unless it is already present
+pub fn index_add(
+    write_txn: &WriteTransaction,
+    table: JsonTable,
+    key: &str,
+    id: &str,
+) -> DbResult<()> {
+    let mut ids = index_get(write_txn, table, key)?;
+    if !ids.iter().any(|i| i == id) {
+        ids.push(id.to_string());
+    }
+    index_set(write_txn, table, key, &ids)
+}
+
+/// Remove an ID from the list stored under `key`
+pub fn index_remove(
+    write_txn: &WriteTransaction,
+    table: JsonTable,
+    key: &str,
+    id: &str,
+) -> DbResult<()> {
+    let mut ids = index_get(write_txn, table, key)?;
+    ids.retain(|i| i != id);
+    index_set(write_txn, table, key, &ids)
+}
+
+/// Drop the whole entry for `key` from an index table
+pub fn index_clear(write_txn: &WriteTransaction, table: JsonTable, key: &str) -> DbResult<()> {
+    let mut idx_table = write_txn.open_table(table)?;
+    idx_table.remove(key)?;
+    Ok(())
+}
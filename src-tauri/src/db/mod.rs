@@ -4,6 +4,7 @@
 
 mod database;
 mod error;
+pub mod helpers;
 mod tables;
 
 pub use database::Database;
@@ -14,12 +14,24 @@ pub const WORKSPACE_SYNC_GROUPS: TableDefinition<&str, &str> =
 /// Collections table: collection_id -> collection JSON
 pub const COLLECTIONS: TableDefinition<&str, &str> = TableDefinition::new("collections");
 
+/// Folders table: folder_id -> folder JSON
+pub const FOLDERS: TableDefinition<&str, &str> = TableDefinition::new("folders");
+
 /// Requests table: request_id -> request JSON
 pub const REQUESTS: TableDefinition<&str, &str> = TableDefinition::new("requests");
 
 /// Variables table: variable_id -> variable JSON
 pub const VARIABLES: TableDefinition<&str, &str> = TableDefinition::new("variables");
 
+/// Environments table: environment_id -> environment JSON
+pub const ENVIRONMENTS: TableDefinition<&str, &str> = TableDefinition::new("environments");
+
+/// Collection runs table: run_id -> run record JSON
+pub const COLLECTION_RUNS: TableDefinition<&str, &str> = TableDefinition::new("collection_runs");
+
+/// Vault table: "config" -> vault config JSON (single row)
+pub const VAULT: TableDefinition<&str, &str> = TableDefinition::new("vault");
+
 /// App settings table: "settings" -> settings JSON (single row)
 pub const APP_SETTINGS: TableDefinition<&str, &str> = TableDefinition::new("app_settings");
 
@@ -29,19 +41,36 @@ pub const APP_SETTINGS: TableDefinition<&str, &str> = TableDefinition::new("app_
 pub const COLLECTIONS_BY_WORKSPACE: TableDefinition<&str, &str> =
     TableDefinition::new("idx_collections_by_workspace");
 
+/// Folders by collection index: collection_id -> folder_ids JSON array (all nesting levels)
+pub const FOLDERS_BY_COLLECTION: TableDefinition<&str, &str> =
+    TableDefinition::new("idx_folders_by_collection");
+
 /// Requests by collection index: collection_id -> request_ids JSON array
 pub const REQUESTS_BY_COLLECTION: TableDefinition<&str, &str> =
     TableDefinition::new("idx_requests_by_collection");
 
+/// Requests by folder index: folder_id -> request_ids JSON array (direct children only)
+pub const REQUESTS_BY_FOLDER: TableDefinition<&str, &str> =
+    TableDefinition::new("idx_requests_by_folder");
+
 /// Requests by workspace (standalone) index: workspace_id -> request_ids JSON array
 pub const REQUESTS_BY_WORKSPACE: TableDefinition<&str, &str> =
     TableDefinition::new("idx_requests_by_workspace");
 
 /// Variables by scope index: scope_key -> variable_ids JSON array
-/// scope_key format: "global", "workspace:{id}", "collection:{id}", "request:{id}"
+/// scope_key format: "global", "workspace:{id}", "environment:{id}", "collection:{id}",
+/// "request:{id}"
 pub const VARIABLES_BY_SCOPE: TableDefinition<&str, &str> =
     TableDefinition::new("idx_variables_by_scope");
 
 /// Workspaces by sync group index: sync_group_id -> workspace_ids JSON array
 pub const WORKSPACES_BY_SYNC_GROUP: TableDefinition<&str, &str> =
     TableDefinition::new("idx_workspaces_by_sync_group");
+
+/// Runs by collection index: collection_id -> run_ids JSON array (oldest first)
+pub const RUNS_BY_COLLECTION: TableDefinition<&str, &str> =
+    TableDefinition::new("idx_runs_by_collection");
+
+/// Environments by workspace index: workspace_id -> environment_ids JSON array
+pub const ENVIRONMENTS_BY_WORKSPACE: TableDefinition<&str, &str> =
+    TableDefinition::new("idx_environments_by_workspace");
@@ -0,0 +1,59 @@
+use tauri::State;
+
+use crate::db::Database;
+use crate::workspaces::Workspace;
+
+use super::service::EnvironmentService;
+use super::types::{CreateEnvironmentInput, Environment, UpdateEnvironmentInput};
+
+#[tauri::command]
+pub fn get_environment(db: State<Database>, id: String) -> Result<Environment, String> {
+    let service = EnvironmentService::new(db.inner().clone());
+    service.get(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_environments_by_workspace(
+    db: State<Database>,
+    workspace_id: String,
+) -> Result<Vec<Environment>, String> {
+    let service = EnvironmentService::new(db.inner().clone());
+    service.get_by_workspace(&workspace_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn create_environment(
+    db: State<Database>,
+    input: CreateEnvironmentInput,
+) -> Result<Environment, String> {
+    let service = EnvironmentService::new(db.inner().clone());
+    service.create(input).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_environment(
+    db: State<Database>,
+    input: UpdateEnvironmentInput,
+) -> Result<Environment, String> {
+    let service = EnvironmentService::new(db.inner().clone());
+    service.update(input).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_environment(db: State<Database>, id: String) -> Result<(), String> {
+    let service = EnvironmentService::new(db.inner().clone());
+    service.delete(&id).map_err(|e| e.to_string())
+}
+
+/// Select the environment used when resolving variables; `None` clears it
+#[tauri::command]
+pub fn set_active_environment(
+    db: State<Database>,
+    workspace_id: String,
+    environment_id: Option<String>,
+) -> Result<Workspace, String> {
+    let service = EnvironmentService::new(db.inner().clone());
+    service
+        .set_active(&workspace_id, environment_id.as_deref())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,15 @@
+//! Environments module
+//!
+//! Named variable sets (dev, staging, prod, ...) within a workspace. The active
+//! environment's variables resolve above the workspace's own variables and below
+//! collection and request variables.
+
+mod commands;
+mod service;
+mod types;
+
+pub use commands::*;
+#[allow(unused_imports)]
+pub use types::{CreateEnvironmentInput, Environment, UpdateEnvironmentInput};
+#[allow(unused_imports)]
+pub(crate) use service::EnvironmentService;
@@ -0,0 +1,280 @@
+use chrono::Utc;
+
+use crate::db::helpers::{
+    get_row, index_add, index_clear, index_get, index_remove, put_row, remove_row,
+};
+use crate::db::{
+    Database, DbError, DbResult, ENVIRONMENTS, ENVIRONMENTS_BY_WORKSPACE, VARIABLES,
+    VARIABLES_BY_SCOPE, WORKSPACES,
+};
+use crate::workspaces::{Workspace, WorkspaceService};
+
+use super::types::{CreateEnvironmentInput, Environment, UpdateEnvironmentInput};
+
+pub struct EnvironmentService {
+    db: Database,
+}
+
+impl EnvironmentService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub fn get(&self, id: &str) -> DbResult<Environment> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(ENVIRONMENTS)?;
+
+        let value = table
+            .get(id)?
+            .ok_or_else(|| DbError::NotFound(format!("Environment not found: {}", id)))?;
+
+        let environment: Environment = serde_json::from_str(value.value())
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(environment)
+    }
+
+    pub fn get_by_workspace(&self, workspace_id: &str) -> DbResult<Vec<Environment>> {
+        let read_txn = self.db.begin_read()?;
+        let idx_table = read_txn.open_table(ENVIRONMENTS_BY_WORKSPACE)?;
+
+        let environment_ids: Vec<String> = match idx_table.get(workspace_id)? {
+            Some(value) => serde_json::from_str(value.value())
+                .map_err(|e| DbError::Serialization(e.to_string()))?,
+            None => return Ok(Vec::new()),
+        };
+
+        drop(idx_table);
+        drop(read_txn);
+
+        let mut environments = Vec::new();
+        for id in environment_ids {
+            if let Ok(environment) = self.get(&id) {
+                environments.push(environment);
+            }
+        }
+
+        environments.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(environments)
+    }
+
+    pub fn create(&self, input: CreateEnvironmentInput) -> DbResult<Environment> {
+        // Fail early on an unknown workspace
+        WorkspaceService::new(self.db.clone()).get(&input.workspace_id)?;
+        let name = self.validate_name(&input.workspace_id, None, &input.name)?;
+
+        let mut environment = Environment::new(name, input.workspace_id.clone());
+
+        let write_txn = self.db.begin_write()?;
+
+        // New environments go to the end of the workspace
+        environment.sort_order =
+            index_get(&write_txn, ENVIRONMENTS_BY_WORKSPACE, &input.workspace_id)?.len() as i64;
+
+        put_row(&write_txn, ENVIRONMENTS, &environment.id, &environment)?;
+        index_add(
+            &write_txn,
+            ENVIRONMENTS_BY_WORKSPACE,
+            &input.workspace_id,
+            &environment.id,
+        )?;
+
+        write_txn.commit()?;
+
+        Ok(environment)
+    }
+
+    pub fn update(&self, input: UpdateEnvironmentInput) -> DbResult<Environment> {
+        let mut environment = self.get(&input.id)?;
+
+        if let Some(name) = input.name {
+            environment.name =
+                self.validate_name(&environment.workspace_id, Some(&environment.id), &name)?;
+        }
+        environment.updated_at = Utc::now();
+
+        let write_txn = self.db.begin_write()?;
+        put_row(&write_txn, ENVIRONMENTS, &environment.id, &environment)?;
+        write_txn.commit()?;
+
+        Ok(environment)
+    }
+
+    /// Delete an environment together with its variables, deselecting it if it
+    /// was the workspace's active environment
+    pub fn delete(&self, id: &str) -> DbResult<()> {
+        let environment = self.get(id)?;
+        let scope_key = format!("environment:{}", id);
+
+        let write_txn = self.db.begin_write()?;
+
+        for variable_id in index_get(&write_txn, VARIABLES_BY_SCOPE, &scope_key)? {
+            remove_row(&write_txn, VARIABLES, &variable_id)?;
+        }
+        index_clear(&write_txn, VARIABLES_BY_SCOPE, &scope_key)?;
+
+        if let Some(mut workspace) =
+            get_row::<Workspace>(&write_txn, WORKSPACES, &environment.workspace_id)?
+        {
+            if workspace.active_environment_id.as_deref() == Some(id) {
+                workspace.active_environment_id = None;
+                workspace.updated_at = Utc::now();
+                put_row(&write_txn, WORKSPACES, &workspace.id, &workspace)?;
+            }
+        }
+
+        remove_row(&write_txn, ENVIRONMENTS, id)?;
+        index_remove(&write_txn, ENVIRONMENTS_BY_WORKSPACE, &environment.workspace_id, id)?;
+
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Select the environment whose variables resolve for the workspace, or clear
+    /// the selection with `None`
+    pub fn set_active(
+        &self,
+        workspace_id: &str,
+        environment_id: Option<&str>,
+    ) -> DbResult<Workspace> {
+        let mut workspace = WorkspaceService::new(self.db.clone()).get(workspace_id)?;
+
+        if let Some(environment_id) = environment_id {
+            let environment = self.get(environment_id)?;
+            if environment.workspace_id != workspace_id {
+                return Err(DbError::InvalidInput(format!(
+                    "Environment {} belongs to another workspace",
+                    environment.name
+                )));
+            }
+        }
+
+        workspace.active_environment_id = environment_id.map(str::to_string);
+        workspace.updated_at = Utc::now();
+
+        let write_txn = self.db.begin_write()?;
+        put_row(&write_txn, WORKSPACES, &workspace.id, &workspace)?;
+        write_txn.commit()?;
+
+        Ok(workspace)
+    }
+
+    /// Trimmed name, rejected when empty or already used by another environment
+    /// of the workspace
+    fn validate_name(
+        &self,
+        workspace_id: &str,
+        environment_id: Option<&str>,
+        name: &str,
+    ) -> DbResult<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(DbError::InvalidInput(
+                "Environment name cannot be empty".to_string(),
+            ));
+        }
+
+        let taken = self
+            .get_by_workspace(workspace_id)?
+            .iter()
+            .any(|e| Some(e.id.as_str()) != environment_id && e.name.eq_ignore_ascii_case(name));
+        if taken {
+            return Err(DbError::InvalidInput(format!(
+                "An environment named {} already exists",
+                name
+            )));
+        }
+
+        Ok(name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variables::{CreateVariableInput, VariableScope, VariableService};
+    use crate::workspaces::CreateWorkspaceInput;
+    use std::env::temp_dir;
+
+    fn create_test_db() -> Database {
+        let path = temp_dir().join(format!("resona_test_{}.redb", uuid::Uuid::new_v4()));
+        Database::open_at(path).expect("Failed to create test database")
+    }
+
+    #[test]
+    fn test_active_environment_layers_over_workspace() {
+        let db = create_test_db();
+        let workspace = WorkspaceService::new(db.clone())
+            .create(CreateWorkspaceInput {
+                name: "Shop".to_string(),
+                description: String::new(),
+                tags: Vec::new(),
+            })
+            .expect("Failed to create workspace");
+
+        let service = EnvironmentService::new(db.clone());
+        let dev = service
+            .create(CreateEnvironmentInput {
+                name: "dev".to_string(),
+                workspace_id: workspace.id.clone(),
+            })
+            .expect("Failed to create environment");
+        let staging = service
+            .create(CreateEnvironmentInput {
+                name: "staging".to_string(),
+                workspace_id: workspace.id.clone(),
+            })
+            .expect("Failed to create environment");
+        assert!(service
+            .create(CreateEnvironmentInput {
+                name: " Dev ".to_string(),
+                workspace_id: workspace.id.clone(),
+            })
+            .is_err());
+
+        let variables = VariableService::new(db.clone());
+        for (scope, scope_id, value) in [
+            (VariableScope::Workspace, &workspace.id, "http://localhost"),
+            (VariableScope::Environment, &dev.id, "https://dev.example.com"),
+            (VariableScope::Environment, &staging.id, "https://staging.example.com"),
+        ] {
+            variables
+                .create(CreateVariableInput {
+                    name: "base_url".to_string(),
+                    value: value.to_string(),
+                    scope,
+                    scope_id: Some(scope_id.clone()),
+                    ..Default::default()
+                })
+                .expect("Failed to create variable");
+        }
+        let base_url = || {
+            variables
+                .get_resolved(Some(&workspace.id), None, None)
+                .expect("Failed to resolve")[0]
+                .value
+                .clone()
+        };
+
+        assert_eq!(base_url(), "http://localhost");
+        service
+            .set_active(&workspace.id, Some(&staging.id))
+            .expect("Failed to select environment");
+        assert_eq!(base_url(), "https://staging.example.com");
+
+        variables
+            .set_resolved(Some(&workspace.id), None, None, "base_url", "https://s2".to_string())
+            .expect("Failed to set variable");
+        assert_eq!(variables.get_by_environment(&staging.id).unwrap()[0].value, "https://s2");
+
+        service.delete(&staging.id).expect("Failed to delete environment");
+        assert_eq!(base_url(), "http://localhost");
+        assert!(variables.get_by_environment(&staging.id).unwrap().is_empty());
+        assert_eq!(service.get_by_workspace(&workspace.id).unwrap().len(), 1);
+    }
+}
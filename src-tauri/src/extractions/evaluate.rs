@@ -0,0 +1,151 @@
+//! Reading values out of a response
+
+use regex::Regex;
+use serde_json_path::JsonPath;
+use sxd_xpath::Value as XPathValue;
+
+use crate::http::HttpResponse;
+
+use super::types::ExtractionSource;
+
+/// Extract a value, or explain why nothing was found
+pub fn extract(source: &ExtractionSource, response: &HttpResponse) -> Result<String, String> {
+    match source {
+        ExtractionSource::JsonPath { path } => json_path(path, &response.body),
+        ExtractionSource::XPath { expression } => xpath(expression, &response.body),
+        ExtractionSource::Regex { pattern, group } => regex(pattern, *group, &response.body),
+        ExtractionSource::Header { name } => response
+            .headers
+            .iter()
+            .find(|h| h.key.eq_ignore_ascii_case(name))
+            .map(|h| h.value.clone())
+            .ok_or_else(|| format!("No {} header", name)),
+        ExtractionSource::Cookie { name } => response
+            .headers
+            .iter()
+            .filter(|h| h.key.eq_ignore_ascii_case("set-cookie"))
+            .find_map(|h| cookie_value(&h.value, name))
+            .ok_or_else(|| format!("No {} cookie", name)),
+        ExtractionSource::Status => Ok(response.status.to_string()),
+    }
+}
+
+fn json_path(path: &str, body: &str) -> Result<String, String> {
+    let path = JsonPath::parse(path).map_err(|e| format!("Invalid JSONPath: {}", e))?;
+    let value: serde_json::Value =
+        serde_json::from_str(body).map_err(|_| "Response body is not valid JSON".to_string())?;
+
+    match path.query(&value).first() {
+        Some(serde_json::Value::String(s)) => Ok(s.clone()),
+        Some(other) => Ok(other.to_string()),
+        None => Err("No match".to_string()),
+    }
+}
+
+fn xpath(expression: &str, body: &str) -> Result<String, String> {
+    let package = sxd_document::parser::parse(body)
+        .map_err(|e| format!("Response body is not valid XML: {}", e))?;
+    let document = package.as_document();
+    let value = sxd_xpath::evaluate_xpath(&document, expression)
+        .map_err(|e| format!("Invalid XPath: {}", e))?;
+
+    match value {
+        XPathValue::Nodeset(nodes) => nodes
+            .document_order_first()
+            .map(|node| node.string_value())
+            .ok_or_else(|| "No match".to_string()),
+        XPathValue::String(s) => Ok(s),
+        XPathValue::Number(n) => Ok(n.to_string()),
+        XPathValue::Boolean(b) => Ok(b.to_string()),
+    }
+}
+
+fn regex(pattern: &str, group: Option<usize>, body: &str) -> Result<String, String> {
+    let re = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+    let group = group.unwrap_or(if re.captures_len() > 1 { 1 } else { 0 });
+    let captures = re.captures(body).ok_or_else(|| "No match".to_string())?;
+
+    captures
+        .get(group)
+        .map(|m| m.as_str().to_string())
+        .ok_or_else(|| format!("Group {} did not match", group))
+}
+
+/// Value of `name` in a `Set-Cookie` header, e.g. `session=abc; Path=/; HttpOnly`
+fn cookie_value(header: &str, name: &str) -> Option<String> {
+    let pair = header.split(';').next()?;
+    let (key, value) = pair.split_once('=')?;
+    (key.trim() == name).then(|| value.trim().trim_matches('"').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpResponseHeader;
+
+    fn response(body: &str) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            status_text: "OK".to_string(),
+            headers: vec![
+                HttpResponseHeader {
+                    key: "Set-Cookie".to_string(),
+                    value: "theme=dark; Path=/".to_string(),
+                },
+                HttpResponseHeader {
+                    key: "set-cookie".to_string(),
+                    value: "session=abc123; Path=/; HttpOnly".to_string(),
+                },
+            ],
+            http_version: "HTTP/1.1".to_string(),
+            body: body.to_string(),
+            time_ms: 5,
+            size_bytes: body.len(),
+            assertions: Vec::new(),
+            extractions: Vec::new(),
+            console: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_extract_sources() {
+        let json = response(r#"{"auth": {"token": "t-1", "ttl": 60}}"#);
+        let xml = response("<user><id>7</id><name>Ada</name></user>");
+
+        let extract_ok = |source: ExtractionSource, response: &HttpResponse| {
+            extract(&source, response).expect("Extraction failed")
+        };
+        assert_eq!(
+            extract_ok(ExtractionSource::JsonPath { path: "$.auth.token".to_string() }, &json),
+            "t-1"
+        );
+        assert_eq!(
+            extract_ok(ExtractionSource::JsonPath { path: "$.auth.ttl".to_string() }, &json),
+            "60"
+        );
+        assert_eq!(
+            extract_ok(ExtractionSource::XPath { expression: "/user/name".to_string() }, &xml),
+            "Ada"
+        );
+        assert_eq!(
+            extract_ok(
+                ExtractionSource::Regex {
+                    pattern: r#""token": "([^"]+)""#.to_string(),
+                    group: None,
+                },
+                &json
+            ),
+            "t-1"
+        );
+        assert_eq!(
+            extract_ok(ExtractionSource::Cookie { name: "session".to_string() }, &json),
+            "abc123"
+        );
+        assert_eq!(extract_ok(ExtractionSource::Status, &json), "200");
+
+        assert!(extract(&ExtractionSource::JsonPath { path: "$.missing".to_string() }, &json)
+            .is_err());
+        assert!(extract(&ExtractionSource::XPath { expression: "/user".to_string() }, &json)
+            .is_err());
+    }
+}
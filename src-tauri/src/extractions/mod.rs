@@ -0,0 +1,12 @@
+//! Response value extraction
+//!
+//! Rules stored on a request that copy values from its responses into variables,
+//! so later requests can use them (e.g. a login token).
+
+mod evaluate;
+mod service;
+mod types;
+
+#[allow(unused_imports)]
+pub use types::{Extraction, ExtractionResult, ExtractionSource};
+pub(crate) use service::ExtractionService;
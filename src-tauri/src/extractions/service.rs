@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use crate::db::{Database, DbError, DbResult};
+use crate::http::HttpResponse;
+use crate::requests::Request;
+use crate::variables::{template, VariableScope, VariableService};
+
+use super::evaluate::extract;
+use super::types::ExtractionResult;
+
+pub struct ExtractionService {
+    db: Database,
+}
+
+impl ExtractionService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Run the request's extraction rules against its response and save each value
+    /// found. `variables` is updated so later steps of the same send see them.
+    pub fn apply(
+        &self,
+        request: &Request,
+        response: &HttpResponse,
+        variables: &mut HashMap<String, String>,
+    ) -> DbResult<Vec<ExtractionResult>> {
+        let service = VariableService::new(self.db.clone());
+        let environment_id = service.active_environment(&request.workspace_id)?;
+        let mut results = Vec::new();
+
+        for extraction in request.extractions.iter().filter(|e| e.enabled) {
+            let mut result = ExtractionResult {
+                variable: extraction.variable.clone(),
+                value: None,
+                error: None,
+            };
+
+            let scope_id = match extraction.scope {
+                VariableScope::Global => Ok(None),
+                VariableScope::Workspace => Ok(Some(request.workspace_id.as_str())),
+                VariableScope::Environment => environment_id
+                    .as_deref()
+                    .map(Some)
+                    .ok_or_else(|| "Workspace has no active environment".to_string()),
+                VariableScope::Collection => request
+                    .collection_id
+                    .as_deref()
+                    .map(Some)
+                    .ok_or_else(|| "Request is not in a collection".to_string()),
+                VariableScope::Request => Ok(Some(request.id.as_str())),
+            };
+
+            match scope_id.and_then(|scope_id| {
+                extract(&extraction.source, response).map(|value| (scope_id, value))
+            }) {
+                Ok((scope_id, value)) => match service.set_in_scope(
+                    extraction.scope.clone(),
+                    scope_id,
+                    &extraction.variable,
+                    value.clone(),
+                ) {
+                    Ok(variable) => {
+                        result.value = Some(if variable.is_secret {
+                            template::SECRET_MASK.to_string()
+                        } else {
+                            value.clone()
+                        });
+                        variables.insert(extraction.variable.clone(), value);
+                    }
+                    // e.g. a value that doesn't fit the variable's type
+                    Err(DbError::InvalidInput(e)) => result.error = Some(e),
+                    Err(e) => return Err(e),
+                },
+                Err(e) => result.error = Some(e),
+            }
+
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::variables::VariableScope;
+
+/// Copies a value out of each response into a variable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Extraction {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Name of the variable to write
+    pub variable: String,
+    /// Where to write it; the scope ID comes from the request (its workspace, the
+    /// workspace's active environment, its collection or itself)
+    #[serde(default = "default_scope")]
+    pub scope: VariableScope,
+    #[serde(flatten)]
+    pub source: ExtractionSource,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_scope() -> VariableScope {
+    VariableScope::Workspace
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "kebab-case")]
+pub enum ExtractionSource {
+    /// RFC 9535 JSONPath into a JSON body; the first match is used
+    JsonPath { path: String },
+    /// XPath 1.0 into an XML body; node sets yield the first node's text
+    #[serde(rename = "xpath")]
+    XPath { expression: String },
+    /// Regular expression over the body. Uses `group`, or the first capture
+    /// group when the pattern has one and the whole match otherwise.
+    Regex {
+        pattern: String,
+        #[serde(default)]
+        group: Option<usize>,
+    },
+    Header { name: String },
+    /// Value of a cookie set by the response
+    Cookie { name: String },
+    Status,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionResult {
+    pub variable: String,
+    /// Extracted value, masked when the variable is secret
+    pub value: Option<String>,
+    pub error: Option<String>,
+}
@@ -0,0 +1,51 @@
+use tauri::State;
+
+use crate::db::Database;
+
+use super::service::FolderService;
+use super::types::{CreateFolderInput, Folder, MoveFolderInput, UpdateFolderInput};
+
+#[tauri::command]
+pub fn get_folder(db: State<Database>, id: String) -> Result<Folder, String> {
+    let service = FolderService::new(db.inner().clone());
+    service.get(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_folders_by_collection(
+    db: State<Database>,
+    collection_id: String,
+) -> Result<Vec<Folder>, String> {
+    let service = FolderService::new(db.inner().clone());
+    service.get_by_collection(&collection_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn create_folder(db: State<Database>, input: CreateFolderInput) -> Result<Folder, String> {
+    let service = FolderService::new(db.inner().clone());
+    service.create(input).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_folder(db: State<Database>, input: UpdateFolderInput) -> Result<Folder, String> {
+    let service = FolderService::new(db.inner().clone());
+    service.update(input).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn move_folder(db: State<Database>, input: MoveFolderInput) -> Result<Folder, String> {
+    let service = FolderService::new(db.inner().clone());
+    service.move_folder(input).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn reorder_folders(db: State<Database>, ids: Vec<String>) -> Result<(), String> {
+    let service = FolderService::new(db.inner().clone());
+    service.reorder(&ids).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_folder(db: State<Database>, id: String) -> Result<(), String> {
+    let service = FolderService::new(db.inner().clone());
+    service.delete(&id).map_err(|e| e.to_string())
+}
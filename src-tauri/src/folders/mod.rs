@@ -0,0 +1,8 @@
+mod commands;
+mod service;
+mod types;
+
+pub use commands::*;
+#[allow(unused_imports)]
+pub use types::{CreateFolderInput, Folder, MoveFolderInput, UpdateFolderInput};
+pub(crate) use service::FolderService;
@@ -0,0 +1,429 @@
+use chrono::Utc;
+use redb::WriteTransaction;
+
+use crate::collections::Collection;
+use crate::db::helpers::{
+    get_row, index_add, index_clear, index_get, index_remove, put_row, remove_row,
+};
+use crate::db::{
+    Database, DbError, DbResult, COLLECTIONS, FOLDERS, FOLDERS_BY_COLLECTION, REQUESTS,
+    REQUESTS_BY_COLLECTION, REQUESTS_BY_FOLDER,
+};
+use crate::requests::{Request, RequestService};
+
+use super::types::{CreateFolderInput, Folder, MoveFolderInput, UpdateFolderInput};
+
+pub struct FolderService {
+    db: Database,
+}
+
+impl FolderService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub fn get(&self, id: &str) -> DbResult<Folder> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(FOLDERS)?;
+
+        let value = table
+            .get(id)?
+            .ok_or_else(|| DbError::NotFound(format!("Folder not found: {}", id)))?;
+
+        let folder: Folder = serde_json::from_str(value.value())
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(folder)
+    }
+
+    /// All folders of a collection, at every nesting level
+    pub fn get_by_collection(&self, collection_id: &str) -> DbResult<Vec<Folder>> {
+        let read_txn = self.db.begin_read()?;
+        let idx_table = read_txn.open_table(FOLDERS_BY_COLLECTION)?;
+
+        let folder_ids: Vec<String> = match idx_table.get(collection_id)? {
+            Some(value) => serde_json::from_str(value.value())
+                .map_err(|e| DbError::Serialization(e.to_string()))?,
+            None => return Ok(Vec::new()),
+        };
+
+        drop(idx_table);
+        drop(read_txn);
+
+        let mut folders = Vec::new();
+        for id in folder_ids {
+            if let Ok(folder) = self.get(&id) {
+                folders.push(folder);
+            }
+        }
+
+        folders.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(folders)
+    }
+
+    pub fn create(&self, input: CreateFolderInput) -> DbResult<Folder> {
+        let write_txn = self.db.begin_write()?;
+
+        let collection = Self::collection_in(&write_txn, &input.collection_id)?;
+        if let Some(ref parent_id) = input.parent_id {
+            let parent = Self::folder_in(&write_txn, parent_id)?;
+            if parent.collection_id != collection.id {
+                return Err(DbError::InvalidInput(format!(
+                    "Parent folder {} belongs to another collection",
+                    parent_id
+                )));
+            }
+        }
+
+        let mut folder = Folder::new(
+            input.name,
+            collection.id.clone(),
+            input.parent_id,
+            collection.workspace_id,
+        );
+        folder.sort_order = Self::next_sort_order_in(&write_txn, &folder)?;
+
+        put_row(&write_txn, FOLDERS, &folder.id, &folder)?;
+        index_add(&write_txn, FOLDERS_BY_COLLECTION, &collection.id, &folder.id)?;
+
+        write_txn.commit()?;
+
+        Ok(folder)
+    }
+
+    pub fn update(&self, input: UpdateFolderInput) -> DbResult<Folder> {
+        let mut folder = self.get(&input.id)?;
+
+        if let Some(name) = input.name {
+            folder.name = name;
+        }
+        folder.updated_at = Utc::now();
+
+        let json = serde_json::to_string(&folder)
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(FOLDERS)?;
+            table.insert(folder.id.as_str(), json.as_str())?;
+        }
+        write_txn.commit()?;
+
+        Ok(folder)
+    }
+
+    /// Re-parent a folder. Moving to another collection carries every nested
+    /// folder and request along and rewrites their collection indexes.
+    pub fn move_folder(&self, input: MoveFolderInput) -> DbResult<Folder> {
+        let mut folder = self.get(&input.id)?;
+
+        let write_txn = self.db.begin_write()?;
+
+        let collection = Self::collection_in(&write_txn, &input.collection_id)?;
+        if collection.workspace_id != folder.workspace_id {
+            return Err(DbError::InvalidInput(
+                "Folders cannot be moved to a collection in another workspace".to_string(),
+            ));
+        }
+
+        let subtree = Self::subtree_in(&write_txn, &folder)?;
+
+        if let Some(ref parent_id) = input.parent_id {
+            if subtree.iter().any(|f| &f.id == parent_id) {
+                return Err(DbError::InvalidInput(
+                    "A folder cannot be moved into itself or one of its subfolders".to_string(),
+                ));
+            }
+            let parent = Self::folder_in(&write_txn, parent_id)?;
+            if parent.collection_id != collection.id {
+                return Err(DbError::InvalidInput(format!(
+                    "Parent folder {} belongs to another collection",
+                    parent_id
+                )));
+            }
+        }
+
+        if collection.id != folder.collection_id {
+            let now = Utc::now();
+            for nested in &subtree {
+                let old_collection_id = nested.collection_id.as_str();
+                index_remove(&write_txn, FOLDERS_BY_COLLECTION, old_collection_id, &nested.id)?;
+                index_add(&write_txn, FOLDERS_BY_COLLECTION, &collection.id, &nested.id)?;
+
+                for request_id in index_get(&write_txn, REQUESTS_BY_FOLDER, &nested.id)? {
+                    let Some(mut request) = get_row::<Request>(&write_txn, REQUESTS, &request_id)?
+                    else {
+                        continue;
+                    };
+                    if let Some(ref old_id) = request.collection_id {
+                        index_remove(&write_txn, REQUESTS_BY_COLLECTION, old_id, &request.id)?;
+                    }
+                    index_add(&write_txn, REQUESTS_BY_COLLECTION, &collection.id, &request.id)?;
+                    request.collection_id = Some(collection.id.clone());
+                    request.updated_at = now;
+                    put_row(&write_txn, REQUESTS, &request.id, &request)?;
+                }
+
+                if nested.id != folder.id {
+                    let mut moved = nested.clone();
+                    moved.collection_id = collection.id.clone();
+                    moved.updated_at = now;
+                    put_row(&write_txn, FOLDERS, &moved.id, &moved)?;
+                }
+            }
+        }
+
+        let changed = folder.collection_id != collection.id || folder.parent_id != input.parent_id;
+        folder.collection_id = collection.id;
+        folder.parent_id = input.parent_id;
+        if changed {
+            folder.sort_order = Self::next_sort_order_in(&write_txn, &folder)?;
+        }
+        folder.updated_at = Utc::now();
+        put_row(&write_txn, FOLDERS, &folder.id, &folder)?;
+
+        write_txn.commit()?;
+
+        Ok(folder)
+    }
+
+    /// Set the position of each folder to its index in `ids`, in one transaction
+    pub fn reorder(&self, ids: &[String]) -> DbResult<()> {
+        let write_txn = self.db.begin_write()?;
+        let now = Utc::now();
+
+        let mut folders = Vec::new();
+        for id in ids {
+            folders.push(Self::folder_in(&write_txn, id)?);
+        }
+        if folders.windows(2).any(|pair| !Self::are_siblings(&pair[0], &pair[1])) {
+            return Err(DbError::InvalidInput(
+                "Only folders with the same parent can be reordered together".to_string(),
+            ));
+        }
+
+        for (position, mut folder) in folders.into_iter().enumerate() {
+            folder.sort_order = position as i64;
+            folder.updated_at = now;
+            put_row(&write_txn, FOLDERS, &folder.id, &folder)?;
+        }
+
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Delete a folder together with its subfolders and the requests inside them
+    pub fn delete(&self, id: &str) -> DbResult<()> {
+        let folder = self.get(id)?;
+
+        let write_txn = self.db.begin_write()?;
+        Self::delete_tree_in(&write_txn, &folder)?;
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Cascading delete of a folder subtree inside an open transaction
+    pub(crate) fn delete_tree_in(write_txn: &WriteTransaction, folder: &Folder) -> DbResult<()> {
+        for nested in Self::subtree_in(write_txn, folder)? {
+            for request_id in index_get(write_txn, REQUESTS_BY_FOLDER, &nested.id)? {
+                if let Some(request) = get_row::<Request>(write_txn, REQUESTS, &request_id)? {
+                    RequestService::delete_in(write_txn, &request)?;
+                }
+            }
+            index_clear(write_txn, REQUESTS_BY_FOLDER, &nested.id)?;
+            remove_row(write_txn, FOLDERS, &nested.id)?;
+            index_remove(write_txn, FOLDERS_BY_COLLECTION, &nested.collection_id, &nested.id)?;
+        }
+        Ok(())
+    }
+
+    /// The folder followed by all of its descendants, parents before children
+    fn subtree_in(write_txn: &WriteTransaction, root: &Folder) -> DbResult<Vec<Folder>> {
+        let mut siblings = Vec::new();
+        for id in index_get(write_txn, FOLDERS_BY_COLLECTION, &root.collection_id)? {
+            if let Some(folder) = get_row::<Folder>(write_txn, FOLDERS, &id)? {
+                siblings.push(folder);
+            }
+        }
+
+        let mut subtree = vec![root.clone()];
+        let mut next = 0;
+        while next < subtree.len() {
+            let parent_id = subtree[next].id.clone();
+            subtree.extend(
+                siblings
+                    .iter()
+                    .filter(|f| f.parent_id.as_deref() == Some(parent_id.as_str()))
+                    .cloned(),
+            );
+            next += 1;
+        }
+
+        Ok(subtree)
+    }
+
+    /// A position after every other folder under the same parent
+    fn next_sort_order_in(write_txn: &WriteTransaction, folder: &Folder) -> DbResult<i64> {
+        let mut next = 0;
+        for id in index_get(write_txn, FOLDERS_BY_COLLECTION, &folder.collection_id)? {
+            if id == folder.id {
+                continue;
+            }
+            if let Some(sibling) = get_row::<Folder>(write_txn, FOLDERS, &id)? {
+                if Self::are_siblings(&sibling, folder) {
+                    next = next.max(sibling.sort_order + 1);
+                }
+            }
+        }
+        Ok(next)
+    }
+
+    fn are_siblings(a: &Folder, b: &Folder) -> bool {
+        a.collection_id == b.collection_id && a.parent_id == b.parent_id
+    }
+
+    fn folder_in(write_txn: &WriteTransaction, folder_id: &str) -> DbResult<Folder> {
+        get_row(write_txn, FOLDERS, folder_id)?
+            .ok_or_else(|| DbError::NotFound(format!("Folder not found: {}", folder_id)))
+    }
+
+    fn collection_in(write_txn: &WriteTransaction, collection_id: &str) -> DbResult<Collection> {
+        get_row(write_txn, COLLECTIONS, collection_id)?
+            .ok_or_else(|| DbError::NotFound(format!("Collection not found: {}", collection_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::{CollectionService, CreateCollectionInput};
+    use crate::requests::CreateRequestInput;
+    use std::env::temp_dir;
+
+    fn create_test_db() -> Database {
+        let path = temp_dir().join(format!("resona_test_{}.redb", uuid::Uuid::new_v4()));
+        Database::open_at(path).expect("Failed to create test database")
+    }
+
+    fn create_collection(db: &Database, name: &str) -> Collection {
+        CollectionService::new(db.clone())
+            .create(CreateCollectionInput {
+                name: name.to_string(),
+                workspace_id: "ws".to_string(),
+                ..Default::default()
+            })
+            .expect("Failed to create collection")
+    }
+
+    #[test]
+    fn test_nested_folders_move_and_cascade() {
+        let db = create_test_db();
+        let folders = FolderService::new(db.clone());
+        let requests = RequestService::new(db.clone());
+
+        let source = create_collection(&db, "Source");
+        let target = create_collection(&db, "Target");
+
+        let outer = folders
+            .create(CreateFolderInput {
+                name: "Users".to_string(),
+                collection_id: source.id.clone(),
+                parent_id: None,
+            })
+            .expect("Failed to create folder");
+        let inner = folders
+            .create(CreateFolderInput {
+                name: "Admin".to_string(),
+                collection_id: source.id.clone(),
+                parent_id: Some(outer.id.clone()),
+            })
+            .expect("Failed to create nested folder");
+
+        let request = requests
+            .create(CreateRequestInput {
+                name: "List admins".to_string(),
+                url: "https://example.com/admins".to_string(),
+                collection_id: Some(source.id.clone()),
+                folder_id: Some(inner.id.clone()),
+                workspace_id: "ws".to_string(),
+                ..Default::default()
+            })
+            .expect("Failed to create request");
+
+        // A folder cannot become its own descendant
+        let cycle = folders.move_folder(MoveFolderInput {
+            id: outer.id.clone(),
+            collection_id: source.id.clone(),
+            parent_id: Some(inner.id.clone()),
+        });
+        assert!(matches!(cycle, Err(DbError::InvalidInput(_))));
+
+        folders
+            .move_folder(MoveFolderInput {
+                id: outer.id.clone(),
+                collection_id: target.id.clone(),
+                parent_id: None,
+            })
+            .expect("Failed to move folder");
+
+        let moved = requests.get(&request.id).expect("Request should still exist");
+        assert_eq!(moved.collection_id, Some(target.id.clone()));
+        assert!(requests.get_by_collection(&source.id).unwrap().is_empty());
+        assert_eq!(requests.get_by_collection(&target.id).unwrap().len(), 1);
+        assert_eq!(folders.get_by_collection(&target.id).unwrap().len(), 2);
+
+        folders.delete(&outer.id).expect("Failed to delete folder");
+
+        assert!(folders.get(&inner.id).is_err());
+        assert!(requests.get(&request.id).is_err());
+        assert!(requests.get_by_collection(&target.id).unwrap().is_empty());
+        assert!(requests.get_all_by_workspace("ws").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sort_order_and_reorder_follow_the_parent() {
+        let db = create_test_db();
+        let folders = FolderService::new(db.clone());
+        let collection = create_collection(&db, "Shop");
+        let folder = |name: &str, parent_id: Option<&String>| CreateFolderInput {
+            name: name.to_string(),
+            collection_id: collection.id.clone(),
+            parent_id: parent_id.cloned(),
+        };
+
+        let users = folders.create(folder("Users", None)).expect("Failed to create folder");
+        let orders = folders.create(folder("Orders", None)).expect("Failed to create folder");
+        let admins = folders
+            .create(folder("Admins", Some(&users.id)))
+            .expect("Failed to create folder");
+        assert_eq!((users.sort_order, orders.sort_order, admins.sort_order), (0, 1, 0));
+
+        folders.delete(&users.id).expect("Failed to delete folder");
+        let billing = folders.create(folder("Billing", None)).expect("Failed to create folder");
+        assert_eq!(billing.sort_order, 2);
+
+        let nested = folders
+            .create(folder("Refunds", Some(&billing.id)))
+            .expect("Failed to create folder");
+        let mixed = folders.reorder(&[orders.id.clone(), nested.id.clone()]);
+        assert!(matches!(mixed, Err(DbError::InvalidInput(_))));
+
+        folders
+            .reorder(&[billing.id.clone(), orders.id.clone()])
+            .expect("Failed to reorder folders");
+        let names: Vec<String> = folders
+            .get_by_collection(&collection.id)
+            .unwrap()
+            .into_iter()
+            .filter(|f| f.parent_id.is_none())
+            .map(|f| f.name)
+            .collect();
+        assert_eq!(names, vec!["Billing".to_string(), "Orders".to_string()]);
+    }
+}
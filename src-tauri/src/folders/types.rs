@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Folder {
+    pub id: String,
+    pub name: String,
+    pub collection_id: String,
+    /// Enclosing folder, or None for a folder at the collection root
+    pub parent_id: Option<String>,
+    pub workspace_id: String,
+    /// Position among sibling folders
+    #[serde(default)]
+    pub sort_order: i64,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Folder {
+    pub fn new(
+        name: String,
+        collection_id: String,
+        parent_id: Option<String>,
+        workspace_id: String,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            collection_id,
+            parent_id,
+            workspace_id,
+            sort_order: 0,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFolderInput {
+    pub name: String,
+    pub collection_id: String,
+    pub parent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateFolderInput {
+    pub id: String,
+    pub name: Option<String>,
+}
+
+/// Move a folder (with everything inside it) under another folder or to a collection root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveFolderInput {
+    pub id: String,
+    pub collection_id: String,
+    pub parent_id: Option<String>,
+}
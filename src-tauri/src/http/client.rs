@@ -1,11 +1,72 @@
-use std::time::Instant;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use reqwest::{header::HeaderMap, Client, Method, Version};
+
+use super::types::{ClientOptions, HttpRequest, HttpResponse, HttpResponseHeader, HttpVersion};
+
+/// Build a client for a single request, applying its protocol, timeout and TLS
+/// settings and any resolve overrides that match its URL
+fn build_client(
+    url: &str,
+    request: &HttpRequest,
+    options: &ClientOptions,
+) -> Result<Client, String> {
+    let mut builder = Client::builder();
+
+    match request.http_version {
+        HttpVersion::Auto => {}
+        HttpVersion::Http1Only => builder = builder.http1_only(),
+        HttpVersion::Http2PriorKnowledge => builder = builder.http2_prior_knowledge(),
+    }
 
-use reqwest::{header::HeaderMap, Client, Method};
+    if let Some(timeout_ms) = request.timeout_ms {
+        builder = builder.timeout(Duration::from_millis(timeout_ms));
+    }
+    if request.validate_ssl == Some(false) {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
 
-use super::types::{HttpRequest, HttpResponse, HttpResponseHeader};
+    if !options.resolve_overrides.is_empty() {
+        let parsed =
+            reqwest::Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+        let host = parsed.host_str().unwrap_or("");
+        let port = parsed.port_or_known_default();
+
+        for entry in &options.resolve_overrides {
+            // reqwest ignores the port of an override address, so only apply
+            // entries that match the port this request actually connects to
+            if !entry.host.eq_ignore_ascii_case(host) || Some(entry.port) != port {
+                continue;
+            }
+            let ip: IpAddr = entry.address.parse().map_err(|_| {
+                format!("Invalid resolve address for {}: {}", entry.host, entry.address)
+            })?;
+            builder = builder.resolve(host, SocketAddr::new(ip, entry.port));
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+/// Protocol version as shown to users and scripts, e.g. "HTTP/1.1"
+fn version_name(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_11 => "HTTP/1.1",
+        Version::HTTP_2 => "HTTP/2.0",
+        Version::HTTP_3 => "HTTP/3.0",
+        _ => "unknown",
+    }
+}
 
-pub async fn execute_request(request: HttpRequest) -> Result<HttpResponse, String> {
-    let client = Client::new();
+pub async fn execute_request(
+    request: HttpRequest,
+    options: &ClientOptions,
+) -> Result<HttpResponse, String> {
     let start = Instant::now();
 
     // Parse method
@@ -56,6 +117,7 @@ pub async fn execute_request(request: HttpRequest) -> Result<HttpResponse, Strin
     }
 
     // Build request
+    let client = build_client(&url, &request, options)?;
     let mut req_builder = client.request(method, &url).headers(headers);
 
     // Add body based on body type
@@ -138,6 +200,7 @@ pub async fn execute_request(request: HttpRequest) -> Result<HttpResponse, Strin
 
     let elapsed = start.elapsed();
     let status = response.status().as_u16();
+    let http_version = version_name(response.version()).to_string();
     let status_text = response
         .status()
         .canonical_reason()
@@ -167,8 +230,12 @@ pub async fn execute_request(request: HttpRequest) -> Result<HttpResponse, Strin
         status,
         status_text,
         headers: response_headers,
+        http_version,
         body,
         time_ms: elapsed.as_millis() as u64,
         size_bytes,
+        assertions: Vec::new(),
+        extractions: Vec::new(),
+        console: Vec::new(),
     })
 }
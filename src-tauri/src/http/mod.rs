@@ -2,4 +2,7 @@ mod client;
 mod types;
 
 pub use client::execute_request;
-pub use types::{HttpRequest, HttpResponse, HttpResponseHeader};
+pub use types::{
+    ClientOptions, HttpFormDataItem, HttpRequest, HttpRequestHeader, HttpRequestParam,
+    HttpResponse, HttpResponseHeader, HttpVersion, ResolveOverride,
+};
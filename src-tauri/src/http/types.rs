@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use crate::assertions::{Assertion, AssertionResult};
+use crate::extractions::ExtractionResult;
+use crate::scripts::ConsoleEntry;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpRequest {
     pub method: String,
@@ -13,6 +17,29 @@ pub struct HttpRequest {
     pub body: String,
     #[serde(default)]
     pub form_data: Vec<HttpFormDataItem>,
+    #[serde(default)]
+    pub http_version: HttpVersion,
+    /// Total request timeout; the client default applies when unset
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Set to false to accept invalid TLS certificates
+    #[serde(default)]
+    pub validate_ssl: Option<bool>,
+    /// Checked against the response once it arrives
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+}
+
+/// Which HTTP protocol version the client is allowed to use
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum HttpVersion {
+    /// Let ALPN negotiate (HTTP/1.1 for cleartext)
+    #[default]
+    Auto,
+    Http1Only,
+    /// Speak HTTP/2 immediately, without upgrade or ALPN (h2c over cleartext)
+    Http2PriorKnowledge,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,14 +65,40 @@ pub struct HttpFormDataItem {
     pub enabled: bool,
 }
 
+/// Options applied when building the HTTP client rather than the request itself
+#[derive(Debug, Clone, Default)]
+pub struct ClientOptions {
+    pub resolve_overrides: Vec<ResolveOverride>,
+}
+
+/// Pins `host:port` to a fixed address, like curl's `--resolve host:port:address`.
+/// The URL is left untouched so the Host header and TLS SNI keep the original name.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResolveOverride {
+    pub host: String,
+    pub port: u16,
+    pub address: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpResponse {
     pub status: u16,
     pub status_text: String,
     pub headers: Vec<HttpResponseHeader>,
+    /// Protocol version actually used for the exchange, e.g. "HTTP/1.1" or "HTTP/2.0"
+    pub http_version: String,
     pub body: String,
     pub time_ms: u64,
     pub size_bytes: usize,
+    /// Outcome of the request's assertions, in order
+    #[serde(default)]
+    pub assertions: Vec<AssertionResult>,
+    /// Values saved to variables by the request's extraction rules
+    #[serde(default)]
+    pub extractions: Vec<ExtractionResult>,
+    /// Output of the scripts that ran around the send
+    #[serde(default)]
+    pub console: Vec<ConsoleEntry>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
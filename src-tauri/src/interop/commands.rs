@@ -0,0 +1,53 @@
+use tauri::State;
+
+use crate::db::Database;
+use crate::variables::SecretExport;
+
+use super::service::InteropService;
+use super::types::{ImportFileInput, ImportReport};
+
+/// Import a Postman v2.1 collection file into a new or existing workspace
+#[tauri::command]
+pub fn import_postman_collection(
+    db: State<Database>,
+    input: ImportFileInput,
+) -> Result<ImportReport, String> {
+    let service = InteropService::new(db.inner().clone());
+    service.import_postman(input).map_err(|e| e.to_string())
+}
+
+/// Import an OpenAPI 3.x or Swagger 2.0 document (JSON or YAML) as a collection
+#[tauri::command]
+pub fn import_openapi_spec(
+    db: State<Database>,
+    input: ImportFileInput,
+) -> Result<ImportReport, String> {
+    let service = InteropService::new(db.inner().clone());
+    service.import_openapi(input).map_err(|e| e.to_string())
+}
+
+/// A collection as Postman v2.1 JSON; secrets are left out unless `secrets` says otherwise
+#[tauri::command]
+pub fn export_postman_collection(
+    db: State<Database>,
+    collection_id: String,
+    secrets: Option<SecretExport>,
+) -> Result<String, String> {
+    let service = InteropService::new(db.inner().clone());
+    service
+        .export_postman_collection(&collection_id, secrets.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// A whole workspace as one Postman v2.1 collection
+#[tauri::command]
+pub fn export_postman_workspace(
+    db: State<Database>,
+    workspace_id: String,
+    secrets: Option<SecretExport>,
+) -> Result<String, String> {
+    let service = InteropService::new(db.inner().clone());
+    service
+        .export_postman_workspace(&workspace_id, secrets.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,18 @@
+//! Interop module
+//!
+//! Imports collections from other tools' formats and exports them back. Every
+//! import is parsed into an `ImportTree` first and written in a single
+//! transaction, so a file that fails halfway leaves nothing behind.
+
+mod commands;
+mod openapi;
+mod postman;
+mod service;
+mod tree;
+mod types;
+
+pub use commands::*;
+#[allow(unused_imports)]
+pub use types::{ImportFileInput, ImportReport, UnsupportedItem};
+#[allow(unused_imports)]
+pub(crate) use service::InteropService;
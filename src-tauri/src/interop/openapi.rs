@@ -0,0 +1,697 @@
+//! OpenAPI 3.x and Swagger 2.0 import, from JSON or YAML
+//!
+//! Every operation becomes a request in a folder named after its first tag;
+//! untagged operations sit at the collection root. The server URL is stored
+//! as a workspace variable named after the API, such as `petstore_base_url`,
+//! and used as the collection's base URL, so request URLs are the spec's
+//! paths. Parameters and bodies are filled from the spec's examples, falling
+//! back to values generated from the schema.
+//! Security schemes become auth that reads credentials from workspace
+//! variables, created empty for the user to fill in.
+
+use serde_json::{json, Map, Value};
+
+use crate::collections::Collection;
+use crate::db::{DbError, DbResult};
+use crate::folders::Folder;
+use crate::interop::tree::ImportTree;
+use crate::interop::types::UnsupportedItem;
+use crate::requests::{
+    ApiKeyLocation, BodyType, FormDataItem, HttpMethod, Request, RequestAuth, RequestHeader,
+    RequestParam,
+};
+use crate::variables::{Variable, VariableScope};
+use crate::workspaces::Workspace;
+
+/// How deep `$ref`s and generated examples are followed, so recursive schemas end
+const MAX_DEPTH: usize = 8;
+
+const METHODS: [(&str, HttpMethod); 7] = [
+    ("get", HttpMethod::Get),
+    ("put", HttpMethod::Put),
+    ("post", HttpMethod::Post),
+    ("delete", HttpMethod::Delete),
+    ("options", HttpMethod::Options),
+    ("head", HttpMethod::Head),
+    ("patch", HttpMethod::Patch),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Version {
+    Swagger2,
+    OpenApi3,
+}
+
+struct Importer<'a> {
+    spec: &'a Value,
+    version: Version,
+    tree: ImportTree,
+    collection_id: String,
+    workspace_id: String,
+    unsupported: Vec<UnsupportedItem>,
+    /// Folder id per tag, created on first use
+    folders: Vec<(String, String)>,
+    /// Next request position per folder (`None` for the collection root)
+    positions: Vec<(Option<String>, i64)>,
+}
+
+/// Parse an OpenAPI or Swagger document into records for `workspace`, or for
+/// a new workspace named after the API
+pub fn import(
+    content: &str,
+    workspace: Option<&Workspace>,
+) -> DbResult<(ImportTree, Vec<UnsupportedItem>)> {
+    let spec: Value = match serde_json::from_str(content) {
+        Ok(spec) => spec,
+        Err(_) => serde_yaml_ng::from_str(content)
+            .map_err(|e| DbError::InvalidInput(format!("Not a JSON or YAML document: {}", e)))?,
+    };
+
+    let openapi = spec.get("openapi").and_then(Value::as_str);
+    let version = if openapi.is_some_and(|v| v.starts_with("3.")) {
+        Version::OpenApi3
+    } else if spec.get("swagger").and_then(Value::as_str) == Some("2.0") {
+        Version::Swagger2
+    } else {
+        return Err(DbError::InvalidInput(
+            "Not an OpenAPI 3.x or Swagger 2.0 document".to_string(),
+        ));
+    };
+
+    let title = spec
+        .pointer("/info/title")
+        .and_then(Value::as_str)
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or("API")
+        .to_string();
+    let description = text(spec.pointer("/info/description"));
+
+    let mut tree = ImportTree::default();
+    let workspace_id = match workspace {
+        Some(workspace) => workspace.id.clone(),
+        None => {
+            let workspace = Workspace::new(title.clone(), description.clone());
+            let id = workspace.id.clone();
+            tree.workspace = Some(workspace);
+            id
+        }
+    };
+    tree.workspace_id = workspace_id.clone();
+
+    let mut collection = Collection::new(title, description, workspace_id.clone());
+    let mut importer = Importer {
+        spec: &spec,
+        version,
+        collection_id: collection.id.clone(),
+        workspace_id,
+        tree,
+        unsupported: Vec::new(),
+        folders: Vec::new(),
+        positions: Vec::new(),
+    };
+
+    // Named after the API, so APIs imported into one workspace keep their
+    // own servers
+    let base_url = format!("{}_base_url", slug(&collection.name));
+    let server = importer.server_url(&base_url);
+    collection.defaults.base_url = importer.workspace_variable(&base_url, &server, false);
+
+    // Security that applies to every operation that doesn't set its own
+    let default_security = spec.get("security");
+    if let Some(auth) = default_security.and_then(|s| importer.security(s, &[])) {
+        collection.defaults.auth = auth;
+    }
+    importer.tree.collections.push(collection);
+
+    // Folders follow the order of the top-level tag list, then first use
+    if let Some(tags) = spec.get("tags").and_then(Value::as_array) {
+        for tag in tags {
+            if let Some(name) = tag.get("name").and_then(Value::as_str) {
+                importer.folder(name);
+            }
+        }
+    }
+
+    if let Some(paths) = spec.get("paths").and_then(Value::as_object) {
+        for (path, item) in paths {
+            let item = importer.resolve(item);
+            for (key, operation) in item.as_object().into_iter().flatten() {
+                let Some((_, method)) = METHODS.iter().find(|(name, _)| name == key) else {
+                    if key == "trace" {
+                        importer.report(&[format!("TRACE {}", path)], "TRACE is not supported");
+                    }
+                    continue;
+                };
+                importer.operation(path, item, key, method.clone(), operation, default_security);
+            }
+        }
+    }
+
+    // Folders nobody used (a tag listed without operations) are dropped
+    let used: Vec<String> = importer
+        .tree
+        .requests
+        .iter()
+        .filter_map(|r| r.folder_id.clone())
+        .collect();
+    importer.tree.folders.retain(|f| used.contains(&f.id));
+    for (position, folder) in importer.tree.folders.iter_mut().enumerate() {
+        folder.sort_order = position as i64;
+    }
+
+    Ok((importer.tree, importer.unsupported))
+}
+
+impl<'a> Importer<'a> {
+    /// The first server, with its variables set to their defaults
+    fn server_url(&mut self, variable: &str) -> String {
+        match self.version {
+            Version::OpenApi3 => {
+                let Some(server) = self.spec.pointer("/servers/0") else {
+                    self.report(&[], &format!("no server URL; set the {} variable", variable));
+                    return String::new();
+                };
+                let mut url = text(server.get("url"));
+                for (name, variable) in server
+                    .get("variables")
+                    .and_then(Value::as_object)
+                    .into_iter()
+                    .flatten()
+                {
+                    url = url.replace(&format!("{{{}}}", name), &text(variable.get("default")));
+                }
+                url.trim_end_matches('/').to_string()
+            }
+            Version::Swagger2 => {
+                let Some(host) = self.spec.get("host").and_then(Value::as_str) else {
+                    self.report(&[], &format!("no host; set the {} variable", variable));
+                    return text(self.spec.get("basePath")).trim_end_matches('/').to_string();
+                };
+                let scheme = self
+                    .spec
+                    .pointer("/schemes/0")
+                    .and_then(Value::as_str)
+                    .unwrap_or("https");
+                let base_path = text(self.spec.get("basePath"));
+                format!("{}://{}{}", scheme, host, base_path.trim_end_matches('/'))
+            }
+        }
+    }
+
+    fn operation(
+        &mut self,
+        path: &str,
+        path_item: &'a Value,
+        method_name: &str,
+        method: HttpMethod,
+        operation: &'a Value,
+        default_security: Option<&'a Value>,
+    ) {
+        let operation = self.resolve(operation);
+        let name = [operation.get("summary"), operation.get("operationId")]
+            .into_iter()
+            .map(text)
+            .find(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| format!("{} {}", method_name.to_uppercase(), path));
+        let folder_id = operation
+            .pointer("/tags/0")
+            .and_then(Value::as_str)
+            .map(|tag| self.folder(tag));
+        let report_path = match operation.pointer("/tags/0").and_then(Value::as_str) {
+            Some(tag) => vec![tag.to_string(), name.clone()],
+            None => vec![name.clone()],
+        };
+
+        let mut request = Request::new(name, method, self.workspace_id.clone());
+        request.collection_id = Some(self.collection_id.clone());
+        request.sort_order = self.position(folder_id.clone());
+        request.folder_id = folder_id;
+        request.url = path.to_string();
+
+        // Operation parameters override path-level ones with the same name and location
+        let mut parameters: Vec<&Value> = Vec::new();
+        for list in [path_item.get("parameters"), operation.get("parameters")] {
+            for parameter in list.and_then(Value::as_array).into_iter().flatten() {
+                let parameter = self.resolve(parameter);
+                let same = |p: &&Value| {
+                    p.get("name") == parameter.get("name") && p.get("in") == parameter.get("in")
+                };
+                parameters.retain(|p| !same(p));
+                parameters.push(parameter);
+            }
+        }
+
+        let mut form_fields = Vec::new();
+        for parameter in parameters {
+            let key = text(parameter.get("name"));
+            let required = parameter.get("required").and_then(Value::as_bool).unwrap_or(false);
+            let example = self.parameter_example(parameter);
+            match parameter.get("in").and_then(Value::as_str).unwrap_or("") {
+                "path" => {
+                    let value = if example.is_empty() {
+                        format!("{{{{{}}}}}", key)
+                    } else {
+                        example
+                    };
+                    request.url = request.url.replace(&format!("{{{}}}", key), &value);
+                }
+                "query" => request.params.push(RequestParam {
+                    enabled: required || !example.is_empty(),
+                    key,
+                    value: example,
+                }),
+                "header" => {
+                    // OpenAPI ignores these as parameters; they come from the body and auth
+                    if ["accept", "content-type", "authorization"]
+                        .contains(&key.to_ascii_lowercase().as_str())
+                    {
+                        continue;
+                    }
+                    request.headers.push(RequestHeader {
+                        enabled: required || !example.is_empty(),
+                        key,
+                        value: example,
+                    });
+                }
+                "body" => {
+                    let schema = parameter.get("schema").unwrap_or(&Value::Null);
+                    request.body_type = BodyType::Json;
+                    request.body = pretty(&self.schema_example(schema, &[]));
+                }
+                "formData" => {
+                    let file = parameter.get("type").and_then(Value::as_str) == Some("file");
+                    form_fields.push(FormDataItem {
+                        key,
+                        value: if file { String::new() } else { example },
+                        item_type: if file { "file" } else { "text" }.to_string(),
+                        enabled: true,
+                    });
+                }
+                other => self.report(
+                    &report_path,
+                    &format!("{} parameter {} is not supported", other, key),
+                ),
+            }
+        }
+
+        if !form_fields.is_empty() {
+            let consumes = operation.get("consumes").or_else(|| self.spec.get("consumes"));
+            let multipart = form_fields.iter().any(|f| f.item_type == "file")
+                || consumes
+                    .and_then(Value::as_array)
+                    .is_some_and(|c| c.iter().any(|t| t.as_str() == Some("multipart/form-data")));
+            request.body_type = if multipart {
+                BodyType::FormData
+            } else {
+                BodyType::XWwwFormUrlencoded
+            };
+            request.form_data = form_fields;
+        }
+
+        if let Some(body) = operation.get("requestBody") {
+            let body = self.resolve(body);
+            self.request_body(&mut request, body, &report_path);
+        }
+
+        request.auth = match operation.get("security") {
+            Some(security) if Some(security) != default_security => self
+                .security(security, &report_path)
+                .unwrap_or(RequestAuth::Inherit),
+            _ => RequestAuth::Inherit,
+        };
+
+        self.tree.requests.push(request);
+    }
+
+    /// OpenAPI 3 request bodies, preferring JSON when several types are offered
+    fn request_body(&mut self, request: &mut Request, body: &'a Value, path: &[String]) {
+        let Some(content) = body.get("content").and_then(Value::as_object) else {
+            return;
+        };
+        let preference = |media_type: &str| match media_type {
+            "application/json" => 0,
+            t if t.ends_with("+json") => 1,
+            "application/x-www-form-urlencoded" => 2,
+            "multipart/form-data" => 3,
+            t if t.contains("xml") => 4,
+            t if t.starts_with("text/") => 5,
+            _ => 6,
+        };
+        let Some((media_type, media)) = content.iter().min_by_key(|(t, _)| preference(t)) else {
+            return;
+        };
+        let media = self.resolve(media);
+        let schema = media.get("schema").unwrap_or(&Value::Null);
+        let example = media
+            .get("example")
+            .cloned()
+            .or_else(|| self.first_example(media.get("examples")))
+            .unwrap_or_else(|| self.schema_example(schema, &[]));
+
+        match preference(media_type) {
+            0 | 1 => {
+                request.body_type = BodyType::Json;
+                request.body = pretty(&example);
+            }
+            2 | 3 => {
+                request.body_type = if preference(media_type) == 3 {
+                    BodyType::FormData
+                } else {
+                    BodyType::XWwwFormUrlencoded
+                };
+                let properties = self.properties(schema, &[]);
+                request.form_data = properties
+                    .into_iter()
+                    .map(|(key, property)| {
+                        let format = self.resolve(property).get("format");
+                        let file = format.and_then(Value::as_str) == Some("binary");
+                        let value = if file {
+                            String::new()
+                        } else {
+                            scalar(example.get(&key).unwrap_or(&Value::Null))
+                        };
+                        FormDataItem {
+                            key,
+                            value,
+                            item_type: if file { "file" } else { "text" }.to_string(),
+                            enabled: true,
+                        }
+                    })
+                    .collect();
+            }
+            4 => {
+                request.body_type = BodyType::Xml;
+                match example {
+                    Value::String(xml) => request.body = xml,
+                    _ => self.report(path, "XML body has no example and was left empty"),
+                }
+            }
+            5 => {
+                request.body_type = BodyType::Text;
+                request.body = scalar(&example);
+            }
+            _ => self.report(path, &format!("{} bodies are not supported", media_type)),
+        }
+    }
+
+    /// Auth for the first security requirement Resona supports; an empty list
+    /// means the operation needs no auth
+    fn security(&mut self, security: &Value, path: &[String]) -> Option<RequestAuth> {
+        let requirements = security.as_array()?;
+        if requirements.is_empty() {
+            return Some(RequestAuth::None);
+        }
+        let schemes = match self.version {
+            Version::OpenApi3 => self.spec.pointer("/components/securitySchemes"),
+            Version::Swagger2 => self.spec.get("securityDefinitions"),
+        };
+
+        let mut skipped = Vec::new();
+        for requirement in requirements {
+            let Some(name) = requirement.as_object().and_then(|r| r.keys().next()) else {
+                // `{}` makes auth optional
+                return Some(RequestAuth::None);
+            };
+            let Some(scheme) = schemes.and_then(|s| s.get(name)).map(|s| self.resolve(s)) else {
+                skipped.push(format!("{} (not defined)", name));
+                continue;
+            };
+            match self.scheme_auth(scheme) {
+                Some(auth) => {
+                    if requirement.as_object().is_some_and(|r| r.len() > 1) {
+                        self.report(path, "only the first of several combined schemes is applied");
+                    }
+                    return Some(auth);
+                }
+                None => skipped.push(name.clone()),
+            }
+        }
+        self.report(
+            path,
+            &format!("security schemes not supported: {}", skipped.join(", ")),
+        );
+        None
+    }
+
+    fn scheme_auth(&mut self, scheme: &Value) -> Option<RequestAuth> {
+        let kind = scheme.get("type").and_then(Value::as_str).unwrap_or("");
+        let http_scheme = scheme
+            .get("scheme")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let auth = match (kind, http_scheme.as_str()) {
+            ("basic", _) | ("http", "basic") => RequestAuth::Basic {
+                username: self.workspace_variable("username", "", false),
+                password: self.workspace_variable("password", "", true),
+            },
+            ("http", "bearer") => RequestAuth::Bearer {
+                token: self.workspace_variable("token", "", true),
+            },
+            ("apiKey", _) => {
+                let location = match scheme.get("in").and_then(Value::as_str) {
+                    Some("header") => ApiKeyLocation::Header,
+                    Some("query") => ApiKeyLocation::Query,
+                    _ => return None,
+                };
+                RequestAuth::ApiKey {
+                    key: text(scheme.get("name")),
+                    value: self.workspace_variable("api_key", "", true),
+                    location,
+                }
+            }
+            // The token is fetched outside Resona and pasted into the variable
+            ("oauth2", _) | ("openIdConnect", _) => RequestAuth::Bearer {
+                token: self.workspace_variable("access_token", "", true),
+            },
+            _ => return None,
+        };
+        Some(auth)
+    }
+
+    /// Add a workspace variable unless one with that name is already being
+    /// created, and return a reference to it
+    fn workspace_variable(&mut self, name: &str, value: &str, secret: bool) -> String {
+        if !self.tree.variables.iter().any(|v| v.name == name) {
+            let mut variable = Variable::new(
+                name.to_string(),
+                value.to_string(),
+                VariableScope::Workspace,
+                Some(self.workspace_id.clone()),
+            );
+            variable.is_secret = secret;
+            self.tree.variables.push(variable);
+        }
+        format!("{{{{{}}}}}", name)
+    }
+
+    /// Folder id for a tag, creating the folder on first use
+    fn folder(&mut self, tag: &str) -> String {
+        if let Some((_, id)) = self.folders.iter().find(|(t, _)| t == tag) {
+            return id.clone();
+        }
+        let folder = Folder::new(
+            tag.to_string(),
+            self.collection_id.clone(),
+            None,
+            self.workspace_id.clone(),
+        );
+        let id = folder.id.clone();
+        self.folders.push((tag.to_string(), id.clone()));
+        self.tree.folders.push(folder);
+        id
+    }
+
+    fn position(&mut self, folder_id: Option<String>) -> i64 {
+        match self.positions.iter_mut().find(|(f, _)| *f == folder_id) {
+            Some((_, next)) => {
+                *next += 1;
+                *next
+            }
+            None => {
+                self.positions.push((folder_id, 0));
+                0
+            }
+        }
+    }
+
+    /// Follow local `$ref`s (`#/components/...`); unresolvable ones are left as they are
+    fn resolve(&self, value: &'a Value) -> &'a Value {
+        let mut value = value;
+        for _ in 0..MAX_DEPTH {
+            let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+                break;
+            };
+            match reference.strip_prefix('#').and_then(|p| self.spec.pointer(p)) {
+                Some(target) => value = target,
+                None => break,
+            }
+        }
+        value
+    }
+
+    /// A parameter's example as text: its own example, the schema's, or the
+    /// schema's default or first enum value
+    fn parameter_example(&self, parameter: &'a Value) -> String {
+        let schema = match self.version {
+            Version::OpenApi3 => parameter.get("schema").map(|s| self.resolve(s)),
+            Version::Swagger2 => Some(parameter),
+        };
+        let example = parameter
+            .get("example")
+            .or_else(|| parameter.get("x-example"))
+            .cloned()
+            .or_else(|| self.first_example(parameter.get("examples")))
+            .or_else(|| {
+                let schema = schema?;
+                ["example", "default"]
+                    .iter()
+                    .find_map(|key| schema.get(*key))
+                    .or_else(|| schema.pointer("/enum/0"))
+                    .cloned()
+            });
+        example.as_ref().map(scalar).unwrap_or_default()
+    }
+
+    /// Value of the first entry of an OpenAPI 3 `examples` map
+    fn first_example(&self, examples: Option<&'a Value>) -> Option<Value> {
+        let (_, example) = examples?.as_object()?.iter().next()?;
+        self.resolve(example).get("value").cloned()
+    }
+
+    /// A value matching `schema`, from its examples where it has them.
+    /// `expanding` holds the `$ref`s being expanded; a schema that refers back
+    /// to one of them (e.g. a pet's owner being a pet) ends in `null`.
+    fn schema_example(&self, schema: &'a Value, expanding: &[&'a str]) -> Value {
+        let Some(expanding) = expand(schema, expanding) else {
+            return Value::Null;
+        };
+        let schema = self.resolve(schema);
+        if let Some(example) = ["example", "default"].iter().find_map(|key| schema.get(*key)) {
+            return example.clone();
+        }
+        if let Some(first) = schema.pointer("/enum/0") {
+            return first.clone();
+        }
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in all {
+                if let Value::Object(fields) = self.schema_example(part, &expanding) {
+                    merged.extend(fields);
+                }
+            }
+            return Value::Object(merged);
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(first) = schema.get(key).and_then(|s| s.get(0)) {
+                return self.schema_example(first, &expanding);
+            }
+        }
+
+        match schema_type(schema) {
+            Some("object") | None if schema.get("properties").is_some() => Value::Object(
+                self.properties(schema, &expanding)
+                    .into_iter()
+                    .map(|(key, property)| (key, self.schema_example(property, &expanding)))
+                    .collect(),
+            ),
+            Some("object") => json!({}),
+            Some("array") => {
+                let items = schema.get("items").unwrap_or(&Value::Null);
+                json!([self.schema_example(items, &expanding)])
+            }
+            Some("string") => json!(match schema.get("format").and_then(Value::as_str) {
+                Some("date") => "2024-01-01",
+                Some("date-time") => "2024-01-01T00:00:00Z",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("email") => "user@example.com",
+                Some("uri") | Some("url") => "https://example.com",
+                _ => "string",
+            }),
+            Some("integer") | Some("number") => json!(0),
+            Some("boolean") => json!(false),
+            _ => Value::Null,
+        }
+    }
+
+    /// Properties of an object schema, including those of its `allOf` parts;
+    /// `expanding` is as for `schema_example`
+    fn properties(&self, schema: &'a Value, expanding: &[&'a str]) -> Vec<(String, &'a Value)> {
+        let Some(expanding) = expand(schema, expanding) else {
+            return Vec::new();
+        };
+        let schema = self.resolve(schema);
+        let mut properties: Vec<(String, &Value)> = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(|(key, property)| (key.clone(), property))
+            .collect();
+        for part in schema.get("allOf").and_then(Value::as_array).into_iter().flatten() {
+            properties.extend(self.properties(part, &expanding));
+        }
+        properties
+    }
+
+    fn report(&mut self, path: &[String], message: &str) {
+        self.unsupported.push(UnsupportedItem {
+            path: path.join(" / "),
+            message: message.to_string(),
+        });
+    }
+}
+
+/// `expanding` with the `$ref` of `schema` added, or `None` when that `$ref`
+/// is already being expanded or the schemas nest too deep
+fn expand<'a>(schema: &'a Value, expanding: &[&'a str]) -> Option<Vec<&'a str>> {
+    let mut expanding = expanding.to_vec();
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        if expanding.contains(&reference) || expanding.len() > MAX_DEPTH {
+            return None;
+        }
+        expanding.push(reference);
+    }
+    Some(expanding)
+}
+
+/// `type` of a schema; OpenAPI 3.1 allows a list such as `["string", "null"]`
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(kind) => Some(kind),
+        Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).find(|k| *k != "null"),
+        _ => None,
+    }
+}
+
+/// Lowercase `name` with runs of other characters turned into `_`, for use
+/// in a variable name
+fn slug(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect();
+    if words.is_empty() {
+        "api".to_string()
+    } else {
+        words.join("_")
+    }
+}
+
+fn text(value: Option<&Value>) -> String {
+    value.and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+/// Strings as they are, other values in their JSON form
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
@@ -0,0 +1,449 @@
+//! Postman Collection v2.1 export
+//!
+//! Postman has no collection base URL, headers or params. The base URL becomes
+//! a collection variable that relative request URLs start with, and default
+//! headers and params are written onto the requests that don't set their own.
+//! Auth stays on the collection so requests that inherit it still do.
+//! Assertions, extractions and per-request client settings have no Postman
+//! equivalent and are left out.
+
+use serde_json::{json, Map, Value};
+
+use crate::collections::{is_absolute_url, Collection, CollectionDefaults};
+use crate::db::{DbError, DbResult};
+use crate::folders::Folder;
+use crate::requests::{
+    ApiKeyLocation, BodyType, Request, RequestAuth, RequestHeader, RequestParam,
+};
+use crate::scripts::Scripts;
+use crate::workspaces::Workspace;
+
+use super::{to_javascript, DEFAULTS_MARKER, SCHEMA};
+
+/// A collection with everything inside it
+pub struct CollectionRecords {
+    pub collection: Collection,
+    pub folders: Vec<Folder>,
+    pub requests: Vec<Request>,
+}
+
+/// A variable as written to the file, with its value already revealed or masked
+pub struct ExportedVariable {
+    pub name: String,
+    pub value: String,
+    pub secret: bool,
+}
+
+pub fn export_collection(
+    records: &CollectionRecords,
+    variables: &[ExportedVariable],
+) -> DbResult<String> {
+    let collection = &records.collection;
+    let taken: Vec<&str> = variables.iter().map(|v| v.name.as_str()).collect();
+    let defaults = Defaults {
+        defaults: &collection.defaults,
+        base_url: base_url_variable(&collection.defaults, &taken),
+        marked: true,
+    };
+
+    let mut document = Map::new();
+    let info = info(&collection.id, &collection.name, &collection.description);
+    document.insert("info".to_string(), info);
+    let items = collection_items(records, &defaults, None);
+    document.insert("item".to_string(), Value::Array(items));
+    insert_auth(&mut document, &collection.defaults.auth);
+    insert_events(&mut document, &collection.scripts);
+    insert_variables(&mut document, variables, defaults.base_url_entry().into_iter().collect());
+    to_string(document)
+}
+
+/// The workspace as one Postman collection: each collection becomes a
+/// top-level folder, followed by the workspace's standalone requests.
+/// Postman folders can't hold variables, so collection variables are written
+/// at the top; when two collections share a name the first one is kept, and
+/// base URL variables are numbered apart.
+pub fn export_workspace(
+    workspace: &Workspace,
+    collections: &[CollectionRecords],
+    standalone: &[Request],
+    variables: &[ExportedVariable],
+) -> DbResult<String> {
+    let mut taken: Vec<String> = variables.iter().map(|v| v.name.clone()).collect();
+    let mut base_urls = Vec::new();
+    let mut items = Vec::new();
+    for records in collections {
+        let collection = &records.collection;
+        let taken_names: Vec<&str> = taken.iter().map(String::as_str).collect();
+        let defaults = Defaults {
+            defaults: &collection.defaults,
+            base_url: base_url_variable(&collection.defaults, &taken_names),
+            // A workspace comes back as a single collection, which can't hold
+            // every collection's defaults
+            marked: false,
+        };
+        taken.extend(defaults.base_url.clone());
+        base_urls.extend(defaults.base_url_entry());
+
+        let mut folder = Map::new();
+        folder.insert("name".to_string(), json!(collection.name));
+        if !collection.description.is_empty() {
+            folder.insert("description".to_string(), json!(collection.description));
+        }
+        let children = collection_items(records, &defaults, None);
+        folder.insert("item".to_string(), Value::Array(children));
+        insert_auth(&mut folder, &collection.defaults.auth);
+        insert_events(&mut folder, &collection.scripts);
+        items.push(Value::Object(folder));
+    }
+    items.extend(standalone.iter().map(|request| request_item(request, None)));
+
+    let mut unique: Vec<&ExportedVariable> = Vec::new();
+    for variable in variables {
+        if !unique.iter().any(|v| v.name == variable.name) {
+            unique.push(variable);
+        }
+    }
+
+    let mut document = Map::new();
+    let info = info(&workspace.id, &workspace.name, &workspace.description);
+    document.insert("info".to_string(), info);
+    document.insert("item".to_string(), Value::Array(items));
+    insert_variables(&mut document, unique, base_urls);
+    to_string(document)
+}
+
+/// A collection's defaults as written onto its requests
+struct Defaults<'a> {
+    defaults: &'a CollectionDefaults,
+    /// Variable holding the base URL, when there is one
+    base_url: Option<String>,
+    /// Whether what the defaults wrote carries `DEFAULTS_MARKER`
+    marked: bool,
+}
+
+impl Defaults<'_> {
+    /// The request's URL, starting with the base URL variable when relative
+    fn url(&self, request: &Request) -> String {
+        match &self.base_url {
+            Some(name) if !is_absolute_url(&request.url) => {
+                let base = format!("{{{{{}}}}}", name);
+                if request.url.is_empty() {
+                    base
+                } else {
+                    format!("{}/{}", base, request.url.trim_start_matches('/'))
+                }
+            }
+            _ => request.url.clone(),
+        }
+    }
+
+    /// Default headers the request doesn't enable itself
+    fn headers(&self, request: &Request) -> Vec<&RequestHeader> {
+        self.defaults
+            .headers
+            .iter()
+            .filter(|d| {
+                !request
+                    .headers
+                    .iter()
+                    .any(|h| h.enabled && h.key.eq_ignore_ascii_case(&d.key))
+            })
+            .collect()
+    }
+
+    /// Default params the request doesn't enable itself
+    fn params(&self, request: &Request) -> Vec<&RequestParam> {
+        self.defaults
+            .params
+            .iter()
+            .filter(|d| !request.params.iter().any(|p| p.enabled && p.key == d.key))
+            .collect()
+    }
+
+    fn entry(&self, mut entry: Value) -> Value {
+        if let (true, Value::Object(fields)) = (self.marked, &mut entry) {
+            fields.insert("description".to_string(), json!(DEFAULTS_MARKER));
+        }
+        entry
+    }
+
+    fn base_url_entry(&self) -> Option<Value> {
+        let name = self.base_url.as_ref()?;
+        Some(self.entry(json!({
+            "key": name,
+            "value": self.defaults.base_url,
+            "type": "string",
+        })))
+    }
+}
+
+/// Variable for a collection's base URL: `base_url`, or `base_url_2` and so on
+/// when the name is taken or appears in the base URL itself
+fn base_url_variable(defaults: &CollectionDefaults, taken: &[&str]) -> Option<String> {
+    if defaults.base_url.is_empty() {
+        return None;
+    }
+    (1..)
+        .map(|n| match n {
+            1 => "base_url".to_string(),
+            n => format!("base_url_{}", n),
+        })
+        .find(|name| !taken.contains(&name.as_str()) && !defaults.base_url.contains(name.as_str()))
+}
+
+fn info(id: &str, name: &str, description: &str) -> Value {
+    let mut info = Map::new();
+    info.insert("_postman_id".to_string(), json!(id));
+    info.insert("name".to_string(), json!(name));
+    if !description.is_empty() {
+        info.insert("description".to_string(), json!(description));
+    }
+    info.insert("schema".to_string(), json!(SCHEMA));
+    Value::Object(info)
+}
+
+/// Folders and requests directly under `parent_id` (the collection root when
+/// `None`), folders first, each in their stored order
+fn collection_items(
+    records: &CollectionRecords,
+    defaults: &Defaults,
+    parent_id: Option<&str>,
+) -> Vec<Value> {
+    let mut folders: Vec<&Folder> = records
+        .folders
+        .iter()
+        .filter(|f| f.parent_id.as_deref() == parent_id)
+        .collect();
+    folders.sort_by(|a, b| a.sort_order.cmp(&b.sort_order).then_with(|| a.name.cmp(&b.name)));
+
+    let mut requests: Vec<&Request> = records
+        .requests
+        .iter()
+        .filter(|r| r.folder_id.as_deref() == parent_id)
+        .collect();
+    requests.sort_by(|a, b| a.sort_order.cmp(&b.sort_order).then_with(|| a.name.cmp(&b.name)));
+
+    let mut items = Vec::new();
+    for folder in folders {
+        items.push(json!({
+            "name": folder.name,
+            "item": collection_items(records, defaults, Some(&folder.id)),
+        }));
+    }
+    items.extend(requests.into_iter().map(|request| request_item(request, Some(defaults))));
+    items
+}
+
+/// A request, with the defaults of its collection when it has one. Defaults go
+/// first, as `CollectionDefaults::apply` puts them.
+fn request_item(request: &Request, defaults: Option<&Defaults>) -> Value {
+    let mut headers = Vec::new();
+    let mut params = Vec::new();
+    let mut query = Vec::new();
+    if let Some(defaults) = defaults {
+        for h in defaults.headers(request) {
+            headers.push(defaults.entry(key_value(&h.key, &h.value, h.enabled)));
+        }
+        for p in defaults.params(request) {
+            query.push(defaults.entry(key_value(&p.key, &p.value, p.enabled)));
+            params.push(p);
+        }
+    }
+    headers.extend(request.headers.iter().map(|h| key_value(&h.key, &h.value, h.enabled)));
+    query.extend(request.params.iter().map(|p| key_value(&p.key, &p.value, p.enabled)));
+    params.extend(&request.params);
+    let raw_url = match defaults {
+        Some(defaults) => defaults.url(request),
+        None => request.url.clone(),
+    };
+
+    let mut wire = Map::new();
+    wire.insert("method".to_string(), json!(request.method.as_str()));
+    wire.insert("header".to_string(), Value::Array(headers));
+    if let Some(body) = body(request) {
+        wire.insert("body".to_string(), body);
+    }
+    wire.insert("url".to_string(), url(&raw_url, &params, query));
+    // The request's own auth: `Inherit` is left out so Postman inherits too
+    match (&request.auth, defaults) {
+        (RequestAuth::Inherit, None) => insert_auth(&mut wire, &RequestAuth::None),
+        (auth, _) => insert_auth(&mut wire, auth),
+    }
+
+    let mut item = Map::new();
+    item.insert("name".to_string(), json!(request.name));
+    item.insert("request".to_string(), Value::Object(wire));
+    insert_events(&mut item, &request.scripts);
+    Value::Object(item)
+}
+
+/// `raw` with enabled params as the query string, and the parts Postman
+/// shows separately; `query` holds the params as written to the file
+fn url(raw_url: &str, params: &[&RequestParam], query: Vec<Value>) -> Value {
+    let query_string: Vec<String> = params
+        .iter()
+        .filter(|p| p.enabled)
+        .map(|p| format!("{}={}", p.key, p.value))
+        .collect();
+    let raw = if query_string.is_empty() {
+        raw_url.to_string()
+    } else {
+        format!("{}?{}", raw_url, query_string.join("&"))
+    };
+
+    let mut url = Map::new();
+    url.insert("raw".to_string(), json!(raw));
+    let (protocol, rest) = match raw_url.split_once("://") {
+        Some((protocol, rest)) => (Some(protocol), rest),
+        None => (None, raw_url),
+    };
+    if let Some(protocol) = protocol {
+        url.insert("protocol".to_string(), json!(protocol));
+    }
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => {
+            url.insert("port".to_string(), json!(port));
+            name
+        }
+        _ => host,
+    };
+    if !host.is_empty() {
+        url.insert("host".to_string(), json!(host.split('.').collect::<Vec<_>>()));
+    }
+    if !path.is_empty() {
+        url.insert("path".to_string(), json!(path.split('/').collect::<Vec<_>>()));
+    }
+    if !query.is_empty() {
+        url.insert("query".to_string(), Value::Array(query));
+    }
+    Value::Object(url)
+}
+
+fn body(request: &Request) -> Option<Value> {
+    let raw = |language: &str| {
+        json!({
+            "mode": "raw",
+            "raw": request.body,
+            "options": { "raw": { "language": language } },
+        })
+    };
+    match request.body_type {
+        BodyType::None => None,
+        BodyType::Json => Some(raw("json")),
+        BodyType::Xml => Some(raw("xml")),
+        BodyType::Html => Some(raw("html")),
+        BodyType::Text => Some(raw("text")),
+        BodyType::XWwwFormUrlencoded => Some(json!({
+            "mode": "urlencoded",
+            "urlencoded": request
+                .form_data
+                .iter()
+                .map(|f| key_value(&f.key, &f.value, f.enabled))
+                .collect::<Vec<_>>(),
+        })),
+        BodyType::FormData => Some(json!({
+            "mode": "formdata",
+            "formdata": request
+                .form_data
+                .iter()
+                .map(|f| {
+                    let mut field = Map::new();
+                    field.insert("key".to_string(), json!(f.key));
+                    if f.item_type == "file" {
+                        field.insert("type".to_string(), json!("file"));
+                        field.insert("src".to_string(), json!(f.value));
+                    } else {
+                        field.insert("value".to_string(), json!(f.value));
+                        field.insert("type".to_string(), json!("text"));
+                    }
+                    if !f.enabled {
+                        field.insert("disabled".to_string(), json!(true));
+                    }
+                    Value::Object(field)
+                })
+                .collect::<Vec<_>>(),
+        })),
+    }
+}
+
+fn key_value(key: &str, value: &str, enabled: bool) -> Value {
+    let mut entry = Map::new();
+    entry.insert("key".to_string(), json!(key));
+    entry.insert("value".to_string(), json!(value));
+    if !enabled {
+        entry.insert("disabled".to_string(), json!(true));
+    }
+    Value::Object(entry)
+}
+
+fn insert_auth(target: &mut Map<String, Value>, auth: &RequestAuth) {
+    let param = |key: &str, value: &str| json!({ "key": key, "value": value, "type": "string" });
+    let auth = match auth {
+        RequestAuth::Inherit => return,
+        RequestAuth::None => json!({ "type": "noauth" }),
+        RequestAuth::Basic { username, password } => json!({
+            "type": "basic",
+            "basic": [param("username", username), param("password", password)],
+        }),
+        RequestAuth::Bearer { token } => json!({
+            "type": "bearer",
+            "bearer": [param("token", token)],
+        }),
+        RequestAuth::ApiKey { key, value, location } => json!({
+            "type": "apikey",
+            "apikey": [
+                param("key", key),
+                param("value", value),
+                param("in", if *location == ApiKeyLocation::Query { "query" } else { "header" }),
+            ],
+        }),
+    };
+    target.insert("auth".to_string(), auth);
+}
+
+fn insert_events(target: &mut Map<String, Value>, scripts: &Scripts) {
+    let mut events = Vec::new();
+    for (source, listen, label) in [
+        (&scripts.pre_request, "prerequest", "pre-request"),
+        (&scripts.post_response, "test", "test"),
+    ] {
+        if source.trim().is_empty() {
+            continue;
+        }
+        events.push(json!({
+            "listen": listen,
+            "script": { "type": "text/javascript", "exec": to_javascript(source, label) },
+        }));
+    }
+    if !events.is_empty() {
+        target.insert("event".to_string(), Value::Array(events));
+    }
+}
+
+/// Variables, followed by the already written base URL variables
+fn insert_variables<'a>(
+    target: &mut Map<String, Value>,
+    variables: impl IntoIterator<Item = &'a ExportedVariable>,
+    base_urls: Vec<Value>,
+) {
+    let variables: Vec<Value> = variables
+        .into_iter()
+        .map(|v| {
+            json!({
+                "key": v.name,
+                "value": v.value,
+                "type": if v.secret { "secret" } else { "string" },
+            })
+        })
+        .chain(base_urls)
+        .collect();
+    if !variables.is_empty() {
+        target.insert("variable".to_string(), Value::Array(variables));
+    }
+}
+
+fn to_string(document: Map<String, Value>) -> DbResult<String> {
+    serde_json::to_string_pretty(&Value::Object(document))
+        .map_err(|e| DbError::Serialization(e.to_string()))
+}
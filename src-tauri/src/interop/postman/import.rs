@@ -0,0 +1,773 @@
+//! Postman Collection v2.1 (and v2.0) import
+//!
+//! Folders become folders, requests keep their URL, params, headers, body and
+//! auth, and collection variables become collection-scoped variables. Postman
+//! has auth on folders but Resona does not, so a folder's auth is copied onto
+//! the requests inheriting it. What a Resona export wrote for a collection's
+//! defaults (see `DEFAULTS_MARKER`) becomes defaults again.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::collections::{Collection, CollectionDefaults};
+use crate::db::{DbError, DbResult};
+use crate::folders::Folder;
+use crate::interop::tree::ImportTree;
+use crate::interop::types::UnsupportedItem;
+use crate::requests::{
+    ApiKeyLocation, BodyType, FormDataItem, HttpMethod, Request, RequestAuth, RequestHeader,
+    RequestParam,
+};
+use crate::scripts::Scripts;
+use crate::variables::{Variable, VariableScope};
+use crate::workspaces::Workspace;
+
+use super::{to_rhai, DEFAULTS_MARKER};
+
+#[derive(Debug, Deserialize)]
+struct PostmanCollection {
+    info: Info,
+    #[serde(default)]
+    item: Vec<Item>,
+    #[serde(default)]
+    variable: Vec<KeyValue>,
+    #[serde(default)]
+    auth: Option<Auth>,
+    #[serde(default)]
+    event: Vec<Event>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Info {
+    name: String,
+    #[serde(default)]
+    description: Option<Value>,
+    #[serde(default)]
+    schema: String,
+}
+
+/// A request, or a folder when `item` is present
+#[derive(Debug, Deserialize)]
+struct Item {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    request: Option<Value>,
+    #[serde(default)]
+    item: Option<Vec<Item>>,
+    #[serde(default)]
+    auth: Option<Auth>,
+    #[serde(default)]
+    event: Vec<Event>,
+    #[serde(default)]
+    variable: Vec<KeyValue>,
+    #[serde(default)]
+    response: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostmanRequest {
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    url: Option<Value>,
+    #[serde(default)]
+    header: Option<Value>,
+    #[serde(default)]
+    body: Option<Body>,
+    #[serde(default)]
+    auth: Option<Auth>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct KeyValue {
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    value: Option<Value>,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    src: Option<Value>,
+    #[serde(default)]
+    description: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Body {
+    #[serde(default)]
+    mode: Option<String>,
+    #[serde(default)]
+    raw: Option<String>,
+    #[serde(default)]
+    urlencoded: Vec<KeyValue>,
+    #[serde(default)]
+    formdata: Vec<KeyValue>,
+    #[serde(default)]
+    graphql: Option<Value>,
+    #[serde(default)]
+    options: Option<Value>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Auth {
+    #[serde(rename = "type")]
+    kind: String,
+    /// Parameters under the type's name: a list of `{key, value}` in v2.1, an
+    /// object in v2.0
+    #[serde(flatten)]
+    params: HashMap<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Event {
+    listen: String,
+    #[serde(default)]
+    script: Option<Script>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Script {
+    #[serde(default)]
+    exec: Option<Value>,
+}
+
+/// What a folder passes down to the items inside it
+#[derive(Clone, Default)]
+struct Inherited {
+    path: Vec<String>,
+    folder_id: Option<String>,
+    /// Auth of the nearest folder that sets one
+    auth: Option<RequestAuth>,
+    /// Commented-out scripts of enclosing folders
+    scripts: Scripts,
+}
+
+struct Importer {
+    tree: ImportTree,
+    collection_id: String,
+    workspace_id: String,
+    unsupported: Vec<UnsupportedItem>,
+    examples: usize,
+    /// Variable an export wrote for the collection's base URL
+    base_url: Option<String>,
+    /// Base URL, headers and params taken back from an export
+    defaults: CollectionDefaults,
+}
+
+/// Parse a Postman collection into records for `workspace`, or for a new
+/// workspace named after the collection
+pub fn import(
+    content: &str,
+    workspace: Option<&Workspace>,
+) -> DbResult<(ImportTree, Vec<UnsupportedItem>)> {
+    let source: PostmanCollection = serde_json::from_str(content)
+        .map_err(|e| DbError::InvalidInput(format!("Not a Postman collection: {}", e)))?;
+    if !source.info.schema.is_empty()
+        && !source.info.schema.contains("v2.1")
+        && !source.info.schema.contains("v2.0")
+    {
+        return Err(DbError::InvalidInput(format!(
+            "Unsupported Postman schema: {}",
+            source.info.schema
+        )));
+    }
+
+    let description = source.info.description.as_ref().map(description).unwrap_or_default();
+    let mut tree = ImportTree::default();
+    let workspace_id = match workspace {
+        Some(workspace) => workspace.id.clone(),
+        None => {
+            let workspace = Workspace::new(source.info.name.clone(), description.clone());
+            let id = workspace.id.clone();
+            tree.workspace = Some(workspace);
+            id
+        }
+    };
+    tree.workspace_id = workspace_id.clone();
+
+    let mut collection =
+        Collection::new(source.info.name.clone(), description, workspace_id.clone());
+    let mut importer = Importer {
+        collection_id: collection.id.clone(),
+        workspace_id,
+        tree,
+        unsupported: Vec::new(),
+        examples: 0,
+        base_url: None,
+        defaults: CollectionDefaults::default(),
+    };
+
+    if let Some(auth) = &source.auth {
+        if let Some(auth) = importer.auth(auth, &[]) {
+            collection.defaults.auth = auth;
+        }
+    }
+    collection.scripts = importer.scripts(&source.event, &[]);
+
+    for variable in &source.variable {
+        importer.variable(variable, &[]);
+    }
+    importer.items(&source.item, &Inherited::default());
+
+    let taken = std::mem::take(&mut importer.defaults);
+    collection.defaults.base_url = taken.base_url;
+    collection.defaults.headers = taken.headers;
+    collection.defaults.params = taken.params;
+    importer.tree.collections.push(collection);
+
+    if importer.examples > 0 {
+        importer.unsupported.push(UnsupportedItem {
+            path: String::new(),
+            message: format!(
+                "{} saved example response(s) were not imported",
+                importer.examples
+            ),
+        });
+    }
+
+    Ok((importer.tree, importer.unsupported))
+}
+
+impl Importer {
+    fn items(&mut self, items: &[Item], inherited: &Inherited) {
+        for (position, item) in items.iter().enumerate() {
+            let mut path = inherited.path.clone();
+            path.push(item.name.clone());
+
+            match &item.item {
+                Some(children) => self.folder(item, children, path, position as i64, inherited),
+                None => self.request(item, path, position as i64, inherited),
+            }
+        }
+    }
+
+    fn folder(
+        &mut self,
+        item: &Item,
+        children: &[Item],
+        path: Vec<String>,
+        position: i64,
+        inherited: &Inherited,
+    ) {
+        let mut folder = Folder::new(
+            item.name.clone(),
+            self.collection_id.clone(),
+            inherited.folder_id.clone(),
+            self.workspace_id.clone(),
+        );
+        // Numbered among its siblings, like the requests next to it
+        folder.sort_order = position;
+
+        if !item.variable.is_empty() {
+            self.report(&path, "folder variables are not supported and were skipped");
+        }
+
+        let mut inner = Inherited {
+            folder_id: Some(folder.id.clone()),
+            ..inherited.clone()
+        };
+        if let Some(auth) = &item.auth {
+            if let Some(auth) = self.auth(auth, &path) {
+                inner.auth = Some(auth);
+            }
+        }
+        let scripts = self.scripts(&item.event, &path);
+        inner.scripts = Scripts {
+            pre_request: join(&inherited.scripts.pre_request, &scripts.pre_request),
+            post_response: join(&inherited.scripts.post_response, &scripts.post_response),
+        };
+        inner.path = path;
+
+        self.tree.folders.push(folder);
+        self.items(children, &inner);
+    }
+
+    fn request(&mut self, item: &Item, path: Vec<String>, position: i64, inherited: &Inherited) {
+        let source = match &item.request {
+            // A bare URL is a GET
+            Some(Value::String(url)) => PostmanRequest {
+                method: None,
+                url: Some(Value::String(url.clone())),
+                header: None,
+                body: None,
+                auth: None,
+            },
+            Some(value) => match serde_json::from_value(value.clone()) {
+                Ok(request) => request,
+                Err(e) => {
+                    self.report(&path, &format!("request could not be read: {}", e));
+                    return;
+                }
+            },
+            None => {
+                self.report(&path, "item has no request");
+                return;
+            }
+        };
+
+        let method_name = source.method.as_deref().unwrap_or("GET").to_ascii_uppercase();
+        let method = match method_name.as_str() {
+            "GET" => HttpMethod::Get,
+            "POST" => HttpMethod::Post,
+            "PUT" => HttpMethod::Put,
+            "PATCH" => HttpMethod::Patch,
+            "DELETE" => HttpMethod::Delete,
+            "HEAD" => HttpMethod::Head,
+            "OPTIONS" => HttpMethod::Options,
+            other => {
+                self.report(&path, &format!("method {} is not supported; imported as GET", other));
+                HttpMethod::Get
+            }
+        };
+
+        let mut request = Request::new(item.name.clone(), method, self.workspace_id.clone());
+        request.collection_id = Some(self.collection_id.clone());
+        request.folder_id = inherited.folder_id.clone();
+        request.sort_order = position;
+
+        if let Some(url) = &source.url {
+            let (url, params) = self.url(url, &path);
+            request.url = url;
+            request.params = params;
+        }
+        if let Some(relative) = self
+            .base_url
+            .as_ref()
+            .and_then(|name| request.url.strip_prefix(&format!("{{{{{}}}}}", name)))
+        {
+            request.url = relative.to_string();
+        }
+        if let Some(header) = &source.header {
+            request.headers = headers(header);
+        }
+        let header_keys = marked_keys(source.header.as_ref());
+        let param_keys = marked_keys(source.url.as_ref().and_then(|url| url.get("query")));
+        self.take_defaults(&mut request, &header_keys, &param_keys);
+        if let Some(body) = &source.body {
+            self.body(&mut request, body, &path);
+        }
+
+        // Unsupported auth falls back to what the request would otherwise inherit
+        let auth = source.auth.as_ref().filter(|a| a.kind != "inherit");
+        request.auth = auth
+            .and_then(|auth| self.auth(auth, &path))
+            .or_else(|| inherited.auth.clone())
+            .unwrap_or_default();
+
+        let scripts = self.scripts(&item.event, &path);
+        request.scripts = Scripts {
+            pre_request: join(&inherited.scripts.pre_request, &scripts.pre_request),
+            post_response: join(&inherited.scripts.post_response, &scripts.post_response),
+        };
+        self.examples += item.response.len();
+
+        self.tree.requests.push(request);
+    }
+
+    /// URL without its query string, and the query as params. Path variables
+    /// (`:id`) are replaced by their value, or by `{{id}}` when they have none.
+    fn url(&mut self, url: &Value, path: &[String]) -> (String, Vec<RequestParam>) {
+        let (raw, query, path_variables) = match url {
+            Value::String(raw) => (raw.clone(), None, Vec::new()),
+            Value::Object(parts) => {
+                let raw = match parts.get("raw").and_then(Value::as_str) {
+                    Some(raw) => raw.to_string(),
+                    None => url_from_parts(parts),
+                };
+                let query = parts
+                    .get("query")
+                    .and_then(|q| serde_json::from_value::<Vec<KeyValue>>(q.clone()).ok());
+                let variables = parts
+                    .get("variable")
+                    .and_then(|v| serde_json::from_value::<Vec<KeyValue>>(v.clone()).ok())
+                    .unwrap_or_default();
+                (raw, query, variables)
+            }
+            _ => {
+                self.report(path, "URL could not be read");
+                return (String::new(), Vec::new());
+            }
+        };
+
+        let (base, query_string) = match raw.split_once('?') {
+            Some((base, query)) => (base.to_string(), Some(query)),
+            None => (raw.clone(), None),
+        };
+        let params = match query {
+            Some(query) => query
+                .iter()
+                .filter_map(|p| {
+                    Some(RequestParam {
+                        key: p.key.clone()?,
+                        value: p.value.as_ref().map(text).unwrap_or_default(),
+                        enabled: !p.disabled,
+                    })
+                })
+                .collect(),
+            None => query_string
+                .map(|q| {
+                    q.split('&')
+                        .filter(|pair| !pair.is_empty())
+                        .map(|pair| {
+                            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                            RequestParam {
+                                key: decode(key),
+                                value: decode(value),
+                                enabled: true,
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        let mut url = base;
+        for variable in &path_variables {
+            let Some(key) = &variable.key else { continue };
+            let value = variable.value.as_ref().map(text).unwrap_or_default();
+            let replacement = if value.is_empty() {
+                format!("{{{{{}}}}}", key)
+            } else {
+                value
+            };
+            url = url
+                .split('/')
+                .map(|segment| {
+                    if segment == format!(":{}", key) {
+                        replacement.clone()
+                    } else {
+                        segment.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+        }
+
+        (url, params)
+    }
+
+    fn body(&mut self, request: &mut Request, body: &Body, path: &[String]) {
+        if body.disabled {
+            return;
+        }
+        match body.mode.as_deref() {
+            Some("raw") => {
+                let language = body
+                    .options
+                    .as_ref()
+                    .and_then(|o| o.pointer("/raw/language"))
+                    .and_then(Value::as_str);
+                let json_header = request.headers.iter().any(|h| {
+                    h.key.eq_ignore_ascii_case("content-type") && h.value.contains("json")
+                });
+                request.body_type = match language {
+                    Some("json") => BodyType::Json,
+                    Some("xml") => BodyType::Xml,
+                    Some("html") => BodyType::Html,
+                    None if json_header => BodyType::Json,
+                    _ => BodyType::Text,
+                };
+                request.body = body.raw.clone().unwrap_or_default();
+            }
+            Some("urlencoded") => {
+                request.body_type = BodyType::XWwwFormUrlencoded;
+                request.form_data = body
+                    .urlencoded
+                    .iter()
+                    .filter_map(|f| form_item(f, "text"))
+                    .collect();
+            }
+            Some("formdata") => {
+                request.body_type = BodyType::FormData;
+                for field in &body.formdata {
+                    if field.kind.as_deref() == Some("file") {
+                        let src = match &field.src {
+                            Some(Value::Array(files)) => {
+                                if files.len() > 1 {
+                                    self.report(path, "only the first file of a field was kept");
+                                }
+                                files.first().map(text).unwrap_or_default()
+                            }
+                            Some(src) => text(src),
+                            None => String::new(),
+                        };
+                        if let Some(mut item) = form_item(field, "file") {
+                            item.value = src;
+                            request.form_data.push(item);
+                        }
+                    } else if let Some(item) = form_item(field, "text") {
+                        request.form_data.push(item);
+                    }
+                }
+            }
+            Some("graphql") => {
+                let graphql = body.graphql.clone().unwrap_or(Value::Null);
+                let query = graphql.get("query").cloned().unwrap_or(Value::Null);
+                let variables = match graphql.get("variables") {
+                    Some(Value::String(s)) if !s.trim().is_empty() => {
+                        serde_json::from_str(s).unwrap_or(Value::String(s.clone()))
+                    }
+                    Some(Value::String(_)) | None => Value::Null,
+                    Some(other) => other.clone(),
+                };
+                request.body_type = BodyType::Json;
+                request.body = serde_json::to_string_pretty(
+                    &serde_json::json!({ "query": query, "variables": variables }),
+                )
+                .unwrap_or_default();
+            }
+            Some("file") => self.report(path, "binary file bodies are not supported"),
+            Some(other) => self.report(path, &format!("body mode {} is not supported", other)),
+            None => {}
+        }
+    }
+
+    /// `None` for auth types Resona doesn't have, after reporting them
+    fn auth(&mut self, auth: &Auth, path: &[String]) -> Option<RequestAuth> {
+        let param = |name: &str| -> String {
+            match auth.params.get(&auth.kind) {
+                Some(Value::Array(entries)) => entries
+                    .iter()
+                    .find(|e| e.get("key").and_then(Value::as_str) == Some(name))
+                    .and_then(|e| e.get("value"))
+                    .map(text)
+                    .unwrap_or_default(),
+                Some(Value::Object(fields)) => fields.get(name).map(text).unwrap_or_default(),
+                _ => String::new(),
+            }
+        };
+
+        match auth.kind.as_str() {
+            "noauth" => Some(RequestAuth::None),
+            "basic" => Some(RequestAuth::Basic {
+                username: param("username"),
+                password: param("password"),
+            }),
+            "bearer" => Some(RequestAuth::Bearer {
+                token: param("token"),
+            }),
+            "apikey" => Some(RequestAuth::ApiKey {
+                key: param("key"),
+                value: param("value"),
+                location: if param("in") == "query" {
+                    ApiKeyLocation::Query
+                } else {
+                    ApiKeyLocation::Header
+                },
+            }),
+            other => {
+                self.report(path, &format!("{} auth is not supported", other));
+                None
+            }
+        }
+    }
+
+    /// Scripts as Rhai comments, reporting each one kept
+    fn scripts(&mut self, events: &[Event], path: &[String]) -> Scripts {
+        let mut scripts = Scripts::default();
+        for event in events {
+            let source = match event.script.as_ref().and_then(|s| s.exec.as_ref()) {
+                Some(Value::Array(lines)) => {
+                    lines.iter().map(text).collect::<Vec<_>>().join("\n")
+                }
+                Some(exec) => text(exec),
+                None => continue,
+            };
+            if source.trim().is_empty() {
+                continue;
+            }
+            let (target, label) = match event.listen.as_str() {
+                "prerequest" => (&mut scripts.pre_request, "pre-request"),
+                "test" => (&mut scripts.post_response, "test"),
+                other => {
+                    self.report(path, &format!("{} scripts are not supported", other));
+                    continue;
+                }
+            };
+            let (rhai, commented) = to_rhai(&source, label);
+            *target = join(target, &rhai);
+            if commented {
+                self.report(path, &format!("{} script kept as a comment", label));
+            }
+        }
+        scripts
+    }
+
+    fn variable(&mut self, source: &KeyValue, path: &[String]) {
+        let Some(name) = source.key.clone().filter(|k| !k.is_empty()) else {
+            return;
+        };
+        if source.disabled {
+            self.report(path, &format!("disabled variable {} was skipped", name));
+            return;
+        }
+        let value = source.value.as_ref().map(text).unwrap_or_default();
+        let marked = source.description.as_ref().map(description).as_deref()
+            == Some(DEFAULTS_MARKER);
+        if marked && self.base_url.is_none() {
+            self.defaults.base_url = value;
+            self.base_url = Some(name);
+            return;
+        }
+        let mut variable = Variable::new(
+            name,
+            value,
+            VariableScope::Collection,
+            Some(self.collection_id.clone()),
+        );
+        variable.is_secret = source.kind.as_deref() == Some("secret");
+        self.tree.variables.push(variable);
+    }
+
+    /// Move the headers and params an export wrote for the collection's
+    /// defaults back onto the collection. One that differs from the default
+    /// already taken stays on the request.
+    fn take_defaults(
+        &mut self,
+        request: &mut Request,
+        header_keys: &[String],
+        param_keys: &[String],
+    ) {
+        let defaults = &mut self.defaults;
+        request.headers.retain(|header| {
+            if !header_keys.contains(&header.key) {
+                return true;
+            }
+            match defaults.headers.iter().find(|d| d.key.eq_ignore_ascii_case(&header.key)) {
+                Some(default) => default != header,
+                None => {
+                    defaults.headers.push(header.clone());
+                    false
+                }
+            }
+        });
+        request.params.retain(|param| {
+            if !param_keys.contains(&param.key) {
+                return true;
+            }
+            match defaults.params.iter().find(|d| d.key == param.key) {
+                Some(default) => default != param,
+                None => {
+                    defaults.params.push(param.clone());
+                    false
+                }
+            }
+        });
+    }
+
+    fn report(&mut self, path: &[String], message: &str) {
+        self.unsupported.push(UnsupportedItem {
+            path: path.join(" / "),
+            message: message.to_string(),
+        });
+    }
+}
+
+/// Descriptions are a string or `{ content, type }`
+fn description(value: &Value) -> String {
+    match value {
+        Value::Object(fields) => fields.get("content").map(text).unwrap_or_default(),
+        other => text(other),
+    }
+}
+
+/// Strings as they are, other JSON values in their JSON form
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn decode(value: &str) -> String {
+    urlencoding::decode(value)
+        .map(|v| v.into_owned())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+fn url_from_parts(parts: &serde_json::Map<String, Value>) -> String {
+    let joined = |key: &str, separator: &str| match parts.get(key) {
+        Some(Value::Array(items)) => items.iter().map(text).collect::<Vec<_>>().join(separator),
+        Some(value) => text(value),
+        None => String::new(),
+    };
+    let mut url = String::new();
+    if let Some(protocol) = parts.get("protocol").and_then(Value::as_str) {
+        url.push_str(protocol);
+        url.push_str("://");
+    }
+    url.push_str(&joined("host", "."));
+    if let Some(port) = parts.get("port") {
+        url.push(':');
+        url.push_str(&text(port));
+    }
+    let path = joined("path", "/");
+    if !path.is_empty() {
+        url.push('/');
+        url.push_str(&path);
+    }
+    url
+}
+
+/// Keys of the entries an export wrote for the collection's defaults
+fn marked_keys(entries: Option<&Value>) -> Vec<String> {
+    let Some(Value::Array(entries)) = entries else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter(|e| e.get("description").map(description).as_deref() == Some(DEFAULTS_MARKER))
+        .filter_map(|e| e.get("key").and_then(Value::as_str).map(str::to_string))
+        .collect()
+}
+
+/// Headers from a list of `{key, value, disabled}` or a raw `Key: value` block
+fn headers(value: &Value) -> Vec<RequestHeader> {
+    match value {
+        Value::String(raw) => raw
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| RequestHeader {
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+                enabled: true,
+            })
+            .collect(),
+        other => serde_json::from_value::<Vec<KeyValue>>(other.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|h| {
+                Some(RequestHeader {
+                    key: h.key?,
+                    value: h.value.as_ref().map(text).unwrap_or_default(),
+                    enabled: !h.disabled,
+                })
+            })
+            .collect(),
+    }
+}
+
+fn form_item(field: &KeyValue, item_type: &str) -> Option<FormDataItem> {
+    Some(FormDataItem {
+        key: field.key.clone()?,
+        value: field.value.as_ref().map(text).unwrap_or_default(),
+        item_type: item_type.to_string(),
+        enabled: !field.disabled,
+    })
+}
+
+fn join(first: &str, second: &str) -> String {
+    match (first.is_empty(), second.is_empty()) {
+        (true, _) => second.to_string(),
+        (_, true) => first.to_string(),
+        _ => format!("{}\n{}", first, second),
+    }
+}
@@ -0,0 +1,82 @@
+//! Postman Collection v2.1 import and export
+//!
+//! Postman scripts are JavaScript and Resona's are Rhai, so neither side can
+//! run the other's. Each is kept as a marked comment block in the other
+//! format, and the block is restored when the file comes back, so a collection
+//! can go back and forth without losing either.
+
+mod export;
+mod import;
+
+pub use export::{export_collection, export_workspace, CollectionRecords, ExportedVariable};
+pub use import::import;
+
+const SCHEMA: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+/// Description of what an export wrote for a collection's defaults: the base
+/// URL variable, and headers and params copied onto requests. The import turns
+/// them back into defaults.
+const DEFAULTS_MARKER: &str = "Resona collection default";
+
+const RHAI_HEADER: &str = "// Resona script (Rhai), not run by Postman:";
+
+fn javascript_header(label: &str) -> String {
+    format!("// Postman {} script (JavaScript), kept for reference:", label)
+}
+
+/// Rhai for a Postman script: JavaScript is commented out, and Rhai kept by a
+/// previous export is restored. The flag is set when JavaScript was commented.
+fn to_rhai(javascript: &str, label: &str) -> (String, bool) {
+    let (restored, rest) = uncomment(javascript, RHAI_HEADER);
+    let has_javascript = rest.iter().any(|line| !line.trim().is_empty());
+    let mut lines = restored;
+    if has_javascript {
+        lines.extend(comment(&rest, &javascript_header(label)));
+    }
+    (lines.join("\n"), has_javascript)
+}
+
+/// Postman script lines for a Resona script, the inverse of `to_rhai`
+fn to_javascript(rhai: &str, label: &str) -> Vec<String> {
+    let (restored, rest) = uncomment(rhai, &javascript_header(label));
+    let mut lines = restored;
+    if rest.iter().any(|line| !line.trim().is_empty()) {
+        lines.extend(comment(&rest, RHAI_HEADER));
+    }
+    lines
+}
+
+fn comment(lines: &[&str], header: &str) -> Vec<String> {
+    let mut commented = vec![header.to_string()];
+    commented.extend(lines.iter().map(|line| format!("// {}", line)));
+    commented
+}
+
+/// Split `text` into the lines of blocks under `header`, uncommented, and
+/// everything else. A block ends at the first line that isn't a comment.
+fn uncomment<'a>(text: &'a str, header: &str) -> (Vec<String>, Vec<&'a str>) {
+    let mut restored = Vec::new();
+    let mut rest = Vec::new();
+    let mut in_block = false;
+    for line in text.lines() {
+        if line == header {
+            in_block = true;
+            continue;
+        }
+        if in_block {
+            match line.strip_prefix("// ") {
+                Some(original) => {
+                    restored.push(original.to_string());
+                    continue;
+                }
+                None if line == "//" => {
+                    restored.push(String::new());
+                    continue;
+                }
+                None => in_block = false,
+            }
+        }
+        rest.push(line);
+    }
+    (restored, rest)
+}
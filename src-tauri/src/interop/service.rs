@@ -0,0 +1,607 @@
+use std::fs;
+
+use crate::collections::{Collection, CollectionService};
+use crate::db::{Database, DbResult};
+use crate::folders::FolderService;
+use crate::requests::RequestService;
+use crate::variables::{SecretExport, Variable, VariableService};
+use crate::workspaces::WorkspaceService;
+
+use super::openapi;
+use super::postman::{self, CollectionRecords, ExportedVariable};
+use super::tree::ImportTree;
+use super::types::{ImportFileInput, ImportReport, UnsupportedItem};
+
+pub struct InteropService {
+    db: Database,
+}
+
+impl InteropService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub fn import_postman(&self, input: ImportFileInput) -> DbResult<ImportReport> {
+        let content = fs::read_to_string(&input.path)?;
+        let workspace = match &input.workspace_id {
+            Some(id) => Some(WorkspaceService::new(self.db.clone()).get(id)?),
+            None => None,
+        };
+        let (tree, unsupported) = postman::import(&content, workspace.as_ref())?;
+        self.write(tree, unsupported)
+    }
+
+    /// Import an OpenAPI 3.x or Swagger 2.0 document. Workspace variables the
+    /// spec needs (its server URL, credentials) are only created when missing,
+    /// so importing into an existing workspace keeps its values.
+    pub fn import_openapi(&self, input: ImportFileInput) -> DbResult<ImportReport> {
+        let content = fs::read_to_string(&input.path)?;
+        let workspace = match &input.workspace_id {
+            Some(id) => Some(WorkspaceService::new(self.db.clone()).get(id)?),
+            None => None,
+        };
+        let (mut tree, mut unsupported) = openapi::import(&content, workspace.as_ref())?;
+
+        if let Some(workspace) = &workspace {
+            let existing = VariableService::new(self.db.clone()).get_by_workspace(&workspace.id)?;
+            tree.variables.retain(|variable| {
+                let exists = existing.iter().any(|v| v.name == variable.name);
+                if exists {
+                    unsupported.push(UnsupportedItem {
+                        path: String::new(),
+                        message: format!("variable {} already exists and was kept", variable.name),
+                    });
+                }
+                !exists
+            });
+        }
+        self.write(tree, unsupported)
+    }
+
+    /// A collection as Postman v2.1 JSON, with its collection variables
+    pub fn export_postman_collection(
+        &self,
+        collection_id: &str,
+        secrets: SecretExport,
+    ) -> DbResult<String> {
+        let collection = CollectionService::new(self.db.clone()).get(collection_id)?;
+        let variables = VariableService::new(self.db.clone()).get_by_collection(collection_id)?;
+        postman::export_collection(
+            &self.records(collection)?,
+            &self.exported(variables, secrets)?,
+        )
+    }
+
+    /// Every collection and standalone request of a workspace as one Postman
+    /// v2.1 collection. Workspace variables belong in a Postman environment,
+    /// exported with `VariableService::export`.
+    pub fn export_postman_workspace(
+        &self,
+        workspace_id: &str,
+        secrets: SecretExport,
+    ) -> DbResult<String> {
+        let workspace = WorkspaceService::new(self.db.clone()).get(workspace_id)?;
+        let variable_service = VariableService::new(self.db.clone());
+
+        let mut collections = Vec::new();
+        let mut variables = Vec::new();
+        for collection in CollectionService::new(self.db.clone()).get_by_workspace(workspace_id)? {
+            variables.extend(variable_service.get_by_collection(&collection.id)?);
+            collections.push(self.records(collection)?);
+        }
+        let standalone =
+            RequestService::new(self.db.clone()).get_standalone_by_workspace(workspace_id)?;
+
+        postman::export_workspace(
+            &workspace,
+            &collections,
+            &standalone,
+            &self.exported(variables, secrets)?,
+        )
+    }
+
+    fn records(&self, collection: Collection) -> DbResult<CollectionRecords> {
+        Ok(CollectionRecords {
+            folders: FolderService::new(self.db.clone()).get_by_collection(&collection.id)?,
+            requests: RequestService::new(self.db.clone()).get_by_collection(&collection.id)?,
+            collection,
+        })
+    }
+
+    /// Variables with their values revealed, masked or left out as `secrets` says
+    fn exported(
+        &self,
+        variables: Vec<Variable>,
+        secrets: SecretExport,
+    ) -> DbResult<Vec<ExportedVariable>> {
+        let service = VariableService::new(self.db.clone());
+        let mut exported = Vec::new();
+        for variable in variables {
+            if variable.is_secret && secrets == SecretExport::Omit {
+                continue;
+            }
+            let value = if !variable.is_secret || secrets == SecretExport::Include {
+                service.plaintext(&variable)?
+            } else {
+                String::new()
+            };
+            exported.push(ExportedVariable {
+                name: variable.name,
+                value,
+                secret: variable.is_secret,
+            });
+        }
+        Ok(exported)
+    }
+
+    /// Seal secret values and write the tree in one transaction
+    fn write(
+        &self,
+        mut tree: ImportTree,
+        unsupported: Vec<UnsupportedItem>,
+    ) -> DbResult<ImportReport> {
+        let variables = VariableService::new(self.db.clone());
+        for variable in &mut tree.variables {
+            let value = std::mem::take(&mut variable.value);
+            variables.prepare(variable, value)?;
+        }
+
+        tree.write(&self.db)?;
+
+        Ok(ImportReport {
+            workspace_id: tree.workspace_id.clone(),
+            collection_ids: tree.collections.iter().map(|c| c.id.clone()).collect(),
+            folders: tree.folders.len(),
+            requests: tree.requests.len(),
+            variables: tree.variables.len(),
+            unsupported,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::{CollectionDefaults, CollectionService, CreateCollectionInput};
+    use crate::folders::FolderService;
+    use crate::requests::{
+        BodyType, CreateRequestInput, HttpMethod, RequestAuth, RequestHeader, RequestParam,
+        RequestService,
+    };
+    use std::env::temp_dir;
+
+    fn create_test_db() -> Database {
+        let path = temp_dir().join(format!("resona_test_{}.redb", uuid::Uuid::new_v4()));
+        Database::open_at(path).expect("Failed to create test database")
+    }
+
+    const COLLECTION: &str = r#"{
+        "info": {
+            "name": "Petstore",
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        },
+        "auth": { "type": "bearer", "bearer": [{ "key": "token", "value": "{{token}}" }] },
+        "variable": [
+            { "key": "base_url", "value": "https://api.example.com" },
+            { "key": "api_key", "value": "s3cret", "type": "secret" },
+            { "key": "old", "value": "x", "disabled": true }
+        ],
+        "item": [
+            {
+                "name": "Pets",
+                "auth": { "type": "basic", "basic": { "username": "admin", "password": "pw" } },
+                "event": [{ "listen": "prerequest", "script": { "exec": ["console.log(1)"] } }],
+                "item": [
+                    {
+                        "name": "Get pet",
+                        "request": {
+                            "method": "GET",
+                            "url": {
+                                "raw": "{{base_url}}/pets/:id?expand=owner",
+                                "query": [
+                                    { "key": "expand", "value": "owner" },
+                                    { "key": "debug", "value": "1", "disabled": true }
+                                ],
+                                "variable": [{ "key": "id", "value": "" }]
+                            }
+                        },
+                        "response": [{ "name": "OK" }]
+                    },
+                    {
+                        "name": "Create pet",
+                        "request": {
+                            "method": "POST",
+                            "header": [{ "key": "X-Trace", "value": "1" }],
+                            "url": "{{base_url}}/pets",
+                            "body": {
+                                "mode": "raw",
+                                "raw": "{\"name\": \"Rex\"}",
+                                "options": { "raw": { "language": "json" } }
+                            },
+                            "auth": { "type": "digest", "digest": [] }
+                        }
+                    }
+                ]
+            },
+            {
+                "name": "Login",
+                "request": {
+                    "method": "POST",
+                    "url": "{{base_url}}/login",
+                    "body": {
+                        "mode": "urlencoded",
+                        "urlencoded": [{ "key": "user", "value": "me" }]
+                    }
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_import_postman_collection() {
+        let db = create_test_db();
+        let path = temp_dir().join(format!("resona_test_{}.json", uuid::Uuid::new_v4()));
+        fs::write(&path, COLLECTION).unwrap();
+
+        let report = InteropService::new(db.clone())
+            .import_postman(ImportFileInput {
+                path: path.to_string_lossy().to_string(),
+                workspace_id: None,
+            })
+            .expect("Failed to import collection");
+        fs::remove_file(&path).ok();
+
+        assert_eq!((report.folders, report.requests, report.variables), (1, 3, 2));
+        let messages: Vec<&str> = report.unsupported.iter().map(|u| u.message.as_str()).collect();
+        assert!(messages.contains(&"digest auth is not supported"));
+        assert!(messages.contains(&"disabled variable old was skipped"));
+        assert!(messages.contains(&"1 saved example response(s) were not imported"));
+
+        let workspace = WorkspaceService::new(db.clone()).get(&report.workspace_id).unwrap();
+        assert_eq!(workspace.name, "Petstore");
+        let collection = CollectionService::new(db.clone()).get(&report.collection_ids[0]).unwrap();
+        assert_eq!(
+            collection.defaults.auth,
+            RequestAuth::Bearer { token: "{{token}}".to_string() }
+        );
+        let folders = FolderService::new(db.clone()).get_by_collection(&collection.id).unwrap();
+        assert_eq!(folders[0].name, "Pets");
+
+        let requests = RequestService::new(db.clone())
+            .get_by_folder(&folders[0].id)
+            .unwrap();
+        let get = requests.iter().find(|r| r.name == "Get pet").unwrap();
+        assert_eq!(get.url, "{{base_url}}/pets/{{id}}");
+        assert_eq!(get.params.len(), 2);
+        assert!(!get.params[1].enabled);
+        // The folder's auth is copied onto requests that inherit it
+        assert!(matches!(&get.auth, RequestAuth::Basic { username, .. } if username == "admin"));
+        assert!(get.scripts.pre_request.starts_with("// Postman pre-request script"));
+        assert!(get.scripts.pre_request.contains("// console.log(1)"));
+
+        let create = requests.iter().find(|r| r.name == "Create pet").unwrap();
+        assert_eq!(create.method, HttpMethod::Post);
+        assert_eq!(create.body_type, BodyType::Json);
+        assert_eq!(create.body, r#"{"name": "Rex"}"#);
+        assert!(matches!(create.auth, RequestAuth::Basic { .. }));
+
+        let login = RequestService::new(db.clone())
+            .get_by_collection(&collection.id)
+            .unwrap()
+            .into_iter()
+            .find(|r| r.name == "Login")
+            .unwrap();
+        assert_eq!(login.body_type, BodyType::XWwwFormUrlencoded);
+        assert_eq!(login.form_data[0].key, "user");
+
+        let variables = VariableService::new(db.clone())
+            .get_by_collection(&collection.id)
+            .unwrap();
+        let api_key = variables.iter().find(|v| v.name == "api_key").unwrap();
+        assert!(api_key.is_secret);
+    }
+
+    const OPENAPI: &str = r##"
+openapi: 3.0.3
+info:
+  title: Petstore
+servers:
+  - url: https://{region}.example.com/v1
+    variables:
+      region:
+        default: eu
+security:
+  - bearerAuth: []
+tags:
+  - name: pets
+paths:
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        required: true
+        schema: { type: integer, example: 42 }
+    get:
+      tags: [pets]
+      summary: Get pet
+      parameters:
+        - name: expand
+          in: query
+          schema: { type: string, enum: [owner, vet] }
+        - name: X-Request-Id
+          in: header
+          schema: { type: string }
+  /pets:
+    post:
+      tags: [pets]
+      operationId: createPet
+      requestBody:
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Pet" }
+      security:
+        - apiKey: []
+  /health:
+    get:
+      security: []
+components:
+  securitySchemes:
+    bearerAuth: { type: http, scheme: bearer }
+    apiKey: { type: apiKey, in: header, name: X-API-Key }
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name: { type: string, example: Rex }
+        tags: { type: array, items: { type: string } }
+        owner: { $ref: "#/components/schemas/Pet" }
+"##;
+
+    fn import_openapi(
+        service: &InteropService,
+        content: &str,
+        workspace_id: Option<String>,
+    ) -> ImportReport {
+        let path = temp_dir().join(format!("resona_test_{}.yaml", uuid::Uuid::new_v4()));
+        fs::write(&path, content).unwrap();
+        let report = service
+            .import_openapi(ImportFileInput {
+                path: path.to_string_lossy().to_string(),
+                workspace_id,
+            })
+            .expect("Failed to import spec");
+        fs::remove_file(&path).ok();
+        report
+    }
+
+    #[test]
+    fn test_import_openapi_spec() {
+        let db = create_test_db();
+        let service = InteropService::new(db.clone());
+        let import = |content: &str| import_openapi(&service, content, None);
+
+        let report = import(OPENAPI);
+        assert_eq!((report.folders, report.requests), (1, 3));
+        let collection = CollectionService::new(db.clone()).get(&report.collection_ids[0]).unwrap();
+        assert_eq!(collection.defaults.base_url, "{{petstore_base_url}}");
+        let bearer = RequestAuth::Bearer { token: "{{token}}".to_string() };
+        assert_eq!(collection.defaults.auth, bearer);
+        let variables = VariableService::new(db.clone())
+            .get_resolved(Some(&report.workspace_id), None, None)
+            .unwrap();
+        let base_url = variables.iter().find(|v| v.name == "petstore_base_url").unwrap();
+        assert_eq!(base_url.value, "https://eu.example.com/v1");
+        assert!(variables.iter().any(|v| v.name == "api_key"));
+
+        let requests = RequestService::new(db.clone()).get_by_collection(&collection.id).unwrap();
+        let find = |name: &str| requests.iter().find(|r| r.name == name).unwrap();
+        let get = find("Get pet");
+        assert_eq!(get.url, "/pets/42");
+        assert_eq!((get.params[0].value.as_str(), get.params[0].enabled), ("owner", true));
+        assert!(!get.headers[0].enabled);
+        assert_eq!(get.auth, RequestAuth::Inherit);
+
+        let create = find("createPet");
+        assert_eq!(create.body_type, BodyType::Json);
+        let body: serde_json::Value = serde_json::from_str(&create.body).unwrap();
+        assert_eq!(body["name"], "Rex");
+        assert_eq!(body["tags"], serde_json::json!(["string"]));
+        assert_eq!(body["owner"], serde_json::Value::Null);
+        assert!(matches!(&create.auth, RequestAuth::ApiKey { key, .. } if key == "X-API-Key"));
+        assert_eq!(find("GET /health").auth, RequestAuth::None);
+
+        let swagger = r#"{
+            "swagger": "2.0",
+            "info": { "title": "Legacy" },
+            "host": "legacy.example.com",
+            "basePath": "/api",
+            "securityDefinitions": { "basic": { "type": "basic" } },
+            "paths": {
+                "/login": {
+                    "post": {
+                        "security": [{ "basic": [] }],
+                        "parameters": [
+                            { "name": "user", "in": "formData", "type": "string", "default": "me" }
+                        ]
+                    },
+                    "trace": {}
+                }
+            }
+        }"#;
+        let report = import(swagger);
+        assert_eq!(report.unsupported[0].message, "TRACE is not supported");
+        let login = RequestService::new(db.clone())
+            .get_by_collection(&report.collection_ids[0])
+            .unwrap()
+            .remove(0);
+        assert_eq!(login.body_type, BodyType::XWwwFormUrlencoded);
+        assert_eq!(login.form_data[0].value, "me");
+        assert!(matches!(login.auth, RequestAuth::Basic { .. }));
+
+        // A second API in the same workspace keeps its own server, and a form
+        // schema made of itself ends
+        let orders = r##"{
+            "openapi": "3.0.0",
+            "info": { "title": "Orders" },
+            "servers": [{ "url": "https://orders.example.com" }],
+            "paths": {
+                "/orders": {
+                    "post": {
+                        "requestBody": {
+                            "content": {
+                                "application/x-www-form-urlencoded": {
+                                    "schema": { "$ref": "#/components/schemas/Order" }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Order": {
+                        "properties": { "id": { "type": "string" } },
+                        "allOf": [{ "$ref": "#/components/schemas/Order" }]
+                    }
+                }
+            }
+        }"##;
+        let second = import_openapi(&service, orders, Some(report.workspace_id.clone()));
+        let variables = VariableService::new(db.clone())
+            .get_resolved(Some(&second.workspace_id), None, None)
+            .unwrap();
+        let value = |name: &str| variables.iter().find(|v| v.name == name).unwrap().value.clone();
+        assert_eq!(value("orders_base_url"), "https://orders.example.com");
+        assert_eq!(value("legacy_base_url"), "https://legacy.example.com/api");
+        let order = RequestService::new(db.clone())
+            .get_by_collection(&second.collection_ids[0])
+            .unwrap()
+            .remove(0);
+        assert_eq!(order.form_data.len(), 1);
+    }
+
+    fn import_postman(service: &InteropService, content: &str) -> ImportReport {
+        let path = temp_dir().join(format!("resona_test_{}.json", uuid::Uuid::new_v4()));
+        fs::write(&path, content).unwrap();
+        let report = service
+            .import_postman(ImportFileInput {
+                path: path.to_string_lossy().to_string(),
+                workspace_id: None,
+            })
+            .expect("Failed to import collection");
+        fs::remove_file(&path).ok();
+        report
+    }
+
+    #[test]
+    fn test_postman_export_round_trips() {
+        let db = create_test_db();
+        let service = InteropService::new(db.clone());
+        let import = |content: &str| import_postman(&service, content);
+
+        let first = import(COLLECTION);
+        let exported = service
+            .export_postman_collection(&first.collection_ids[0], SecretExport::Mask)
+            .unwrap();
+        // Postman's JavaScript comes back as it was, and secrets are masked
+        assert!(exported.contains("\"console.log(1)\""));
+        assert!(!exported.contains("s3cret"));
+
+        let second = import(&exported);
+        assert_eq!((second.folders, second.requests, second.variables), (1, 3, 2));
+        let requests = RequestService::new(db.clone());
+        let sorted = |collection_id: &str| {
+            let mut all = requests.get_by_collection(collection_id).unwrap();
+            all.sort_by(|a, b| a.name.cmp(&b.name));
+            all
+        };
+        for (before, after) in sorted(&first.collection_ids[0])
+            .iter()
+            .zip(sorted(&second.collection_ids[0]).iter())
+        {
+            assert_eq!(before.name, after.name);
+            assert_eq!(before.method, after.method);
+            assert_eq!(before.url, after.url);
+            assert_eq!(before.params.len(), after.params.len());
+            assert_eq!(before.body_type, after.body_type);
+            assert_eq!(before.body, after.body);
+            assert_eq!(before.auth, after.auth);
+            assert_eq!(before.scripts, after.scripts);
+        }
+
+        let workspace = service
+            .export_postman_workspace(&first.workspace_id, SecretExport::Omit)
+            .unwrap();
+        let third = import(&workspace);
+        // The collection becomes a folder around the original one
+        assert_eq!((third.folders, third.requests, third.variables), (2, 3, 1));
+        // and each folder is numbered among its own siblings
+        let folders = FolderService::new(db.clone())
+            .get_by_collection(&third.collection_ids[0])
+            .unwrap();
+        assert!(folders.iter().all(|f| f.sort_order == 0));
+    }
+
+    #[test]
+    fn test_postman_export_keeps_collection_defaults() {
+        let db = create_test_db();
+        let service = InteropService::new(db.clone());
+        let header = |key: &str, value: &str| RequestHeader {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+        };
+        let defaults = CollectionDefaults {
+            base_url: "https://api.example.com/v1".to_string(),
+            headers: vec![header("X-Client", "resona")],
+            params: vec![RequestParam {
+                key: "format".to_string(),
+                value: "json".to_string(),
+                enabled: true,
+            }],
+            ..Default::default()
+        };
+        let collection = CollectionService::new(db.clone())
+            .create(CreateCollectionInput {
+                name: "Shop".to_string(),
+                workspace_id: "ws".to_string(),
+                defaults: defaults.clone(),
+                ..Default::default()
+            })
+            .expect("Failed to create collection");
+        let requests = RequestService::new(db.clone());
+        for (name, url, headers) in [
+            ("Users", "/users", Vec::new()),
+            ("Orders", "/orders", vec![header("X-Client", "cli")]),
+        ] {
+            requests
+                .create(CreateRequestInput {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                    headers,
+                    collection_id: Some(collection.id.clone()),
+                    workspace_id: "ws".to_string(),
+                    ..Default::default()
+                })
+                .expect("Failed to create request");
+        }
+
+        let exported = service
+            .export_postman_collection(&collection.id, SecretExport::Omit)
+            .unwrap();
+        assert!(exported.contains("\"{{base_url}}/users?format=json\""));
+
+        let report = import_postman(&service, &exported);
+        assert_eq!(report.variables, 0);
+        let imported = CollectionService::new(db.clone()).get(&report.collection_ids[0]).unwrap();
+        assert_eq!(imported.defaults.base_url, defaults.base_url);
+        assert_eq!(imported.defaults.headers, defaults.headers);
+        assert_eq!(imported.defaults.params, defaults.params);
+        let mut requests = requests.get_by_collection(&imported.id).unwrap();
+        requests.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(requests[0].url, "/orders");
+        assert_eq!(requests[0].headers, vec![header("X-Client", "cli")]);
+        assert_eq!(requests[1].url, "/users");
+        assert!(requests[1].headers.is_empty() && requests[1].params.is_empty());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::collections::{Collection, CollectionService};
+use crate::db::helpers::{index_add, put_row};
+use crate::db::{
+    Database, DbResult, COLLECTIONS, COLLECTIONS_BY_WORKSPACE, FOLDERS, FOLDERS_BY_COLLECTION,
+    REQUESTS, REQUESTS_BY_COLLECTION, REQUESTS_BY_FOLDER, REQUESTS_BY_WORKSPACE, VARIABLES,
+    VARIABLES_BY_SCOPE, WORKSPACES,
+};
+use crate::folders::Folder;
+use crate::requests::Request;
+use crate::variables::Variable;
+use crate::workspaces::Workspace;
+
+/// Records produced by an importer, in the order they are written. Folders come
+/// before their children, and folders and requests carry their final
+/// `sort_order`; collections are appended after the workspace's existing ones.
+#[derive(Debug, Default)]
+pub struct ImportTree {
+    pub workspace_id: String,
+    /// Set when the import creates its workspace
+    pub workspace: Option<Workspace>,
+    pub collections: Vec<Collection>,
+    pub folders: Vec<Folder>,
+    pub requests: Vec<Request>,
+    pub variables: Vec<Variable>,
+}
+
+impl ImportTree {
+    pub fn write(&mut self, db: &Database) -> DbResult<()> {
+        let write_txn = db.begin_write()?;
+
+        if let Some(workspace) = &self.workspace {
+            put_row(&write_txn, WORKSPACES, &workspace.id, workspace)?;
+        }
+
+        for collection in &mut self.collections {
+            collection.sort_order =
+                CollectionService::next_sort_order_in(&write_txn, &collection.workspace_id)?;
+            put_row(&write_txn, COLLECTIONS, &collection.id, collection)?;
+            index_add(
+                &write_txn,
+                COLLECTIONS_BY_WORKSPACE,
+                &collection.workspace_id,
+                &collection.id,
+            )?;
+        }
+
+        for folder in &self.folders {
+            put_row(&write_txn, FOLDERS, &folder.id, folder)?;
+            index_add(&write_txn, FOLDERS_BY_COLLECTION, &folder.collection_id, &folder.id)?;
+        }
+
+        for request in &self.requests {
+            put_row(&write_txn, REQUESTS, &request.id, request)?;
+            index_add(&write_txn, REQUESTS_BY_WORKSPACE, &request.workspace_id, &request.id)?;
+            if let Some(collection_id) = &request.collection_id {
+                index_add(&write_txn, REQUESTS_BY_COLLECTION, collection_id, &request.id)?;
+            }
+            if let Some(folder_id) = &request.folder_id {
+                index_add(&write_txn, REQUESTS_BY_FOLDER, folder_id, &request.id)?;
+            }
+        }
+
+        for variable in &self.variables {
+            put_row(&write_txn, VARIABLES, &variable.id, variable)?;
+            index_add(&write_txn, VARIABLES_BY_SCOPE, &variable.scope_key(), &variable.id)?;
+        }
+
+        write_txn.commit()?;
+
+        Ok(())
+    }
+}
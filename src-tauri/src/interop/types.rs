@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportFileInput {
+    pub path: String,
+    /// Workspace to import into; a new workspace is created when `None`
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+}
+
+/// What an import created, and what it had to leave out or change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub workspace_id: String,
+    pub collection_ids: Vec<String>,
+    pub folders: usize,
+    pub requests: usize,
+    pub variables: usize,
+    pub unsupported: Vec<UnsupportedItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsupportedItem {
+    /// Folder and request names leading to the item, e.g. `Users / Create user`;
+    /// empty for the file as a whole
+    pub path: String,
+    pub message: String,
+}
@@ -1,30 +1,65 @@
 // Resona - API Client Application
 
+mod assertions;
 mod collections;
 mod db;
+mod environments;
+mod extractions;
+mod folders;
 mod http;
+mod interop;
 mod requests;
+mod runner;
+mod scripts;
 mod settings;
 mod variables;
+mod vault;
 mod workspaces;
 
+use assertions::AssertionResult;
 use db::Database;
-use http::{HttpRequest, HttpResponse};
+use extractions::ExtractionService;
+use http::{ClientOptions, HttpRequest, HttpResponse};
+use requests::RequestService;
+use scripts::ScriptService;
+use tauri::State;
+use variables::{template, VariableService};
+use workspaces::WorkspaceService;
 
 use collections::{
     create_collection, delete_collection, get_collection, get_collections,
-    get_collections_by_workspace, update_collection,
+    get_collections_by_workspace, reorder_collections, update_collection,
+};
+use environments::{
+    create_environment, delete_environment, get_environment, get_environments_by_workspace,
+    set_active_environment, update_environment,
+};
+use folders::{
+    create_folder, delete_folder, get_folder, get_folders_by_collection, move_folder,
+    reorder_folders, update_folder,
+};
+use interop::{
+    export_postman_collection, export_postman_workspace, import_openapi_spec,
+    import_postman_collection,
 };
 use requests::{
-    create_request, delete_request, get_all_requests_by_workspace, get_request,
-    get_requests_by_collection, get_standalone_requests_by_workspace, update_request,
+    create_request, delete_request, get_all_requests_by_workspace, get_effective_request,
+    get_request, get_requests_by_collection, get_requests_by_folder,
+    get_standalone_requests_by_workspace, move_request, reorder_requests, update_request,
+};
+use runner::{
+    delete_collection_run, export_collection_run, get_collection_run, get_collection_runs,
+    run_collection,
 };
 use settings::{get_settings, reset_settings, update_settings};
 use variables::{
-    create_variable, delete_variable, get_collection_variables, get_global_variables,
-    get_request_variables, get_resolved_variables, get_variable, get_workspace_variables,
-    update_variable,
+    copy_workspace_variables, create_variable, delete_variable, export_variables,
+    find_variable_usages, get_collection_variables, get_environment_variables,
+    get_global_variables, get_request_variables, get_resolved_variables, get_sync_status,
+    get_variable, get_workspace_variables, import_variables, lint_variables, rename_variable,
+    resolve_sync_conflict, reveal_variable, update_variable,
 };
+use vault::{get_vault_status, lock_vault, setup_vault, unlock_vault};
 use workspaces::{
     add_workspace_to_sync_group, create_sync_group, create_workspace, delete_sync_group,
     delete_workspace, get_sync_group, get_sync_group_for_workspace, get_sync_groups,
@@ -32,9 +67,96 @@ use workspaces::{
     update_sync_group, update_workspace,
 };
 
+/// Send a request built by the frontend. When `request_id` names a stored request,
+/// its auth, client settings and collection defaults apply, and its collection
+/// and request scripts run around the send.
 #[tauri::command]
-async fn send_http_request(request: HttpRequest) -> Result<HttpResponse, String> {
-    http::execute_request(request).await
+async fn send_http_request(
+    db: State<'_, Database>,
+    request: HttpRequest,
+    workspace_id: Option<String>,
+    request_id: Option<String>,
+) -> Result<HttpResponse, String> {
+    send(db.inner().clone(), request, workspace_id, request_id).await
+}
+
+async fn send(
+    db: Database,
+    mut request: HttpRequest,
+    workspace_id: Option<String>,
+    request_id: Option<String>,
+) -> Result<HttpResponse, String> {
+    let options = match &workspace_id {
+        Some(id) => WorkspaceService::new(db.clone())
+            .get(id)
+            .map_err(|e| e.to_string())?
+            .client_options(),
+        None => ClientOptions::default(),
+    };
+    // Requests that haven't been saved yet have no scripts to run
+    let stored = request_id.and_then(|id| RequestService::new(db.clone()).get(&id).ok());
+    if let Some(stored) = &stored {
+        request = RequestService::new(db.clone())
+            .effective_send(stored, &request)
+            .map_err(|e| e.to_string())?;
+    }
+
+    let scripts = ScriptService::new(db.clone());
+    let mut console = Vec::new();
+    let (resolved, unavailable) = match &stored {
+        Some(stored) => VariableService::new(db.clone()).resolve_context(
+            Some(&stored.workspace_id),
+            stored.collection_id.as_deref(),
+            Some(&stored.id),
+        ),
+        None => {
+            VariableService::new(db.clone()).resolve_context(workspace_id.as_deref(), None, None)
+        }
+    }
+    .map_err(|e| e.to_string())?;
+    let mut variables = template::variable_map(&resolved);
+    let types = template::variable_types(&resolved);
+    if let Some(stored) = &stored {
+        scripts
+            .pre_request(stored, &mut request, &mut variables, &mut console)
+            .map_err(|e| e.to_string())?;
+    }
+    // The frontend substitutes plain variables outside JSON bodies; JSON bodies,
+    // dynamic variables, function calls and values set by scripts are resolved here
+    template::interpolate_request(&mut request, &variables, &types);
+    template::check_available(&request, &unavailable).map_err(|e| e.to_string())?;
+
+    let sent = request.clone();
+    let mut response = http::execute_request(request, &options).await?;
+    // The editor doesn't send assertions; a stored request is checked against its own
+    let checks = match &stored {
+        Some(stored) => &stored.assertions,
+        None => &sent.assertions,
+    };
+    response.assertions = assertions::evaluate(checks, &response);
+
+    if let Some(stored) = &stored {
+        // The response is kept when extractions fail, as a run does
+        match ExtractionService::new(db.clone()).apply(stored, &response, &mut variables) {
+            Ok(extractions) => response.extractions = extractions,
+            Err(e) => response.assertions.push(AssertionResult {
+                name: "Extractions".to_string(),
+                passed: false,
+                message: Some(e.to_string()),
+            }),
+        }
+        if let Err(e) =
+            scripts.post_response(stored, &sent, &response, &mut variables, &mut console)
+        {
+            response.assertions.push(AssertionResult {
+                name: "Post-response script".to_string(),
+                passed: false,
+                message: Some(e.to_string()),
+            });
+        }
+    }
+    response.console = console;
+    Ok(response)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -67,27 +189,73 @@ pub fn run() {
             get_collections_by_workspace,
             create_collection,
             update_collection,
+            reorder_collections,
             delete_collection,
+            // Folder commands
+            get_folder,
+            get_folders_by_collection,
+            create_folder,
+            update_folder,
+            move_folder,
+            reorder_folders,
+            delete_folder,
             // Request commands
             get_request,
+            get_effective_request,
             get_requests_by_collection,
+            get_requests_by_folder,
             get_standalone_requests_by_workspace,
             get_all_requests_by_workspace,
             create_request,
             update_request,
+            move_request,
+            reorder_requests,
             delete_request,
             // Variable commands
             get_variable,
             get_global_variables,
             get_workspace_variables,
+            get_environment_variables,
             get_collection_variables,
             get_request_variables,
             get_resolved_variables,
             create_variable,
             update_variable,
             delete_variable,
+            reveal_variable,
+            find_variable_usages,
+            rename_variable,
+            copy_workspace_variables,
+            lint_variables,
+            import_variables,
+            export_variables,
+            get_sync_status,
+            resolve_sync_conflict,
+            // Vault commands
+            get_vault_status,
+            setup_vault,
+            unlock_vault,
+            lock_vault,
+            // Environment commands
+            get_environment,
+            get_environments_by_workspace,
+            create_environment,
+            update_environment,
+            delete_environment,
+            set_active_environment,
+            // Import and export commands
+            import_postman_collection,
+            import_openapi_spec,
+            export_postman_collection,
+            export_postman_workspace,
             // HTTP client
             send_http_request,
+            // Collection runner commands
+            run_collection,
+            get_collection_run,
+            get_collection_runs,
+            delete_collection_run,
+            export_collection_run,
             // Settings commands
             get_settings,
             update_settings,
@@ -96,3 +264,135 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assertions::{Assertion, AssertionCheck};
+    use crate::collections::{
+        Collection, CollectionDefaults, CollectionService, CreateCollectionInput,
+    };
+    use crate::extractions::{Extraction, ExtractionSource};
+    use crate::requests::{CreateRequestInput, RequestService};
+    use crate::variables::{CreateVariableInput, VariableScope};
+    use crate::vault::{VaultCredentials, VaultService};
+    use std::env::temp_dir;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    fn create_test_db() -> Database {
+        let path = temp_dir().join(format!("resona_test_{}.redb", uuid::Uuid::new_v4()));
+        Database::open_at(path).expect("Failed to create test database")
+    }
+
+    /// Answer every request with 200 and return the server's base URL
+    fn spawn_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind test server");
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                );
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    /// Collection whose requests are relative to a fresh test server
+    fn create_test_collection(db: &Database) -> Collection {
+        CollectionService::new(db.clone())
+            .create(CreateCollectionInput {
+                name: "Users API".to_string(),
+                workspace_id: "ws".to_string(),
+                defaults: CollectionDefaults {
+                    base_url: spawn_server(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .expect("Failed to create collection")
+    }
+
+    #[tokio::test]
+    async fn test_send_checks_stored_assertions() {
+        let db = create_test_db();
+        let collection = create_test_collection(&db);
+        let stored = RequestService::new(db.clone())
+            .create(CreateRequestInput {
+                name: "Create user".to_string(),
+                url: "/users".to_string(),
+                assertions: vec![Assertion {
+                    enabled: true,
+                    check: AssertionCheck::StatusEquals { status: 201 },
+                }],
+                collection_id: Some(collection.id.clone()),
+                workspace_id: collection.workspace_id.clone(),
+                ..Default::default()
+            })
+            .expect("Failed to create request");
+
+        // As the editor sends it: no assertions, no collection defaults
+        let mut edited = stored.to_http_request();
+        edited.assertions.clear();
+        let response = send(db, edited, None, Some(stored.id))
+            .await
+            .expect("Send failed");
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.assertions.len(), 1);
+        assert!(!response.assertions[0].passed);
+    }
+
+    #[tokio::test]
+    async fn test_send_keeps_the_response_when_extractions_fail() {
+        let db = create_test_db();
+        let collection = create_test_collection(&db);
+        let stored = RequestService::new(db.clone())
+            .create(CreateRequestInput {
+                name: "Login".to_string(),
+                url: "/login".to_string(),
+                extractions: vec![Extraction {
+                    enabled: true,
+                    variable: "token".to_string(),
+                    scope: VariableScope::Workspace,
+                    source: ExtractionSource::Regex {
+                        pattern: "ok".to_string(),
+                        group: None,
+                    },
+                }],
+                collection_id: Some(collection.id.clone()),
+                workspace_id: collection.workspace_id.clone(),
+                ..Default::default()
+            })
+            .expect("Failed to create request");
+        VariableService::new(db.clone())
+            .create(CreateVariableInput {
+                name: "token".to_string(),
+                value: String::new(),
+                scope: VariableScope::Workspace,
+                scope_id: Some(stored.workspace_id.clone()),
+                is_secret: true,
+                ..Default::default()
+            })
+            .expect("Failed to create variable");
+        let vault = VaultService::new(db.clone());
+        vault
+            .setup(VaultCredentials::Password {
+                password: "correct horse".to_string(),
+            })
+            .expect("Failed to set up vault");
+        vault.lock().expect("Failed to lock");
+
+        // Writing the secret needs the vault, which is locked
+        let response = send(db, stored.to_http_request(), None, Some(stored.id))
+            .await
+            .expect("Send failed");
+        assert_eq!(response.status, 200);
+        assert_eq!(response.assertions[0].name, "Extractions");
+        assert!(!response.assertions[0].passed);
+    }
+}
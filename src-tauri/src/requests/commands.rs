@@ -3,7 +3,7 @@ use tauri::State;
 use crate::db::Database;
 
 use super::service::RequestService;
-use super::types::{CreateRequestInput, Request, UpdateRequestInput};
+use super::types::{CreateRequestInput, MoveRequestInput, Request, UpdateRequestInput};
 
 #[tauri::command]
 pub fn get_request(db: State<Database>, id: String) -> Result<Request, String> {
@@ -11,6 +11,13 @@ pub fn get_request(db: State<Database>, id: String) -> Result<Request, String> {
     service.get(&id).map_err(|e| e.to_string())
 }
 
+/// The request with collection defaults applied, exactly as the sender will use it
+#[tauri::command]
+pub fn get_effective_request(db: State<Database>, id: String) -> Result<Request, String> {
+    let service = RequestService::new(db.inner().clone());
+    service.get_effective(&id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_requests_by_collection(
     db: State<Database>,
@@ -20,6 +27,15 @@ pub fn get_requests_by_collection(
     service.get_by_collection(&collection_id).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn get_requests_by_folder(
+    db: State<Database>,
+    folder_id: String,
+) -> Result<Vec<Request>, String> {
+    let service = RequestService::new(db.inner().clone());
+    service.get_by_folder(&folder_id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_standalone_requests_by_workspace(
     db: State<Database>,
@@ -56,6 +72,18 @@ pub fn update_request(
     service.update(input).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn move_request(db: State<Database>, input: MoveRequestInput) -> Result<Request, String> {
+    let service = RequestService::new(db.inner().clone());
+    service.move_request(input).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn reorder_requests(db: State<Database>, ids: Vec<String>) -> Result<(), String> {
+    let service = RequestService::new(db.inner().clone());
+    service.reorder(&ids).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn delete_request(db: State<Database>, id: String) -> Result<(), String> {
     let service = RequestService::new(db.inner().clone());
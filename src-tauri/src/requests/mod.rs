@@ -4,8 +4,10 @@ mod types;
 
 pub use commands::*;
 #[allow(unused_imports)]
+pub use crate::http::HttpVersion;
+#[allow(unused_imports)]
 pub use types::{
-    BodyType, CreateRequestInput, FormDataItem, HttpMethod, Request, RequestHeader,
-    RequestParam, UpdateRequestInput,
+    ApiKeyLocation, BodyType, CreateRequestInput, FormDataItem, HttpMethod, MoveRequestInput,
+    Request, RequestAuth, RequestHeader, RequestParam, UpdateRequestInput,
 };
 pub(crate) use service::RequestService;
@@ -1,11 +1,19 @@
 use chrono::Utc;
-use redb::ReadableTable;
+use redb::{ReadableTable, WriteTransaction};
 
+use crate::collections::{Collection, CollectionService};
+use crate::db::helpers::{get_row, index_add, index_get, index_remove, put_row, remove_row};
 use crate::db::{
-    Database, DbError, DbResult, REQUESTS, REQUESTS_BY_COLLECTION, REQUESTS_BY_WORKSPACE,
+    Database, DbError, DbResult, COLLECTIONS, FOLDERS, REQUESTS, REQUESTS_BY_COLLECTION,
+    REQUESTS_BY_FOLDER, REQUESTS_BY_WORKSPACE,
 };
+use crate::folders::Folder;
+use crate::http::HttpRequest;
 
-use super::types::{CreateRequestInput, Request, UpdateRequestInput};
+use super::types::{
+    CreateRequestInput, FormDataItem, MoveRequestInput, Request, RequestAuth, RequestHeader,
+    RequestParam, UpdateRequestInput,
+};
 
 pub struct RequestService {
     db: Database,
@@ -30,6 +38,71 @@ impl RequestService {
         Ok(request)
     }
 
+    /// The request with its collection defaults merged in, as it will be sent
+    pub fn get_effective(&self, id: &str) -> DbResult<Request> {
+        let request = self.get(id)?;
+        self.resolve_effective(request)
+    }
+
+    /// Merge collection defaults into a request that may not be saved yet
+    pub fn resolve_effective(&self, mut request: Request) -> DbResult<Request> {
+        match request.collection_id {
+            Some(ref collection_id) => {
+                let collection = CollectionService::new(self.db.clone()).get(collection_id)?;
+                Ok(collection.defaults.apply(request))
+            }
+            None => {
+                if request.auth == RequestAuth::Inherit {
+                    request.auth = RequestAuth::None;
+                }
+                Ok(request)
+            }
+        }
+    }
+
+    /// What to send for a stored request open in the editor: the unsaved fields
+    /// in `edited` replace the stored ones, then the stored auth and client
+    /// settings and the collection defaults are applied as for a saved request
+    pub fn effective_send(&self, stored: &Request, edited: &HttpRequest) -> DbResult<HttpRequest> {
+        let mut request = stored.clone();
+        request.url = edited.url.clone();
+        request.headers = edited
+            .headers
+            .iter()
+            .map(|h| RequestHeader {
+                key: h.key.clone(),
+                value: h.value.clone(),
+                enabled: h.enabled,
+            })
+            .collect();
+        request.params = edited
+            .params
+            .iter()
+            .map(|p| RequestParam {
+                key: p.key.clone(),
+                value: p.value.clone(),
+                enabled: p.enabled,
+            })
+            .collect();
+        request.body = edited.body.clone();
+        request.form_data = edited
+            .form_data
+            .iter()
+            .map(|f| FormDataItem {
+                key: f.key.clone(),
+                value: f.value.clone(),
+                item_type: f.item_type.clone(),
+                enabled: f.enabled,
+            })
+            .collect();
+
+        let mut http = self.resolve_effective(request)?.to_http_request();
+        http.method = edited.method.clone();
+        http.body_type = edited.body_type.clone();
+        http.http_version = edited.http_version.clone();
+        Ok(http)
+    }
+
     pub fn get_by_collection(&self, collection_id: &str) -> DbResult<Vec<Request>> {
         let read_txn = self.db.begin_read()?;
         let idx_table = read_txn.open_table(REQUESTS_BY_COLLECTION)?;
@@ -50,7 +123,39 @@ impl RequestService {
             }
         }
 
-        requests.sort_by(|a, b| a.name.cmp(&b.name));
+        requests.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(requests)
+    }
+
+    pub fn get_by_folder(&self, folder_id: &str) -> DbResult<Vec<Request>> {
+        let read_txn = self.db.begin_read()?;
+        let idx_table = read_txn.open_table(REQUESTS_BY_FOLDER)?;
+
+        let request_ids: Vec<String> = match idx_table.get(folder_id)? {
+            Some(value) => serde_json::from_str(value.value())
+                .map_err(|e| DbError::Serialization(e.to_string()))?,
+            None => return Ok(Vec::new()),
+        };
+
+        drop(idx_table);
+        drop(read_txn);
+
+        let mut requests = Vec::new();
+        for id in request_ids {
+            if let Ok(request) = self.get(&id) {
+                requests.push(request);
+            }
+        }
+
+        requests.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
         Ok(requests)
     }
 
@@ -76,7 +181,11 @@ impl RequestService {
             }
         }
 
-        requests.sort_by(|a, b| a.name.cmp(&b.name));
+        requests.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
         Ok(requests)
     }
 
@@ -100,7 +209,11 @@ impl RequestService {
             }
         }
 
-        requests.sort_by(|a, b| a.name.cmp(&b.name));
+        requests.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
         Ok(requests)
     }
 
@@ -112,12 +225,32 @@ impl RequestService {
         request.body_type = input.body_type;
         request.body = input.body;
         request.form_data = input.form_data;
+        request.http_version = input.http_version;
+        request.auth = input.auth;
+        request.timeout_ms = input.timeout_ms;
+        request.validate_ssl = input.validate_ssl;
+        request.assertions = input.assertions;
+        request.extractions = input.extractions;
+        request.scripts = input.scripts;
         request.collection_id = input.collection_id.clone();
+        request.folder_id = input.folder_id.clone();
+
+        let write_txn = self.db.begin_write()?;
 
+        request.sort_order = Self::next_sort_order_in(&write_txn, &request)?;
         let json = serde_json::to_string(&request)
             .map_err(|e| DbError::Serialization(e.to_string()))?;
 
-        let write_txn = self.db.begin_write()?;
+        if let Some(ref folder_id) = input.folder_id {
+            let folder = Self::folder_in(&write_txn, folder_id)?;
+            if input.collection_id.as_deref() != Some(folder.collection_id.as_str()) {
+                return Err(DbError::InvalidInput(format!(
+                    "Folder {} does not belong to the request's collection",
+                    folder_id
+                )));
+            }
+            index_add(&write_txn, REQUESTS_BY_FOLDER, folder_id, &request.id)?;
+        }
 
         {
             let mut table = write_txn.open_table(REQUESTS)?;
@@ -167,9 +300,19 @@ impl RequestService {
         Ok(request)
     }
 
+    /// Update a request in place. Moving it to another collection also moves
+    /// it out of its folder and renumbers it, so that goes through
+    /// `move_request` instead.
     pub fn update(&self, input: UpdateRequestInput) -> DbResult<Request> {
         let mut request = self.get(&input.id)?;
-        let old_collection_id = request.collection_id.clone();
+        if input
+            .collection_id
+            .is_some_and(|collection_id| collection_id != request.collection_id)
+        {
+            return Err(DbError::InvalidInput(
+                "Use move_request to move a request to another collection".to_string(),
+            ));
+        }
 
         if let Some(name) = input.name {
             request.name = name;
@@ -195,8 +338,26 @@ impl RequestService {
         if let Some(form_data) = input.form_data {
             request.form_data = form_data;
         }
-        if let Some(collection_id) = input.collection_id {
-            request.collection_id = collection_id;
+        if let Some(http_version) = input.http_version {
+            request.http_version = http_version;
+        }
+        if let Some(auth) = input.auth {
+            request.auth = auth;
+        }
+        if let Some(timeout_ms) = input.timeout_ms {
+            request.timeout_ms = timeout_ms;
+        }
+        if let Some(validate_ssl) = input.validate_ssl {
+            request.validate_ssl = validate_ssl;
+        }
+        if let Some(assertions) = input.assertions {
+            request.assertions = assertions;
+        }
+        if let Some(extractions) = input.extractions {
+            request.extractions = extractions;
+        }
+        if let Some(scripts) = input.scripts {
+            request.scripts = scripts;
         }
         request.updated_at = Utc::now();
 
@@ -210,89 +371,365 @@ impl RequestService {
             table.insert(request.id.as_str(), json.as_str())?;
         }
 
-        // Update collection index if collection changed
-        if old_collection_id != request.collection_id {
-            // Remove from old collection index
-            if let Some(ref old_id) = old_collection_id {
-                let mut idx_table = write_txn.open_table(REQUESTS_BY_COLLECTION)?;
-                let ids_json = idx_table
-                    .get(old_id.as_str())?
-                    .map(|v| v.value().to_string())
-                    .unwrap_or_else(|| "[]".to_string());
-                let mut ids: Vec<String> = serde_json::from_str(&ids_json)
-                    .map_err(|e| DbError::Serialization(e.to_string()))?;
-                ids.retain(|i| i != &request.id);
-                let new_json = serde_json::to_string(&ids)
-                    .map_err(|e| DbError::Serialization(e.to_string()))?;
-                idx_table.insert(old_id.as_str(), new_json.as_str())?;
-            }
+        write_txn.commit()?;
 
-            // Add to new collection index
-            if let Some(ref new_id) = request.collection_id {
-                let mut idx_table = write_txn.open_table(REQUESTS_BY_COLLECTION)?;
-                let ids_json = idx_table
-                    .get(new_id.as_str())?
-                    .map(|v| v.value().to_string())
-                    .unwrap_or_else(|| "[]".to_string());
-                let mut ids: Vec<String> = serde_json::from_str(&ids_json)
-                    .map_err(|e| DbError::Serialization(e.to_string()))?;
-                if !ids.contains(&request.id) {
-                    ids.push(request.id.clone());
+        Ok(request)
+    }
+
+    pub fn delete(&self, id: &str) -> DbResult<()> {
+        let request = self.get(id)?;
+
+        let write_txn = self.db.begin_write()?;
+        Self::delete_in(&write_txn, &request)?;
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Move a request between collections and folders, keeping every index in step
+    pub fn move_request(&self, input: MoveRequestInput) -> DbResult<Request> {
+        let mut request = self.get(&input.id)?;
+
+        let write_txn = self.db.begin_write()?;
+
+        let collection_id = match input.folder_id {
+            Some(ref folder_id) => {
+                let folder = Self::folder_in(&write_txn, folder_id)?;
+                if let Some(ref collection_id) = input.collection_id {
+                    if collection_id != &folder.collection_id {
+                        return Err(DbError::InvalidInput(format!(
+                            "Folder {} does not belong to collection {}",
+                            folder_id, collection_id
+                        )));
+                    }
                 }
-                let new_json = serde_json::to_string(&ids)
-                    .map_err(|e| DbError::Serialization(e.to_string()))?;
-                idx_table.insert(new_id.as_str(), new_json.as_str())?;
+                Some(folder.collection_id)
+            }
+            None => input.collection_id.clone(),
+        };
+
+        if let Some(ref collection_id) = collection_id {
+            let collection: Collection = get_row(&write_txn, COLLECTIONS, collection_id)?
+                .ok_or_else(|| {
+                    DbError::NotFound(format!("Collection not found: {}", collection_id))
+                })?;
+            if collection.workspace_id != request.workspace_id {
+                return Err(DbError::InvalidInput(
+                    "Requests cannot be moved to a collection in another workspace".to_string(),
+                ));
             }
         }
 
+        Self::detach_in(&write_txn, &request)?;
+        request.collection_id = collection_id;
+        request.folder_id = input.folder_id;
+        request.sort_order = Self::next_sort_order_in(&write_txn, &request)?;
+        request.updated_at = Utc::now();
+        Self::attach_in(&write_txn, &request)?;
+        put_row(&write_txn, REQUESTS, &request.id, &request)?;
+
         write_txn.commit()?;
 
         Ok(request)
     }
 
-    pub fn delete(&self, id: &str) -> DbResult<()> {
-        let request = self.get(id)?;
-
+    /// Set the position of each request to its index in `ids`, in one transaction
+    pub fn reorder(&self, ids: &[String]) -> DbResult<()> {
         let write_txn = self.db.begin_write()?;
+        let now = Utc::now();
 
-        // Remove from requests table
-        {
-            let mut table = write_txn.open_table(REQUESTS)?;
-            table.remove(id)?;
+        let mut requests = Vec::new();
+        for id in ids {
+            let request: Request = get_row(&write_txn, REQUESTS, id)?
+                .ok_or_else(|| DbError::NotFound(format!("Request not found: {}", id)))?;
+            requests.push(request);
         }
-
-        // Update workspace index
-        {
-            let mut idx_table = write_txn.open_table(REQUESTS_BY_WORKSPACE)?;
-            let ids_json = idx_table
-                .get(request.workspace_id.as_str())?
-                .map(|v| v.value().to_string())
-                .unwrap_or_else(|| "[]".to_string());
-            let mut ids: Vec<String> = serde_json::from_str(&ids_json)
-                .map_err(|e| DbError::Serialization(e.to_string()))?;
-            ids.retain(|i| i != id);
-            let new_json = serde_json::to_string(&ids)
-                .map_err(|e| DbError::Serialization(e.to_string()))?;
-            idx_table.insert(request.workspace_id.as_str(), new_json.as_str())?;
+        if requests.windows(2).any(|pair| !Self::are_siblings(&pair[0], &pair[1])) {
+            return Err(DbError::InvalidInput(
+                "Only requests in the same folder or collection can be reordered together"
+                    .to_string(),
+            ));
         }
 
-        // Update collection index if applicable
-        if let Some(ref collection_id) = request.collection_id {
-            let mut idx_table = write_txn.open_table(REQUESTS_BY_COLLECTION)?;
-            let ids_json = idx_table
-                .get(collection_id.as_str())?
-                .map(|v| v.value().to_string())
-                .unwrap_or_else(|| "[]".to_string());
-            let mut ids: Vec<String> = serde_json::from_str(&ids_json)
-                .map_err(|e| DbError::Serialization(e.to_string()))?;
-            ids.retain(|i| i != id);
-            let new_json = serde_json::to_string(&ids)
-                .map_err(|e| DbError::Serialization(e.to_string()))?;
-            idx_table.insert(collection_id.as_str(), new_json.as_str())?;
+        for (position, mut request) in requests.into_iter().enumerate() {
+            request.sort_order = position as i64;
+            request.updated_at = now;
+            put_row(&write_txn, REQUESTS, &request.id, &request)?;
         }
 
         write_txn.commit()?;
 
         Ok(())
     }
+
+    /// Remove a request and all of its index entries inside an open transaction
+    pub(crate) fn delete_in(write_txn: &WriteTransaction, request: &Request) -> DbResult<()> {
+        remove_row(write_txn, REQUESTS, &request.id)?;
+        index_remove(write_txn, REQUESTS_BY_WORKSPACE, &request.workspace_id, &request.id)?;
+        Self::detach_in(write_txn, request)
+    }
+
+    /// Remove a request from its collection and folder indexes
+    fn detach_in(write_txn: &WriteTransaction, request: &Request) -> DbResult<()> {
+        if let Some(ref collection_id) = request.collection_id {
+            index_remove(write_txn, REQUESTS_BY_COLLECTION, collection_id, &request.id)?;
+        }
+        if let Some(ref folder_id) = request.folder_id {
+            index_remove(write_txn, REQUESTS_BY_FOLDER, folder_id, &request.id)?;
+        }
+        Ok(())
+    }
+
+    /// Add a request to its collection and folder indexes
+    fn attach_in(write_txn: &WriteTransaction, request: &Request) -> DbResult<()> {
+        if let Some(ref collection_id) = request.collection_id {
+            index_add(write_txn, REQUESTS_BY_COLLECTION, collection_id, &request.id)?;
+        }
+        if let Some(ref folder_id) = request.folder_id {
+            index_add(write_txn, REQUESTS_BY_FOLDER, folder_id, &request.id)?;
+        }
+        Ok(())
+    }
+
+    /// A position after every other request in the container the request belongs to
+    fn next_sort_order_in(write_txn: &WriteTransaction, request: &Request) -> DbResult<i64> {
+        let candidates = match (&request.folder_id, &request.collection_id) {
+            (Some(folder_id), _) => index_get(write_txn, REQUESTS_BY_FOLDER, folder_id)?,
+            (None, Some(collection_id)) => {
+                index_get(write_txn, REQUESTS_BY_COLLECTION, collection_id)?
+            }
+            (None, None) => index_get(write_txn, REQUESTS_BY_WORKSPACE, &request.workspace_id)?,
+        };
+
+        let mut next = 0;
+        for id in candidates {
+            if id == request.id {
+                continue;
+            }
+            if let Some(sibling) = get_row::<Request>(write_txn, REQUESTS, &id)? {
+                if Self::are_siblings(&sibling, request) {
+                    next = next.max(sibling.sort_order + 1);
+                }
+            }
+        }
+        Ok(next)
+    }
+
+    /// Whether two requests sit directly in the same folder, collection root
+    /// or workspace
+    fn are_siblings(a: &Request, b: &Request) -> bool {
+        a.workspace_id == b.workspace_id
+            && a.collection_id == b.collection_id
+            && a.folder_id == b.folder_id
+    }
+
+    fn folder_in(write_txn: &WriteTransaction, folder_id: &str) -> DbResult<Folder> {
+        get_row(write_txn, FOLDERS, folder_id)?
+            .ok_or_else(|| DbError::NotFound(format!("Folder not found: {}", folder_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::{CollectionDefaults, CreateCollectionInput};
+    use crate::folders::{CreateFolderInput, FolderService};
+    use crate::http::HttpRequestHeader;
+    use crate::requests::RequestHeader;
+    use std::env::temp_dir;
+
+    fn create_test_db() -> Database {
+        let path = temp_dir().join(format!("resona_test_{}.redb", uuid::Uuid::new_v4()));
+        Database::open_at(path).expect("Failed to create test database")
+    }
+
+    fn create_collection(db: &Database, defaults: CollectionDefaults) -> Collection {
+        CollectionService::new(db.clone())
+            .create(CreateCollectionInput {
+                name: "Users API".to_string(),
+                workspace_id: "ws".to_string(),
+                defaults,
+                ..Default::default()
+            })
+            .expect("Failed to create collection")
+    }
+
+    fn header(key: &str, value: &str) -> RequestHeader {
+        RequestHeader {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_effective_request_inherits_collection_defaults() {
+        let db = create_test_db();
+        let service = RequestService::new(db.clone());
+
+        let collection = create_collection(
+            &db,
+            CollectionDefaults {
+                base_url: "https://api.example.com/v1/".to_string(),
+                headers: vec![header("Accept", "application/json"), header("X-Team", "core")],
+                params: Vec::new(),
+                auth: RequestAuth::Bearer {
+                    token: "{{token}}".to_string(),
+                },
+                timeout_ms: Some(5000),
+                validate_ssl: None,
+            },
+        );
+
+        let request = service
+            .create(CreateRequestInput {
+                name: "List users".to_string(),
+                url: "/users".to_string(),
+                headers: vec![
+                    header("x-team", "platform"),
+                    RequestHeader {
+                        enabled: false,
+                        ..header("Accept", "text/plain")
+                    },
+                ],
+                auth: RequestAuth::Inherit,
+                timeout_ms: Some(1000),
+                collection_id: Some(collection.id.clone()),
+                workspace_id: "ws".to_string(),
+                ..Default::default()
+            })
+            .expect("Failed to create request");
+
+        let effective = service
+            .get_effective(&request.id)
+            .expect("Failed to resolve effective request");
+
+        assert_eq!(effective.url, "https://api.example.com/v1/users");
+        assert_eq!(effective.headers.len(), 3);
+        assert!(effective
+            .headers
+            .iter()
+            .any(|h| h.key == "x-team" && h.value == "platform"));
+        // A disabled request header doesn't hide the default
+        assert!(effective
+            .headers
+            .iter()
+            .any(|h| h.enabled && h.key == "Accept" && h.value == "application/json"));
+        assert_eq!(effective.timeout_ms, Some(1000));
+
+        let http = effective.to_http_request();
+        assert!(http
+            .headers
+            .iter()
+            .any(|h| h.key == "Authorization" && h.value == "Bearer {{token}}"));
+    }
+
+    #[test]
+    fn test_sort_order_and_reorder_follow_the_container() {
+        let db = create_test_db();
+        let service = RequestService::new(db.clone());
+        let collection = create_collection(&db, CollectionDefaults::default());
+        let folder = FolderService::new(db.clone())
+            .create(CreateFolderInput {
+                name: "Admin".to_string(),
+                collection_id: collection.id.clone(),
+                parent_id: None,
+            })
+            .expect("Failed to create folder");
+        let request = |name: &str, folder_id: Option<&String>| CreateRequestInput {
+            name: name.to_string(),
+            url: "/".to_string(),
+            collection_id: Some(collection.id.clone()),
+            folder_id: folder_id.cloned(),
+            workspace_id: "ws".to_string(),
+            ..Default::default()
+        };
+
+        let first = service.create(request("First", None)).expect("Failed to create request");
+        let second = service.create(request("Second", None)).expect("Failed to create request");
+        let nested = service
+            .create(request("Nested", Some(&folder.id)))
+            .expect("Failed to create request");
+        assert_eq!((first.sort_order, second.sort_order, nested.sort_order), (0, 1, 0));
+
+        service.delete(&first.id).expect("Failed to delete request");
+        let third = service.create(request("Third", None)).expect("Failed to create request");
+        assert_eq!(third.sort_order, 2);
+
+        let mixed = service.reorder(&[second.id.clone(), nested.id.clone()]);
+        assert!(matches!(mixed, Err(DbError::InvalidInput(_))));
+        // Leaving the collection has to go through `move_request`
+        let moved = service.update(UpdateRequestInput {
+            id: nested.id.clone(),
+            collection_id: Some(None),
+            ..Default::default()
+        });
+        assert!(matches!(moved, Err(DbError::InvalidInput(_))));
+        assert_eq!(service.get(&nested.id).unwrap().folder_id, Some(folder.id.clone()));
+
+        service
+            .reorder(&[third.id.clone(), second.id.clone()])
+            .expect("Failed to reorder requests");
+        let names: Vec<String> = service
+            .get_by_collection(&collection.id)
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.folder_id.is_none())
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, vec!["Third".to_string(), "Second".to_string()]);
+    }
+
+    #[test]
+    fn test_effective_send_keeps_edits_and_explicit_authorization() {
+        let db = create_test_db();
+        let service = RequestService::new(db.clone());
+        let collection = create_collection(
+            &db,
+            CollectionDefaults {
+                base_url: "https://api.example.com".to_string(),
+                auth: RequestAuth::Bearer {
+                    token: "from-collection".to_string(),
+                },
+                timeout_ms: Some(5000),
+                ..Default::default()
+            },
+        );
+        let stored = service
+            .create(CreateRequestInput {
+                name: "List users".to_string(),
+                url: "/users".to_string(),
+                collection_id: Some(collection.id.clone()),
+                workspace_id: "ws".to_string(),
+                ..Default::default()
+            })
+            .expect("Failed to create request");
+
+        let mut edited = stored.to_http_request();
+        edited.url = "/admins".to_string();
+        let sent = service.effective_send(&stored, &edited).expect("Failed to build request");
+        assert_eq!(sent.url, "https://api.example.com/admins");
+        assert_eq!(sent.timeout_ms, Some(5000));
+        let authorization: Vec<&str> = sent
+            .headers
+            .iter()
+            .filter(|h| h.key == "Authorization")
+            .map(|h| h.value.as_str())
+            .collect();
+        assert_eq!(authorization, vec!["Bearer from-collection"]);
+
+        edited.headers.push(HttpRequestHeader {
+            key: "authorization".to_string(),
+            value: "Bearer by-hand".to_string(),
+            enabled: true,
+        });
+        let sent = service.effective_send(&stored, &edited).expect("Failed to build request");
+        let authorization: Vec<&str> = sent
+            .headers
+            .iter()
+            .filter(|h| h.key.eq_ignore_ascii_case("Authorization"))
+            .map(|h| h.value.as_str())
+            .collect();
+        assert_eq!(authorization, vec!["Bearer by-hand"]);
+    }
 }
@@ -1,7 +1,15 @@
+use base64::Engine;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::assertions::Assertion;
+use crate::extractions::Extraction;
+use crate::http::{
+    HttpFormDataItem, HttpRequest, HttpRequestHeader, HttpRequestParam, HttpVersion,
+};
+use crate::scripts::Scripts;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum HttpMethod {
@@ -20,6 +28,20 @@ impl Default for HttpMethod {
     }
 }
 
+impl HttpMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Patch => "PATCH",
+            Self::Delete => "DELETE",
+            Self::Head => "HEAD",
+            Self::Options => "OPTIONS",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum BodyType {
@@ -39,14 +61,59 @@ impl Default for BodyType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl BodyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Json => "json",
+            Self::Xml => "xml",
+            Self::Text => "text",
+            Self::Html => "html",
+            Self::FormData => "form-data",
+            Self::XWwwFormUrlencoded => "x-www-form-urlencoded",
+        }
+    }
+}
+
+/// Authentication applied to a request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RequestAuth {
+    /// Use the collection's auth (no auth for standalone requests)
+    #[default]
+    Inherit,
+    None,
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    ApiKey {
+        key: String,
+        value: String,
+        #[serde(default)]
+        location: ApiKeyLocation,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyLocation {
+    #[default]
+    Header,
+    Query,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RequestHeader {
     pub key: String,
     pub value: String,
     pub enabled: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RequestParam {
     pub key: String,
     pub value: String,
@@ -78,8 +145,32 @@ pub struct Request {
     pub body: String,
     #[serde(default)]
     pub form_data: Vec<FormDataItem>,
+    #[serde(default)]
+    pub http_version: HttpVersion,
+    #[serde(default)]
+    pub auth: RequestAuth,
+    /// Overrides the collection timeout when set
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Overrides the collection TLS certificate validation when set
+    #[serde(default)]
+    pub validate_ssl: Option<bool>,
+    /// Checks evaluated against every response to this request
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    /// Values copied from every response into variables
+    #[serde(default)]
+    pub extractions: Vec<Extraction>,
+    #[serde(default)]
+    pub scripts: Scripts,
     pub collection_id: Option<String>,
+    /// Folder inside the collection, or None for the collection root
+    #[serde(default)]
+    pub folder_id: Option<String>,
     pub workspace_id: String,
+    /// Position among sibling requests
+    #[serde(default)]
+    pub sort_order: i64,
     #[serde(default = "Utc::now")]
     pub created_at: DateTime<Utc>,
     #[serde(default = "Utc::now")]
@@ -99,15 +190,110 @@ impl Request {
             body_type: BodyType::None,
             body: String::new(),
             form_data: Vec::new(),
+            http_version: HttpVersion::Auto,
+            auth: RequestAuth::Inherit,
+            timeout_ms: None,
+            validate_ssl: None,
+            assertions: Vec::new(),
+            extractions: Vec::new(),
+            scripts: Scripts::default(),
             collection_id: None,
+            folder_id: None,
             workspace_id,
+            sort_order: 0,
             created_at: now,
             updated_at: now,
         }
     }
+
+    /// Build the wire-level request, applying auth as headers or query params.
+    /// Call on an effective request so collection defaults are already merged.
+    pub fn to_http_request(&self) -> HttpRequest {
+        let mut headers: Vec<HttpRequestHeader> = self
+            .headers
+            .iter()
+            .map(|h| HttpRequestHeader {
+                key: h.key.clone(),
+                value: h.value.clone(),
+                enabled: h.enabled,
+            })
+            .collect();
+        let mut params: Vec<HttpRequestParam> = self
+            .params
+            .iter()
+            .map(|p| HttpRequestParam {
+                key: p.key.clone(),
+                value: p.value.clone(),
+                enabled: p.enabled,
+            })
+            .collect();
+
+        let auth_header = match &self.auth {
+            RequestAuth::Inherit | RequestAuth::None => None,
+            RequestAuth::Basic { username, password } => {
+                let credentials = base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, password));
+                Some(("Authorization".to_string(), format!("Basic {}", credentials)))
+            }
+            RequestAuth::Bearer { token } => {
+                Some(("Authorization".to_string(), format!("Bearer {}", token)))
+            }
+            RequestAuth::ApiKey {
+                key,
+                value,
+                location,
+            } => match location {
+                ApiKeyLocation::Header => Some((key.clone(), value.clone())),
+                ApiKeyLocation::Query => {
+                    params.push(HttpRequestParam {
+                        key: key.clone(),
+                        value: value.clone(),
+                        enabled: true,
+                    });
+                    None
+                }
+            },
+        };
+        // A header set on the request by hand wins over the one auth would add
+        if let Some((key, value)) = auth_header {
+            if !headers
+                .iter()
+                .any(|h| h.enabled && h.key.eq_ignore_ascii_case(&key))
+            {
+                headers.push(HttpRequestHeader {
+                    key,
+                    value,
+                    enabled: true,
+                });
+            }
+        }
+
+        HttpRequest {
+            method: self.method.as_str().to_string(),
+            url: self.url.clone(),
+            headers,
+            params,
+            body_type: self.body_type.as_str().to_string(),
+            body: self.body.clone(),
+            form_data: self
+                .form_data
+                .iter()
+                .map(|f| HttpFormDataItem {
+                    key: f.key.clone(),
+                    value: f.value.clone(),
+                    item_type: f.item_type.clone(),
+                    enabled: f.enabled,
+                })
+                .collect(),
+            http_version: self.http_version.clone(),
+            timeout_ms: self.timeout_ms,
+            validate_ssl: self.validate_ssl,
+            assertions: self.assertions.clone(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateRequestInput {
     pub name: String,
     pub method: HttpMethod,
@@ -123,11 +309,27 @@ pub struct CreateRequestInput {
     pub body: String,
     #[serde(default)]
     pub form_data: Vec<FormDataItem>,
+    #[serde(default)]
+    pub http_version: HttpVersion,
+    #[serde(default)]
+    pub auth: RequestAuth,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub validate_ssl: Option<bool>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    #[serde(default)]
+    pub extractions: Vec<Extraction>,
+    #[serde(default)]
+    pub scripts: Scripts,
     pub collection_id: Option<String>,
+    #[serde(default)]
+    pub folder_id: Option<String>,
     pub workspace_id: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateRequestInput {
     pub id: String,
     pub name: Option<String>,
@@ -138,5 +340,21 @@ pub struct UpdateRequestInput {
     pub body_type: Option<BodyType>,
     pub body: Option<String>,
     pub form_data: Option<Vec<FormDataItem>>,
+    pub http_version: Option<HttpVersion>,
+    pub auth: Option<RequestAuth>,
+    pub timeout_ms: Option<Option<u64>>,
+    pub validate_ssl: Option<Option<bool>>,
+    pub assertions: Option<Vec<Assertion>>,
+    pub extractions: Option<Vec<Extraction>>,
+    pub scripts: Option<Scripts>,
     pub collection_id: Option<Option<String>>,
 }
+
+/// Move a request to a collection root, a folder, or out of any collection.
+/// When `folder_id` is set the collection is taken from the folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveRequestInput {
+    pub id: String,
+    pub collection_id: Option<String>,
+    pub folder_id: Option<String>,
+}
@@ -0,0 +1,56 @@
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::Database;
+
+use super::report;
+use super::service::RunnerService;
+use super::types::{CollectionRun, ReportFormat, RunCollectionInput, RUN_PROGRESS_EVENT};
+
+/// Run a collection, emitting `collection-run-progress` events while it executes
+#[tauri::command]
+pub async fn run_collection(
+    app: AppHandle,
+    db: State<'_, Database>,
+    input: RunCollectionInput,
+) -> Result<CollectionRun, String> {
+    let service = RunnerService::new(db.inner().clone());
+    service
+        .run(input, |progress| {
+            let _ = app.emit(RUN_PROGRESS_EVENT, progress);
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_collection_run(db: State<Database>, id: String) -> Result<CollectionRun, String> {
+    let service = RunnerService::new(db.inner().clone());
+    service.get(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_collection_runs(
+    db: State<Database>,
+    collection_id: String,
+) -> Result<Vec<CollectionRun>, String> {
+    let service = RunnerService::new(db.inner().clone());
+    service.get_by_collection(&collection_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_collection_run(db: State<Database>, id: String) -> Result<(), String> {
+    let service = RunnerService::new(db.inner().clone());
+    service.delete(&id).map_err(|e| e.to_string())
+}
+
+/// Render a stored run as a JUnit XML, JSON or HTML report
+#[tauri::command]
+pub fn export_collection_run(
+    db: State<Database>,
+    id: String,
+    format: ReportFormat,
+) -> Result<String, String> {
+    let service = RunnerService::new(db.inner().clone());
+    let run = service.get(&id).map_err(|e| e.to_string())?;
+    Ok(report::render(&run, format))
+}
@@ -0,0 +1,106 @@
+//! Iteration data for data-driven runs
+//!
+//! A CSV file (header row + one row per iteration) or a JSON array of objects.
+//! Each row becomes a map of variable name -> value for one iteration.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::db::{DbError, DbResult};
+
+pub type IterationData = HashMap<String, String>;
+
+/// Load a data file, choosing the format from its extension (`.csv` or `.json`)
+pub fn load_data_file(path: &str) -> DbResult<Vec<IterationData>> {
+    let content = std::fs::read_to_string(path)?;
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("csv") => parse_csv(&content),
+        Some("json") => parse_json(&content),
+        _ => Err(DbError::InvalidInput(format!(
+            "Unsupported data file (expected .csv or .json): {}",
+            path
+        ))),
+    }
+}
+
+pub fn parse_csv(content: &str) -> DbResult<Vec<IterationData>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::Headers)
+        .from_reader(content.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| DbError::InvalidInput(format!("Invalid CSV header: {}", e)))?
+        .clone();
+
+    let mut rows = Vec::new();
+    for (line, record) in reader.records().enumerate() {
+        let record = record
+            .map_err(|e| DbError::InvalidInput(format!("Invalid CSV row {}: {}", line + 1, e)))?;
+        let row = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Strings are used as-is, other JSON values by their JSON text and `null` as empty
+pub fn parse_json(content: &str) -> DbResult<Vec<IterationData>> {
+    let value: serde_json::Value = serde_json::from_str(content)?;
+    let items = value.as_array().ok_or_else(|| {
+        DbError::InvalidInput("JSON data file must be an array of objects".to_string())
+    })?;
+
+    let mut rows = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let object = item.as_object().ok_or_else(|| {
+            DbError::InvalidInput(format!("JSON data item {} is not an object", index))
+        })?;
+        let row = object
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Null => String::new(),
+                    other => other.to_string(),
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_and_json_rows() {
+        let csv_rows = parse_csv("username, password\nalice,secret1\nbob,\"p,w\"\n").unwrap();
+        assert_eq!(csv_rows.len(), 2);
+        assert_eq!(csv_rows[0]["username"], "alice");
+        assert_eq!(csv_rows[1]["password"], "p,w");
+
+        let json_rows =
+            parse_json(r#"[{"username": "alice", "age": 30, "admin": true, "team": null}]"#)
+                .unwrap();
+        assert_eq!(json_rows[0]["username"], "alice");
+        assert_eq!(json_rows[0]["age"], "30");
+        assert_eq!(json_rows[0]["admin"], "true");
+        assert_eq!(json_rows[0]["team"], "");
+
+        assert!(parse_json(r#"{"username": "alice"}"#).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+//! Collection runner
+//!
+//! Executes the requests of a collection in order, optionally once per row of a
+//! data file, keeps a record of each run and renders run reports.
+
+mod commands;
+mod data;
+mod report;
+mod service;
+mod types;
+
+pub use commands::*;
+#[allow(unused_imports)]
+pub use types::{
+    CollectionRun, ReportFormat, RunCollectionInput, RunHeader, RunProgress, RunRequestDetails,
+    RunRequestResult, RunStatus,
+};
+#[allow(unused_imports)]
+pub(crate) use service::RunnerService;
@@ -0,0 +1,371 @@
+//! Run reports
+//!
+//! Renders a stored `CollectionRun` as JUnit XML (for CI dashboards), a JSON
+//! document or a self-contained HTML page. Secret values are already masked in
+//! the recorded results, so the reports never see them.
+
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use super::types::{CollectionRun, ReportFormat, RunHeader, RunRequestResult};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSummary {
+    pub total: usize,
+    pub passed: usize,
+    /// Requests that got a response but failed an assertion
+    pub failed: usize,
+    /// Requests that never got a response or whose extractions could not be saved
+    pub errors: usize,
+}
+
+impl ReportSummary {
+    /// Each result counts once: as an error when it has one, otherwise as
+    /// passed or failed
+    pub fn of(run: &CollectionRun) -> Self {
+        let errors = run.results.iter().filter(|r| r.error.is_some()).count();
+        let passed = run
+            .results
+            .iter()
+            .filter(|r| r.passed && r.error.is_none())
+            .count();
+        Self {
+            total: run.results.len(),
+            passed,
+            failed: run.results.len() - passed - errors,
+            errors,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    summary: ReportSummary,
+    run: &'a CollectionRun,
+}
+
+pub fn render(run: &CollectionRun, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Junit => render_junit(run),
+        ReportFormat::Json => render_json(run),
+        ReportFormat::Html => render_html(run),
+    }
+}
+
+pub fn render_json(run: &CollectionRun) -> String {
+    let report = JsonReport {
+        summary: ReportSummary::of(run),
+        run,
+    };
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+pub fn render_junit(run: &CollectionRun) -> String {
+    let summary = ReportSummary::of(run);
+    let suite = escape(&run.collection_name);
+    let time = seconds(run.total_time_ms);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">",
+        suite, summary.total, summary.failed, summary.errors, time
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{}\" id=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" \
+         time=\"{}\" timestamp=\"{}\">",
+        suite,
+        escape(&run.id),
+        summary.total,
+        summary.failed,
+        summary.errors,
+        time,
+        run.started_at.format("%Y-%m-%dT%H:%M:%S")
+    );
+
+    for result in &run.results {
+        let _ = writeln!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\">",
+            escape(&case_name(run, result)),
+            suite,
+            seconds(result.time_ms)
+        );
+
+        if let Some(error) = &result.error {
+            let _ = writeln!(
+                xml,
+                "      <error message=\"{}\" type=\"RequestError\"/>",
+                escape(error)
+            );
+        } else if !result.passed {
+            let failed: Vec<String> = result
+                .assertions
+                .iter()
+                .filter(|a| !a.passed)
+                .map(|a| match &a.message {
+                    Some(message) => format!("{}: {}", a.name, message),
+                    None => a.name.clone(),
+                })
+                .collect();
+            let _ = writeln!(
+                xml,
+                "      <failure message=\"{}\" type=\"AssertionFailure\">{}</failure>",
+                escape(failed.first().map(String::as_str).unwrap_or("Request failed")),
+                escape(&failed.join("\n"))
+            );
+        }
+
+        let _ = writeln!(
+            xml,
+            "      <system-out>{}</system-out>",
+            escape(&exchange_text(result))
+        );
+        xml.push_str("    </testcase>\n");
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+pub fn render_html(run: &CollectionRun) -> String {
+    let summary = ReportSummary::of(run);
+    let title = format!("{} run report", run.collection_name);
+    let mut html = String::new();
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(&title),
+        HTML_STYLE,
+        escape(&title)
+    );
+    let _ = writeln!(
+        html,
+        "<p class=\"meta\">Started {} &middot; {} ms &middot; {} iteration(s) &middot; \
+         status <strong>{:?}</strong></p>",
+        run.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        run.total_time_ms,
+        run.iterations,
+        run.status
+    );
+    let _ = writeln!(
+        html,
+        "<p class=\"summary\"><span>{} total</span><span class=\"pass\">{} passed</span>\
+         <span class=\"fail\">{} failed</span><span class=\"fail\">{} errors</span></p>",
+        summary.total, summary.passed, summary.failed, summary.errors
+    );
+
+    for result in &run.results {
+        let class = if result.passed { "pass" } else { "fail" };
+        let status = result
+            .status
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        let _ = writeln!(
+            html,
+            "<details class=\"result {}\">\n<summary><span class=\"name\">{}</span> \
+             <code>{} {}</code> <span>{}</span> <span>{} ms</span></summary>",
+            class,
+            escape(&case_name(run, result)),
+            escape(&result.method),
+            escape(&result.url),
+            status,
+            result.time_ms
+        );
+
+        if let Some(error) = &result.error {
+            let _ = writeln!(html, "<p class=\"fail\">{}</p>", escape(error));
+        }
+        if !result.assertions.is_empty() {
+            html.push_str("<ul class=\"assertions\">\n");
+            for assertion in &result.assertions {
+                let _ = writeln!(
+                    html,
+                    "<li class=\"{}\">{}{}</li>",
+                    if assertion.passed { "pass" } else { "fail" },
+                    escape(&assertion.name),
+                    assertion
+                        .message
+                        .as_deref()
+                        .map(|m| format!(" &mdash; {}", escape(m)))
+                        .unwrap_or_default()
+                );
+            }
+            html.push_str("</ul>\n");
+        }
+
+        let details = &result.details;
+        let _ = writeln!(
+            html,
+            "<h3>Request</h3>\n<pre>{}</pre>\n<h3>Response</h3>\n<pre>{}</pre>",
+            escape(&message_text(&details.request_headers, &details.request_body)),
+            escape(&message_text(&details.response_headers, &details.response_body))
+        );
+        if !result.console.is_empty() {
+            let _ = writeln!(
+                html,
+                "<h3>Console</h3>\n<pre>{}</pre>",
+                escape(&console_text(result))
+            );
+        }
+        html.push_str("</details>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2rem;color:#222}\
+.meta{color:#666}.summary span{margin-right:1rem}.pass{color:#1a7f37}.fail{color:#cf222e}\
+.result{border:1px solid #ddd;border-radius:6px;margin:.5rem 0;padding:.5rem}\
+.result.fail{border-color:#cf222e}.result summary{cursor:pointer}\
+.result summary span{margin-left:.5rem}.name{font-weight:600}\
+pre{background:#f6f8fa;padding:.75rem;overflow-x:auto;white-space:pre-wrap}";
+
+/// Request name, suffixed with the iteration for data-driven runs
+fn case_name(run: &CollectionRun, result: &RunRequestResult) -> String {
+    if run.iterations > 1 {
+        format!("{} [iteration {}]", result.request_name, result.iteration + 1)
+    } else {
+        result.request_name.clone()
+    }
+}
+
+/// Plain-text request and response, as shown in JUnit `system-out`
+fn exchange_text(result: &RunRequestResult) -> String {
+    let details = &result.details;
+    let mut text = format!(
+        "{} {}\n{}",
+        result.method,
+        result.url,
+        message_text(&details.request_headers, &details.request_body)
+    );
+    if let Some(status) = result.status {
+        let _ = write!(
+            text,
+            "\n\n--- Response {} ---\n{}",
+            status,
+            message_text(&details.response_headers, &details.response_body)
+        );
+    }
+    if !result.console.is_empty() {
+        let _ = write!(text, "\n\n--- Console ---\n{}", console_text(result));
+    }
+    text
+}
+
+fn console_text(result: &RunRequestResult) -> String {
+    result
+        .console
+        .iter()
+        .map(|e| format!("[{}] {}\n", e.level.as_str(), e.message))
+        .collect()
+}
+
+fn message_text(headers: &[RunHeader], body: &str) -> String {
+    let mut text: String = headers
+        .iter()
+        .map(|h| format!("{}: {}\n", h.key, h.value))
+        .collect();
+    if !body.is_empty() {
+        text.push('\n');
+        text.push_str(body);
+    }
+    text
+}
+
+fn seconds(ms: u64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+/// Escape text for XML and HTML content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            // Control characters other than tab and newlines are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assertions::AssertionResult;
+    use crate::runner::types::RunStatus;
+
+    fn result(name: &str, passed: bool, error: Option<&str>) -> RunRequestResult {
+        RunRequestResult {
+            iteration: 0,
+            request_id: name.to_string(),
+            request_name: name.to_string(),
+            method: "GET".to_string(),
+            url: "https://api.example.com/items?q=<a&b>".to_string(),
+            status: error.is_none().then_some(if passed { 200 } else { 500 }),
+            time_ms: 1500,
+            passed,
+            error: error.map(str::to_string),
+            assertions: vec![AssertionResult {
+                name: "Status code is not an error".to_string(),
+                passed,
+                message: (!passed).then(|| "Received 500".to_string()),
+            }],
+            details: Default::default(),
+            extractions: Vec::new(),
+            console: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_junit_report_counts_and_escaping() {
+        let mut run = CollectionRun::new(
+            "c1".to_string(),
+            "Smoke \"tests\"".to_string(),
+            "ws".to_string(),
+        );
+        run.status = RunStatus::Failed;
+        run.results = vec![
+            result("List", true, None),
+            result("Create", false, None),
+            result("Delete", false, Some("connection refused")),
+        ];
+
+        let xml = render_junit(&run);
+        assert!(xml.contains("tests=\"3\" failures=\"1\" errors=\"1\""));
+        assert!(xml.contains("name=\"Smoke &quot;tests&quot;\""));
+        assert!(xml.contains("time=\"1.500\""));
+        assert!(xml.contains("<failure message=\"Status code is not an error: Received 500\""));
+        assert!(xml.contains("<error message=\"connection refused\""));
+        assert!(xml.contains("q=&lt;a&amp;b&gt;"));
+        assert!(!xml.contains("<a&b>"));
+
+        let html = render_html(&run);
+        assert!(html.contains("q=&lt;a&amp;b&gt;"));
+    }
+
+    #[test]
+    fn test_summary_counts_failed_extraction_as_error() {
+        let mut run = CollectionRun::new("c1".to_string(), "Smoke".to_string(), "ws".to_string());
+        // Recorded before a failed extraction cleared `passed`
+        run.results = vec![
+            result("Login", true, Some("Vault is locked")),
+            result("List", true, None),
+        ];
+
+        let summary = ReportSummary::of(&run);
+        assert_eq!((summary.passed, summary.failed, summary.errors), (1, 0, 1));
+    }
+}
@@ -0,0 +1,702 @@
+use std::collections::HashMap;
+use std::pin::pin;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+
+use crate::assertions::{self, AssertionResult};
+use crate::collections::{Collection, CollectionService};
+use crate::db::helpers::{index_add, index_remove, put_row, remove_row};
+use crate::db::{Database, DbError, DbResult, COLLECTION_RUNS, RUNS_BY_COLLECTION};
+use crate::extractions::ExtractionService;
+use crate::folders::{Folder, FolderService};
+use crate::http::{self, ClientOptions, HttpRequest};
+use crate::requests::{Request, RequestService};
+use crate::scripts::{ConsoleEntry, ScriptService, Scripts};
+use crate::variables::{template, VariableService};
+use crate::workspaces::WorkspaceService;
+
+use super::data::{load_data_file, IterationData};
+use super::types::{
+    CollectionRun, RunCollectionInput, RunHeader, RunProgress, RunRequestResult,
+    RunStatus, MAX_RECORDED_BODY_BYTES,
+};
+
+pub struct RunnerService {
+    db: Database,
+}
+
+impl RunnerService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub fn get(&self, id: &str) -> DbResult<CollectionRun> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(COLLECTION_RUNS)?;
+
+        let value = table
+            .get(id)?
+            .ok_or_else(|| DbError::NotFound(format!("Run not found: {}", id)))?;
+
+        let run: CollectionRun = serde_json::from_str(value.value())
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(run)
+    }
+
+    /// Stored runs of a collection, newest first
+    pub fn get_by_collection(&self, collection_id: &str) -> DbResult<Vec<CollectionRun>> {
+        let read_txn = self.db.begin_read()?;
+        let idx_table = read_txn.open_table(RUNS_BY_COLLECTION)?;
+
+        let run_ids: Vec<String> = match idx_table.get(collection_id)? {
+            Some(value) => serde_json::from_str(value.value())
+                .map_err(|e| DbError::Serialization(e.to_string()))?,
+            None => return Ok(Vec::new()),
+        };
+
+        drop(idx_table);
+        drop(read_txn);
+
+        let mut runs = Vec::new();
+        for id in run_ids {
+            if let Ok(run) = self.get(&id) {
+                runs.push(run);
+            }
+        }
+
+        runs.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+        Ok(runs)
+    }
+
+    pub fn delete(&self, id: &str) -> DbResult<()> {
+        let run = self.get(id)?;
+
+        let write_txn = self.db.begin_write()?;
+        remove_row(&write_txn, COLLECTION_RUNS, id)?;
+        index_remove(&write_txn, RUNS_BY_COLLECTION, &run.collection_id, id)?;
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Execute the requests of a collection, up to `concurrency` at a time for those
+    /// that set no variables, reporting each result through `on_progress`, and store
+    /// the finished run with its results in run order
+    pub async fn run<F>(
+        &self,
+        input: RunCollectionInput,
+        mut on_progress: F,
+    ) -> DbResult<CollectionRun>
+    where
+        F: FnMut(RunProgress) + Send,
+    {
+        let collection = CollectionService::new(self.db.clone()).get(&input.collection_id)?;
+        let options = WorkspaceService::new(self.db.clone())
+            .get(&collection.workspace_id)
+            .map(|w| w.client_options())
+            .unwrap_or_default();
+
+        let mut requests = self.ordered_requests(&collection.id)?;
+        if !input.request_ids.is_empty() {
+            requests.retain(|r| input.request_ids.contains(&r.id));
+        }
+
+        // Without a data file the run is a single iteration with no extra variables
+        let data = match &input.data_file {
+            Some(path) => load_data_file(path)?,
+            None => vec![IterationData::new()],
+        };
+        if data.is_empty() {
+            return Err(DbError::InvalidInput(
+                "Data file does not contain any rows".to_string(),
+            ));
+        }
+
+        let mut run = CollectionRun::new(
+            collection.id.clone(),
+            collection.name.clone(),
+            collection.workspace_id.clone(),
+        );
+        run.iterations = data.len();
+        let total = requests.len() * data.len();
+        let started = Instant::now();
+
+        on_progress(RunProgress::Started {
+            run_id: run.id.clone(),
+            iterations: run.iterations,
+            total,
+        });
+
+        // Every (iteration, request) pair in run order, split into batches that may
+        // run in parallel. A request that sets variables through extractions or
+        // scripts gets a batch of its own, so requests after it see its values.
+        // Plain indices keep the stream's items free of borrows so the run future
+        // stays `Send`.
+        let mut batches: Vec<Vec<(usize, (usize, usize))>> = Vec::new();
+        let plan = (0..data.len())
+            .flat_map(|iteration| (0..requests.len()).map(move |r| (iteration, r)))
+            .enumerate();
+        let mut shared = false;
+        for (index, (iteration, request)) in plan {
+            let alone = sets_variables(&requests[request], &collection);
+            let item = (index, (iteration, request));
+            match batches.last_mut() {
+                Some(batch) if shared && !alone => batch.push(item),
+                _ => batches.push(vec![item]),
+            }
+            shared = !alone;
+        }
+        let delay = Duration::from_millis(input.delay_ms);
+        let (options, requests, data) = (&options, &requests, &data);
+
+        let mut results = Vec::with_capacity(total);
+        'run: for batch in batches {
+            // The pause goes between dispatches: the next item is only pulled when
+            // buffer_unordered has a free slot, which with the default concurrency
+            // of 1 makes this a plain serial run
+            let mut pending = pin!(stream::iter(batch)
+                .then(|item| async move {
+                    if item.0 > 0 && !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+                    item
+                })
+                .map(|(index, (iteration, request))| async move {
+                    let mut result = self
+                        .execute(&requests[request], &data[iteration], options)
+                        .await;
+                    result.iteration = iteration;
+                    (index, result)
+                })
+                .buffer_unordered(input.concurrency.max(1)));
+
+            while let Some((index, result)) = pending.next().await {
+                let passed = result.passed;
+
+                on_progress(RunProgress::RequestCompleted {
+                    run_id: run.id.clone(),
+                    iteration: result.iteration,
+                    index,
+                    total,
+                    result: Box::new(result.clone()),
+                });
+                results.push((index, result));
+
+                if !passed {
+                    run.status = RunStatus::Failed;
+                    if input.stop_on_failure {
+                        // Dropping the stream cancels requests still in flight
+                        if results.len() < total {
+                            run.status = RunStatus::Stopped;
+                        }
+                        break 'run;
+                    }
+                }
+            }
+        }
+
+        results.sort_by_key(|(index, _)| *index);
+        run.results = results.into_iter().map(|(_, result)| result).collect();
+
+        run.total_time_ms = started.elapsed().as_millis() as u64;
+        run.finished_at = Utc::now();
+        self.save(&run)?;
+
+        on_progress(RunProgress::Finished {
+            run_id: run.id.clone(),
+            status: run.status.clone(),
+        });
+
+        Ok(run)
+    }
+
+    /// Send one request and turn the outcome into a result row. Failures are
+    /// recorded on the row rather than aborting the run.
+    async fn execute(
+        &self,
+        request: &Request,
+        data: &IterationData,
+        options: &ClientOptions,
+    ) -> RunRequestResult {
+        let mut result = RunRequestResult {
+            iteration: 0,
+            request_id: request.id.clone(),
+            request_name: request.name.clone(),
+            method: request.method.as_str().to_string(),
+            url: request.url.clone(),
+            status: None,
+            time_ms: 0,
+            passed: false,
+            error: None,
+            assertions: Vec::new(),
+            details: Default::default(),
+            extractions: Vec::new(),
+            console: Vec::new(),
+        };
+
+        let scripts = ScriptService::new(self.db.clone());
+        let mut console = Vec::new();
+        let Prepared {
+            http_request,
+            mut variables,
+            secrets,
+        } = match self.prepare(request, data, &scripts, &mut console) {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                result.error = Some(e.to_string());
+                result.console = console;
+                return result;
+            }
+        };
+        let mask = |text: &str| template::mask_secrets(text, &secrets);
+        result.url = mask(&http_request.url);
+        result.details.request_headers = http_request
+            .headers
+            .iter()
+            .filter(|h| h.enabled && !h.key.is_empty())
+            .map(|h| RunHeader {
+                key: h.key.clone(),
+                value: mask(&h.value),
+            })
+            .collect();
+        result.details.request_body = if http_request.body.is_empty() {
+            http_request
+                .form_data
+                .iter()
+                .filter(|f| f.enabled && !f.key.is_empty())
+                .map(|f| format!("{}: {}", f.key, mask(&f.value)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            mask(&http_request.body)
+        };
+
+        let sent = http_request.clone();
+        match http::execute_request(http_request, options).await {
+            Ok(response) => {
+                result.details.response_headers = response
+                    .headers
+                    .iter()
+                    .map(|h| RunHeader {
+                        key: h.key.clone(),
+                        value: mask(&h.value),
+                    })
+                    .collect();
+                result.details.response_body = mask(truncate(&response.body));
+
+                result.status = Some(response.status);
+                result.time_ms = response.time_ms;
+                result.assertions = assertions::evaluate(&sent.assertions, &response);
+                // Without assertions of its own a request passes on any non-error status
+                if result.assertions.is_empty() {
+                    let ok = response.status < 400;
+                    result.assertions.push(AssertionResult {
+                        name: "Status code is not an error".to_string(),
+                        passed: ok,
+                        message: (!ok).then(|| {
+                            format!("Received {} {}", response.status, response.status_text)
+                        }),
+                    });
+                }
+                match ExtractionService::new(self.db.clone()).apply(
+                    request,
+                    &response,
+                    &mut variables,
+                ) {
+                    Ok(extractions) => result.extractions = extractions,
+                    Err(e) => result.error = Some(e.to_string()),
+                }
+                if let Err(e) =
+                    scripts.post_response(request, &sent, &response, &mut variables, &mut console)
+                {
+                    result.assertions.push(AssertionResult {
+                        name: "Post-response script".to_string(),
+                        passed: false,
+                        message: Some(e.to_string()),
+                    });
+                }
+                for assertion in &mut result.assertions {
+                    assertion.message = assertion.message.as_deref().map(mask);
+                }
+                for extraction in &mut result.extractions {
+                    extraction.value = extraction.value.as_deref().map(mask);
+                }
+                // A failed extraction leaves later requests without their variables
+                result.passed =
+                    result.error.is_none() && result.assertions.iter().all(|a| a.passed);
+            }
+            Err(e) => result.error = Some(mask(&e)),
+        }
+
+        for entry in &mut console {
+            entry.message = mask(&entry.message);
+        }
+        result.console = console;
+        result
+    }
+
+    /// Merge collection defaults, run pre-request scripts and substitute variables,
+    /// with the iteration's data row taking precedence over every variable scope
+    fn prepare(
+        &self,
+        request: &Request,
+        data: &IterationData,
+        scripts: &ScriptService,
+        console: &mut Vec<ConsoleEntry>,
+    ) -> DbResult<Prepared> {
+        let effective = RequestService::new(self.db.clone()).resolve_effective(request.clone())?;
+        let (variables, unavailable) = VariableService::new(self.db.clone()).resolve_context(
+            Some(&request.workspace_id),
+            request.collection_id.as_deref(),
+            Some(&request.id),
+        )?;
+
+        let mut secrets = template::secret_values(&variables);
+        // Data row values are plain text, whatever the type of the variable they replace
+        let mut types = template::variable_types(&variables);
+        types.retain(|name, _| !data.contains_key(name));
+
+        let mut variables = template::variable_map(&variables);
+        variables.extend(data.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        let mut http_request = effective.to_http_request();
+        scripts.pre_request(request, &mut http_request, &mut variables, console)?;
+        template::interpolate_request(&mut http_request, &variables, &types);
+        template::check_available(&http_request, &unavailable)?;
+        template::add_credentials(&mut secrets, &http_request, &effective.auth);
+
+        Ok(Prepared {
+            http_request,
+            variables,
+            secrets,
+        })
+    }
+
+    /// Requests of a collection in tree order: each folder's contents (subfolders
+    /// first, then its requests) before the requests at the collection root
+    fn ordered_requests(&self, collection_id: &str) -> DbResult<Vec<Request>> {
+        let folders = FolderService::new(self.db.clone()).get_by_collection(collection_id)?;
+        let requests = RequestService::new(self.db.clone());
+
+        let mut ordered = Vec::new();
+        collect_level(&requests, collection_id, None, &folders, &mut ordered)?;
+        Ok(ordered)
+    }
+
+    fn save(&self, run: &CollectionRun) -> DbResult<()> {
+        let write_txn = self.db.begin_write()?;
+        put_row(&write_txn, COLLECTION_RUNS, &run.id, run)?;
+        index_add(&write_txn, RUNS_BY_COLLECTION, &run.collection_id, &run.id)?;
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+/// A request ready to send, with the variables it was built from
+struct Prepared {
+    http_request: HttpRequest,
+    variables: HashMap<String, String>,
+    /// Secret values to mask in the recorded result
+    secrets: Vec<String>,
+}
+
+/// Whether sending the request can change variables that later requests read
+fn sets_variables(request: &Request, collection: &Collection) -> bool {
+    let has_scripts = |scripts: &Scripts| {
+        !scripts.pre_request.trim().is_empty() || !scripts.post_response.trim().is_empty()
+    };
+    request.extractions.iter().any(|e| e.enabled)
+        || has_scripts(&request.scripts)
+        || has_scripts(&collection.scripts)
+}
+
+/// Cut a response body to `MAX_RECORDED_BODY_BYTES` on a character boundary
+fn truncate(body: &str) -> &str {
+    if body.len() <= MAX_RECORDED_BODY_BYTES {
+        return body;
+    }
+    let mut end = MAX_RECORDED_BODY_BYTES;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    &body[..end]
+}
+
+fn collect_level(
+    requests: &RequestService,
+    collection_id: &str,
+    parent_id: Option<&str>,
+    folders: &[Folder],
+    ordered: &mut Vec<Request>,
+) -> DbResult<()> {
+    for folder in folders.iter().filter(|f| f.parent_id.as_deref() == parent_id) {
+        collect_level(requests, collection_id, Some(&folder.id), folders, ordered)?;
+    }
+
+    match parent_id {
+        Some(folder_id) => ordered.extend(requests.get_by_folder(folder_id)?),
+        None => ordered.extend(
+            requests
+                .get_by_collection(collection_id)?
+                .into_iter()
+                .filter(|r| r.folder_id.is_none()),
+        ),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::{Collection, CollectionDefaults, CreateCollectionInput};
+    use crate::extractions::{Extraction, ExtractionSource};
+    use crate::requests::{CreateRequestInput, RequestAuth};
+    use crate::runner::report;
+    use crate::variables::VariableScope;
+    use std::env::temp_dir;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    fn create_test_db() -> Database {
+        let path = temp_dir().join(format!("resona_test_{}.redb", uuid::Uuid::new_v4()));
+        Database::open_at(path).expect("Failed to create test database")
+    }
+
+    /// Serve 200 for every path except `/fail`, which gets a 500, and return
+    /// the server's base URL
+    fn spawn_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind test server");
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let head = String::from_utf8_lossy(&buf[..n]);
+                let status = if head.starts_with("GET /fail") {
+                    "500 Internal Server Error"
+                } else {
+                    "200 OK"
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                    status
+                );
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    /// Collection whose requests are relative to a fresh test server
+    fn create_collection(db: &Database) -> Collection {
+        CollectionService::new(db.clone())
+            .create(CreateCollectionInput {
+                name: "Workflow".to_string(),
+                workspace_id: "ws".to_string(),
+                defaults: CollectionDefaults {
+                    base_url: spawn_server(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .expect("Failed to create collection")
+    }
+
+    fn create_request(db: &Database, collection: &Collection, name: &str, url: &str) -> Request {
+        RequestService::new(db.clone())
+            .create(CreateRequestInput {
+                name: name.to_string(),
+                url: url.to_string(),
+                collection_id: Some(collection.id.clone()),
+                workspace_id: collection.workspace_id.clone(),
+                ..Default::default()
+            })
+            .expect("Failed to create request")
+    }
+
+    #[tokio::test]
+    async fn test_run_collection_stops_on_failure() {
+        let db = create_test_db();
+        let collection = create_collection(&db);
+
+        let login = create_request(&db, &collection, "Login", "/login");
+        let broken = create_request(&db, &collection, "Broken", "/fail");
+        let logout = create_request(&db, &collection, "Logout", "/logout");
+        RequestService::new(db.clone())
+            .reorder(&[login.id.clone(), broken.id.clone(), logout.id.clone()])
+            .expect("Failed to reorder requests");
+
+        let service = RunnerService::new(db);
+        let mut events = Vec::new();
+        let run = service
+            .run(
+                RunCollectionInput {
+                    collection_id: collection.id.clone(),
+                    request_ids: Vec::new(),
+                    delay_ms: 0,
+                    concurrency: 1,
+                    stop_on_failure: true,
+                    data_file: None,
+                },
+                |progress| events.push(progress),
+            )
+            .await
+            .expect("Run failed");
+
+        assert_eq!(run.status, RunStatus::Stopped);
+        assert_eq!(run.results.len(), 2);
+        assert_eq!(run.results[0].request_id, login.id);
+        assert!(run.results[0].passed);
+        assert_eq!(run.results[1].status, Some(500));
+        assert!(!run.results[1].passed);
+        // Started, two completed requests, finished
+        assert_eq!(events.len(), 4);
+
+        let stored = service
+            .get_by_collection(&collection.id)
+            .expect("Failed to load runs");
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, run.id);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_data_driven_run_keeps_run_order() {
+        let db = create_test_db();
+        let collection = create_collection(&db);
+
+        let first = create_request(&db, &collection, "First", "/{{user}}/first");
+        let second = create_request(&db, &collection, "Second", "/{{user}}/second");
+        let third = create_request(&db, &collection, "Third", "/{{user}}/third");
+        RequestService::new(db.clone())
+            .reorder(&[first.id.clone(), second.id.clone(), third.id.clone()])
+            .expect("Failed to reorder requests");
+
+        let data_file = temp_dir().join(format!("resona_data_{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&data_file, r#"[{"user": "alice"}, {"user": "bob"}]"#).unwrap();
+
+        let service = RunnerService::new(db);
+        let run = service
+            .run(
+                RunCollectionInput {
+                    collection_id: collection.id.clone(),
+                    request_ids: Vec::new(),
+                    delay_ms: 0,
+                    concurrency: 4,
+                    stop_on_failure: false,
+                    data_file: Some(data_file.to_string_lossy().to_string()),
+                },
+                |_| {},
+            )
+            .await
+            .expect("Run failed");
+        let _ = std::fs::remove_file(&data_file);
+
+        assert_eq!(run.status, RunStatus::Passed);
+        assert_eq!(run.iterations, 2);
+        let order: Vec<(usize, &str)> = run
+            .results
+            .iter()
+            .map(|r| (r.iteration, r.request_name.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (0, "First"),
+                (0, "Second"),
+                (0, "Third"),
+                (1, "First"),
+                (1, "Second"),
+                (1, "Third"),
+            ]
+        );
+        assert!(run.results[3].url.ends_with("/bob/first"));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_run_waits_for_extractions() {
+        let db = create_test_db();
+        let collection = create_collection(&db);
+
+        let requests = RequestService::new(db.clone());
+        let login = requests
+            .create(CreateRequestInput {
+                name: "Login".to_string(),
+                url: "/login".to_string(),
+                extractions: vec![Extraction {
+                    enabled: true,
+                    variable: "login_status".to_string(),
+                    scope: VariableScope::Workspace,
+                    source: ExtractionSource::Status,
+                }],
+                collection_id: Some(collection.id.clone()),
+                workspace_id: collection.workspace_id.clone(),
+                ..Default::default()
+            })
+            .expect("Failed to create request");
+        let check = create_request(&db, &collection, "Check", "/check/{{login_status}}");
+        requests
+            .reorder(&[login.id.clone(), check.id.clone()])
+            .expect("Failed to reorder requests");
+
+        let run = RunnerService::new(db)
+            .run(
+                RunCollectionInput {
+                    collection_id: collection.id.clone(),
+                    request_ids: Vec::new(),
+                    delay_ms: 0,
+                    concurrency: 4,
+                    stop_on_failure: false,
+                    data_file: None,
+                },
+                |_| {},
+            )
+            .await
+            .expect("Run failed");
+
+        assert_eq!(run.results.len(), 2);
+        assert!(run.results[1].url.ends_with("/check/200"));
+    }
+
+    #[tokio::test]
+    async fn test_report_masks_basic_auth_credentials() {
+        let db = create_test_db();
+        let collection = create_collection(&db);
+        RequestService::new(db.clone())
+            .create(CreateRequestInput {
+                name: "Login".to_string(),
+                url: "/login".to_string(),
+                auth: RequestAuth::Basic {
+                    username: "admin".to_string(),
+                    password: "hunter2".to_string(),
+                },
+                collection_id: Some(collection.id.clone()),
+                workspace_id: collection.workspace_id.clone(),
+                ..Default::default()
+            })
+            .expect("Failed to create request");
+
+        let run = RunnerService::new(db)
+            .run(
+                RunCollectionInput {
+                    collection_id: collection.id.clone(),
+                    request_ids: Vec::new(),
+                    delay_ms: 0,
+                    concurrency: 1,
+                    stop_on_failure: false,
+                    data_file: None,
+                },
+                |_| {},
+            )
+            .await
+            .expect("Run failed");
+
+        let report = report::render_junit(&run);
+        assert!(report.contains("Authorization: Basic ********"));
+        assert!(!report.contains("YWRtaW46aHVudGVyMg=="));
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::assertions::AssertionResult;
+use crate::extractions::ExtractionResult;
+use crate::scripts::ConsoleEntry;
+
+/// Event name used to stream `RunProgress` to the frontend
+pub const RUN_PROGRESS_EVENT: &str = "collection-run-progress";
+
+/// Response bodies are cut to this size before being stored with a run
+pub const MAX_RECORDED_BODY_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunCollectionInput {
+    pub collection_id: String,
+    /// Run only these requests (still in collection order); all requests when empty
+    #[serde(default)]
+    pub request_ids: Vec<String>,
+    /// Pause between dispatching one request and the next
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// Maximum number of requests in flight at once. Requests with extractions or
+    /// scripts, or in a collection with scripts, still run on their own so later
+    /// requests see the variables they set; results are reported in run order.
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    #[serde(default)]
+    pub stop_on_failure: bool,
+    /// Path to a CSV or JSON data file. Each row is one iteration over the
+    /// selected requests, with its columns available as variables.
+    #[serde(default)]
+    pub data_file: Option<String>,
+}
+
+fn default_concurrency() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Passed,
+    Failed,
+    /// A failure ended the run early because `stop_on_failure` was set
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRequestResult {
+    /// Zero-based data row this result belongs to (always 0 without a data file)
+    #[serde(default)]
+    pub iteration: usize,
+    pub request_id: String,
+    pub request_name: String,
+    pub method: String,
+    /// URL after variable substitution
+    pub url: String,
+    /// Response status, or None when no response was received
+    pub status: Option<u16>,
+    pub time_ms: u64,
+    pub passed: bool,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub assertions: Vec<AssertionResult>,
+    #[serde(default)]
+    pub details: RunRequestDetails,
+    /// Variables saved by the request's extraction rules
+    #[serde(default)]
+    pub extractions: Vec<ExtractionResult>,
+    /// Output of the request's scripts
+    #[serde(default)]
+    pub console: Vec<ConsoleEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunHeader {
+    pub key: String,
+    pub value: String,
+}
+
+/// What was sent and received, with the values of secret variables masked
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunRequestDetails {
+    pub request_headers: Vec<RunHeader>,
+    pub request_body: String,
+    pub response_headers: Vec<RunHeader>,
+    /// Truncated to `MAX_RECORDED_BODY_BYTES`
+    pub response_body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionRun {
+    pub id: String,
+    pub collection_id: String,
+    pub collection_name: String,
+    pub workspace_id: String,
+    pub status: RunStatus,
+    /// Number of iterations planned for the run
+    #[serde(default = "default_iterations")]
+    pub iterations: usize,
+    pub results: Vec<RunRequestResult>,
+    pub total_time_ms: u64,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+fn default_iterations() -> usize {
+    1
+}
+
+impl CollectionRun {
+    pub fn new(collection_id: String, collection_name: String, workspace_id: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            collection_id,
+            collection_name,
+            workspace_id,
+            status: RunStatus::Passed,
+            iterations: 1,
+            results: Vec::new(),
+            total_time_ms: 0,
+            started_at: now,
+            finished_at: now,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Junit,
+    Json,
+    Html,
+}
+
+/// Progress streamed while a run executes
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RunProgress {
+    /// `total` counts requests across all iterations
+    Started {
+        run_id: String,
+        iterations: usize,
+        total: usize,
+    },
+    /// Sent as each request finishes, which may be out of run order when the run is
+    /// concurrent; `index` is the request's position in the run
+    RequestCompleted {
+        run_id: String,
+        iteration: usize,
+        index: usize,
+        total: usize,
+        result: Box<RunRequestResult>,
+    },
+    Finished {
+        run_id: String,
+        status: RunStatus,
+    },
+}
@@ -0,0 +1,382 @@
+//! Sandboxed Rhai engine
+//!
+//! Scripts have no file, network or process access. They see the request (and
+//! response, after a send) as object maps plus the functions registered here,
+//! and operation, depth and size limits stop runaway scripts.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use chrono::Utc;
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope};
+use uuid::Uuid;
+
+use crate::http::{HttpRequest, HttpRequestHeader, HttpRequestParam, HttpResponse};
+
+use super::types::{ConsoleEntry, ConsoleLevel, ScriptPhase, ScriptSource};
+
+const MAX_OPERATIONS: u64 = 1_000_000;
+const MAX_CALL_LEVELS: usize = 64;
+const MAX_EXPR_DEPTH: usize = 64;
+const MAX_STRING_SIZE: usize = 10 * 1024 * 1024;
+const MAX_COLLECTION_SIZE: usize = 100_000;
+
+/// Variables visible to a script, and what the script did with them
+#[derive(Debug, Clone, Default)]
+pub struct ScriptContext {
+    pub variables: HashMap<String, String>,
+    /// `set_var` calls in the order they were made
+    pub changes: Vec<(String, String)>,
+    pub console: Vec<ConsoleEntry>,
+}
+
+/// Run a pre-request script. Edits to `request.method`, `url`, `body`, `headers`
+/// and `params` are applied to the outgoing request.
+pub fn run_pre_request(
+    script: &str,
+    source: ScriptSource,
+    request: &mut HttpRequest,
+    context: &mut ScriptContext,
+) -> Result<(), String> {
+    let mut scope = Scope::new();
+    scope.push("request", request_map(request));
+
+    run(script, source, ScriptPhase::PreRequest, &mut scope, context)?;
+
+    let edited = scope
+        .get_value::<Map>("request")
+        .ok_or_else(|| "`request` must remain an object map".to_string())?;
+    apply_request_map(&edited, request);
+    Ok(())
+}
+
+/// Run a post-response script with `request` and `response` maps. They are copies,
+/// so edits have no effect. (Not pushed as constants: assigning to a constant map
+/// property panics inside Rhai instead of raising a script error.)
+pub fn run_post_response(
+    script: &str,
+    source: ScriptSource,
+    request: &HttpRequest,
+    response: &HttpResponse,
+    context: &mut ScriptContext,
+) -> Result<(), String> {
+    let mut scope = Scope::new();
+    scope.push("request", request_map(request));
+    scope.push("response", response_map(response));
+
+    run(script, source, ScriptPhase::PostResponse, &mut scope, context)
+}
+
+fn run(
+    script: &str,
+    source: ScriptSource,
+    phase: ScriptPhase,
+    scope: &mut Scope,
+    context: &mut ScriptContext,
+) -> Result<(), String> {
+    let state = Rc::new(RefCell::new(std::mem::take(context)));
+    let engine = build_engine(&state, source, phase);
+    let outcome = engine.run_with_scope(scope, script);
+    drop(engine);
+    *context = state.take();
+
+    outcome.map_err(|e| {
+        let message = e.to_string();
+        context
+            .console
+            .push(entry(source, phase, ConsoleLevel::Error, message.clone()));
+        message
+    })
+}
+
+fn build_engine(
+    state: &Rc<RefCell<ScriptContext>>,
+    source: ScriptSource,
+    phase: ScriptPhase,
+) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(MAX_COLLECTION_SIZE);
+    engine.disable_symbol("eval");
+
+    let log = state.clone();
+    engine.on_print(move |text| {
+        log.borrow_mut()
+            .console
+            .push(entry(source, phase, ConsoleLevel::Log, text.to_string()));
+    });
+    let log = state.clone();
+    engine.on_debug(move |text, _, _| {
+        log.borrow_mut()
+            .console
+            .push(entry(source, phase, ConsoleLevel::Debug, text.to_string()));
+    });
+
+    let vars = state.clone();
+    engine.register_fn("get_var", move |name: &str| -> Dynamic {
+        vars.borrow()
+            .variables
+            .get(name)
+            .map(|value| value.clone().into())
+            .unwrap_or(Dynamic::UNIT)
+    });
+    let vars = state.clone();
+    engine.register_fn("set_var", move |name: &str, value: Dynamic| {
+        let value = value.to_string();
+        let mut state = vars.borrow_mut();
+        state.variables.insert(name.to_string(), value.clone());
+        state.changes.push((name.to_string(), value));
+    });
+
+    engine.register_fn("uuid", || Uuid::new_v4().to_string());
+    engine.register_fn("timestamp", || Utc::now().timestamp_millis());
+    engine.register_fn(
+        "parse_json",
+        |text: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+            serde_json::from_str(text).map_err(|e| e.to_string().into())
+        },
+    );
+    engine.register_fn(
+        "to_json",
+        |value: Dynamic| -> Result<String, Box<EvalAltResult>> {
+            serde_json::to_string(&value).map_err(|e| e.to_string().into())
+        },
+    );
+
+    engine
+}
+
+fn entry(
+    source: ScriptSource,
+    phase: ScriptPhase,
+    level: ConsoleLevel,
+    message: String,
+) -> ConsoleEntry {
+    ConsoleEntry {
+        source,
+        phase,
+        level,
+        message,
+    }
+}
+
+fn request_map(request: &HttpRequest) -> Map {
+    let headers = pairs_map(
+        request
+            .headers
+            .iter()
+            .filter(|h| h.enabled)
+            .map(|h| (h.key.as_str(), h.value.as_str())),
+    );
+    let params = pairs_map(
+        request
+            .params
+            .iter()
+            .filter(|p| p.enabled)
+            .map(|p| (p.key.as_str(), p.value.as_str())),
+    );
+
+    let mut map = Map::new();
+    map.insert("method".into(), request.method.clone().into());
+    map.insert("url".into(), request.url.clone().into());
+    map.insert("body".into(), request.body.clone().into());
+    map.insert("headers".into(), headers.into());
+    map.insert("params".into(), params.into());
+    map
+}
+
+fn response_map(response: &HttpResponse) -> Map {
+    let mut headers = Map::new();
+    for header in &response.headers {
+        let value = match headers.get(header.key.as_str()) {
+            Some(existing) => format!("{}, {}", existing, header.value),
+            None => header.value.clone(),
+        };
+        headers.insert(header.key.as_str().into(), value.into());
+    }
+
+    let mut map = Map::new();
+    map.insert("status".into(), (response.status as i64).into());
+    map.insert("status_text".into(), response.status_text.clone().into());
+    map.insert("http_version".into(), response.http_version.clone().into());
+    map.insert("headers".into(), headers.into());
+    map.insert("body".into(), response.body.clone().into());
+    map.insert("time_ms".into(), (response.time_ms as i64).into());
+    map
+}
+
+fn pairs_map<'a>(pairs: impl Iterator<Item = (&'a str, &'a str)>) -> Map {
+    pairs
+        .map(|(key, value)| (key.into(), value.to_string().into()))
+        .collect()
+}
+
+fn apply_request_map(map: &Map, request: &mut HttpRequest) {
+    let text = |field: &str| map.get(field).map(|v| v.to_string());
+    if let Some(method) = text("method") {
+        request.method = method.to_uppercase();
+    }
+    if let Some(url) = text("url") {
+        request.url = url;
+    }
+    if let Some(body) = text("body") {
+        request.body = body;
+    }
+
+    if let Some(headers) = map.get("headers").and_then(|v| v.read_lock::<Map>()) {
+        let original: Vec<_> = request
+            .headers
+            .iter()
+            .map(|h| (h.key.clone(), h.value.clone(), h.enabled))
+            .collect();
+        request.headers = merge_pairs(&original, &headers)
+            .into_iter()
+            .map(|(key, value, enabled)| HttpRequestHeader {
+                key,
+                value,
+                enabled,
+            })
+            .collect();
+    }
+    if let Some(params) = map.get("params").and_then(|v| v.read_lock::<Map>()) {
+        let original: Vec<_> = request
+            .params
+            .iter()
+            .map(|p| (p.key.clone(), p.value.clone(), p.enabled))
+            .collect();
+        request.params = merge_pairs(&original, &params)
+            .into_iter()
+            .map(|(key, value, enabled)| HttpRequestParam {
+                key,
+                value,
+                enabled,
+            })
+            .collect();
+    }
+}
+
+/// Rebuild key/value pairs after a script edited them as a map: values are
+/// updated in place, removed keys are disabled and new keys are appended
+fn merge_pairs(original: &[(String, String, bool)], edited: &Map) -> Vec<(String, String, bool)> {
+    let mut merged: Vec<_> = original
+        .iter()
+        .map(|(key, value, enabled)| match edited.get(key.as_str()) {
+            Some(new_value) if *enabled => (key.clone(), new_value.to_string(), true),
+            _ => (key.clone(), value.clone(), false),
+        })
+        .collect();
+
+    for (key, value) in edited {
+        if !original.iter().any(|(k, _, enabled)| *enabled && k == key.as_str()) {
+            merged.push((key.to_string(), value.to_string(), true));
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpResponseHeader;
+
+    fn request() -> HttpRequest {
+        HttpRequest {
+            method: "GET".to_string(),
+            url: "https://api.example.com/items".to_string(),
+            headers: vec![HttpRequestHeader {
+                key: "Accept".to_string(),
+                value: "text/plain".to_string(),
+                enabled: true,
+            }],
+            params: Vec::new(),
+            body_type: "none".to_string(),
+            body: String::new(),
+            form_data: Vec::new(),
+            http_version: Default::default(),
+            timeout_ms: None,
+            validate_ssl: None,
+            assertions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_scripts_edit_request_and_set_variables() {
+        let mut context = ScriptContext::default();
+        context
+            .variables
+            .insert("api_key".to_string(), "k-123".to_string());
+
+        let mut outgoing = request();
+        run_pre_request(
+            r#"
+                let nonce = "n-" + get_var("api_key");
+                request.headers["X-Nonce"] = nonce;
+                request.headers.remove("Accept");
+                request.params.page = 2;
+                print(`signed with ${nonce}`);
+            "#,
+            ScriptSource::Request,
+            &mut outgoing,
+            &mut context,
+        )
+        .expect("Pre-request script failed");
+
+        assert_eq!(outgoing.headers.len(), 2);
+        assert!(!outgoing.headers[0].enabled);
+        assert_eq!(outgoing.headers[1].key, "X-Nonce");
+        assert_eq!(outgoing.headers[1].value, "n-k-123");
+        assert_eq!(outgoing.params[0].value, "2");
+        assert_eq!(context.console[0].message, "signed with n-k-123");
+
+        let response = HttpResponse {
+            status: 200,
+            status_text: "OK".to_string(),
+            headers: vec![HttpResponseHeader {
+                key: "Content-Type".to_string(),
+                value: "application/json".to_string(),
+            }],
+            http_version: "HTTP/1.1".to_string(),
+            body: r#"{"token": "abc", "expires_in": 3600}"#.to_string(),
+            time_ms: 10,
+            size_bytes: 36,
+            assertions: Vec::new(),
+            extractions: Vec::new(),
+            console: Vec::new(),
+        };
+        run_post_response(
+            r#"
+                let data = parse_json(response.body);
+                if response.status == 200 { set_var("token", data.token); }
+            "#,
+            ScriptSource::Collection,
+            &outgoing,
+            &response,
+            &mut context,
+        )
+        .expect("Post-response script failed");
+        assert_eq!(context.changes, vec![("token".to_string(), "abc".to_string())]);
+
+        let error = run_post_response(
+            "let data = parse_json(response.body); data.missing.field",
+            ScriptSource::Request,
+            &outgoing,
+            &response,
+            &mut context,
+        );
+        assert!(error.is_err());
+        assert_eq!(context.console.last().unwrap().level, ConsoleLevel::Error);
+
+        let runaway = run_pre_request(
+            "loop { }",
+            ScriptSource::Request,
+            &mut outgoing,
+            &mut context,
+        );
+        assert!(runaway.is_err());
+    }
+}
@@ -0,0 +1,11 @@
+//! Pre-request and post-response scripts
+//!
+//! Rhai scripts stored on collections and requests, run around each send.
+
+mod engine;
+mod service;
+mod types;
+
+#[allow(unused_imports)]
+pub use types::{ConsoleEntry, ConsoleLevel, ScriptPhase, ScriptSource, Scripts};
+pub(crate) use service::ScriptService;
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::collections::CollectionService;
+use crate::db::{Database, DbError, DbResult};
+use crate::http::{HttpRequest, HttpResponse};
+use crate::requests::Request;
+use crate::variables::VariableService;
+
+use super::engine::{self, ScriptContext};
+use super::types::{ConsoleEntry, ScriptSource, Scripts};
+
+pub struct ScriptService {
+    db: Database,
+}
+
+impl ScriptService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Collection scripts first, then the request's own
+    pub fn hooks_for(&self, request: &Request) -> DbResult<Vec<(ScriptSource, Scripts)>> {
+        let mut hooks = Vec::new();
+        if let Some(collection_id) = &request.collection_id {
+            let collection = CollectionService::new(self.db.clone()).get(collection_id)?;
+            hooks.push((ScriptSource::Collection, collection.scripts));
+        }
+        hooks.push((ScriptSource::Request, request.scripts.clone()));
+        Ok(hooks)
+    }
+
+    /// Run the pre-request hooks of `request` against the outgoing request.
+    /// `variables` is updated with whatever the scripts set, and those values are
+    /// also saved where they resolve for the request.
+    pub fn pre_request(
+        &self,
+        request: &Request,
+        http_request: &mut HttpRequest,
+        variables: &mut HashMap<String, String>,
+        console: &mut Vec<ConsoleEntry>,
+    ) -> DbResult<()> {
+        for (source, scripts) in self.hooks_for(request)? {
+            if scripts.pre_request.trim().is_empty() {
+                continue;
+            }
+            let mut context = self.context(variables);
+            let outcome =
+                engine::run_pre_request(&scripts.pre_request, source, http_request, &mut context);
+            self.finish(request, context, variables, console)?;
+            outcome.map_err(|e| DbError::Script(format!("Pre-request script: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    pub fn post_response(
+        &self,
+        request: &Request,
+        http_request: &HttpRequest,
+        response: &HttpResponse,
+        variables: &mut HashMap<String, String>,
+        console: &mut Vec<ConsoleEntry>,
+    ) -> DbResult<()> {
+        for (source, scripts) in self.hooks_for(request)? {
+            if scripts.post_response.trim().is_empty() {
+                continue;
+            }
+            let mut context = self.context(variables);
+            let outcome = engine::run_post_response(
+                &scripts.post_response,
+                source,
+                http_request,
+                response,
+                &mut context,
+            );
+            self.finish(request, context, variables, console)?;
+            outcome.map_err(|e| DbError::Script(format!("Post-response script: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    fn context(&self, variables: &HashMap<String, String>) -> ScriptContext {
+        ScriptContext {
+            variables: variables.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Save variable changes (even from a script that failed part-way) and collect output
+    fn finish(
+        &self,
+        request: &Request,
+        context: ScriptContext,
+        variables: &mut HashMap<String, String>,
+        console: &mut Vec<ConsoleEntry>,
+    ) -> DbResult<()> {
+        let service = VariableService::new(self.db.clone());
+        for (name, value) in context.changes {
+            service.set_resolved(
+                Some(&request.workspace_id),
+                request.collection_id.as_deref(),
+                Some(&request.id),
+                &name,
+                value.clone(),
+            )?;
+            variables.insert(name, value);
+        }
+        console.extend(context.console);
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// Rhai source for the hooks around a send. Empty scripts are skipped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Scripts {
+    #[serde(default)]
+    pub pre_request: String,
+    #[serde(default)]
+    pub post_response: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptSource {
+    Collection,
+    Request,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScriptPhase {
+    PreRequest,
+    PostResponse,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleLevel {
+    Log,
+    Debug,
+    Error,
+}
+
+impl ConsoleLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Log => "log",
+            Self::Debug => "debug",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// One line of script output: `print`, `debug` or an uncaught script error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsoleEntry {
+    pub source: ScriptSource,
+    pub phase: ScriptPhase,
+    pub level: ConsoleLevel,
+    pub message: String,
+}
@@ -3,7 +3,11 @@ use tauri::State;
 use crate::db::Database;
 
 use super::service::VariableService;
-use super::types::{CreateVariableInput, ResolvedVariable, UpdateVariableInput, Variable};
+use super::types::{
+    CreateVariableInput, ExportVariablesInput, ImportVariablesInput, ImportVariablesResult,
+    ResolvedVariable, SyncResolution, SyncStatus, UpdateVariableInput, Variable, VariableLint,
+    VariableUsage,
+};
 
 #[tauri::command]
 pub fn get_variable(db: State<Database>, id: String) -> Result<Variable, String> {
@@ -11,6 +15,16 @@ pub fn get_variable(db: State<Database>, id: String) -> Result<Variable, String>
     service.get(&id).map_err(|e| e.to_string())
 }
 
+/// Clear-text value of a variable, for showing or editing a secret
+#[tauri::command]
+pub fn reveal_variable(db: State<Database>, id: String) -> Result<String, String> {
+    let service = VariableService::new(db.inner().clone());
+    service
+        .get(&id)
+        .and_then(|variable| service.plaintext(&variable))
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_global_variables(db: State<Database>) -> Result<Vec<Variable>, String> {
     let service = VariableService::new(db.inner().clone());
@@ -26,6 +40,15 @@ pub fn get_workspace_variables(
     service.get_by_workspace(&workspace_id).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn get_environment_variables(
+    db: State<Database>,
+    environment_id: String,
+) -> Result<Vec<Variable>, String> {
+    let service = VariableService::new(db.inner().clone());
+    service.get_by_environment(&environment_id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_collection_variables(
     db: State<Database>,
@@ -84,3 +107,87 @@ pub fn delete_variable(db: State<Database>, id: String) -> Result<(), String> {
     let service = VariableService::new(db.inner().clone());
     service.delete(&id).map_err(|e| e.to_string())
 }
+
+/// Requests and collections of a workspace that reference a variable
+#[tauri::command]
+pub fn find_variable_usages(
+    db: State<Database>,
+    workspace_id: String,
+    name: String,
+) -> Result<Vec<VariableUsage>, String> {
+    let service = VariableService::new(db.inner().clone());
+    service.find_usages(&workspace_id, &name).map_err(|e| e.to_string())
+}
+
+/// Rename a variable together with every reference to it
+#[tauri::command]
+pub fn rename_variable(
+    db: State<Database>,
+    id: String,
+    new_name: String,
+) -> Result<Variable, String> {
+    let service = VariableService::new(db.inner().clone());
+    service.rename(&id, &new_name).map_err(|e| e.to_string())
+}
+
+/// Copy a workspace's variables into another one, e.g. a duplicated workspace
+#[tauri::command]
+pub fn copy_workspace_variables(
+    db: State<Database>,
+    source_workspace_id: String,
+    target_workspace_id: String,
+    copy_secrets: bool,
+) -> Result<Vec<Variable>, String> {
+    let service = VariableService::new(db.inner().clone());
+    service
+        .copy_workspace(&source_workspace_id, &target_workspace_id, copy_secrets)
+        .map_err(|e| e.to_string())
+}
+
+/// Unresolved references, unused variables and shadowed variables of a workspace
+#[tauri::command]
+pub fn lint_variables(db: State<Database>, workspace_id: String) -> Result<VariableLint, String> {
+    let service = VariableService::new(db.inner().clone());
+    service.lint(&workspace_id).map_err(|e| e.to_string())
+}
+
+/// Import a `.env` or JSON file into a scope
+#[tauri::command]
+pub fn import_variables(
+    db: State<Database>,
+    input: ImportVariablesInput,
+) -> Result<ImportVariablesResult, String> {
+    let service = VariableService::new(db.inner().clone());
+    service.import(input).map_err(|e| e.to_string())
+}
+
+/// Render a scope's variables as a `.env` or JSON file; secrets are left out
+/// unless requested
+#[tauri::command]
+pub fn export_variables(
+    db: State<Database>,
+    input: ExportVariablesInput,
+) -> Result<String, String> {
+    let service = VariableService::new(db.inner().clone());
+    service.export(input).map_err(|e| e.to_string())
+}
+
+/// Synced variables that differ between the workspaces of a sync group
+#[tauri::command]
+pub fn get_sync_status(db: State<Database>, group_id: String) -> Result<SyncStatus, String> {
+    let service = VariableService::new(db.inner().clone());
+    service.sync_status(&group_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn resolve_sync_conflict(
+    db: State<Database>,
+    group_id: String,
+    name: String,
+    resolution: SyncResolution,
+) -> Result<SyncStatus, String> {
+    let service = VariableService::new(db.inner().clone());
+    service
+        .resolve_sync_conflict(&group_id, &name, resolution)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,190 @@
+//! Built-in template functions
+//!
+//! Dynamic variables such as `{{$uuid}}` are registered as zero-argument
+//! functions named with a leading `$`; helpers such as `{{sha256(body)}}` take
+//! arguments. See `template::interpolate` for the call syntax.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::OnceLock;
+
+use base64::Engine;
+use chrono::{SecondsFormat, TimeDelta, Utc};
+use hmac::{Hmac, Mac};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub type TemplateFunction = Box<dyn Fn(&[String]) -> Result<String, String> + Send + Sync>;
+
+#[derive(Default)]
+pub struct FunctionRegistry {
+    functions: HashMap<String, TemplateFunction>,
+}
+
+impl FunctionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<F>(&mut self, name: &str, function: F)
+    where
+        F: Fn(&[String]) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.functions.insert(name.to_string(), Box::new(function));
+    }
+
+    pub fn call(&self, name: &str, args: &[String]) -> Result<String, String> {
+        let function = self
+            .functions
+            .get(name)
+            .ok_or_else(|| format!("Unknown function: {}", name))?;
+        function(args)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
+
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+
+        // Dynamic variables
+        registry.register("$uuid", |args| arity(args, 0).map(|_| uuid()));
+        registry.register("$guid", |args| arity(args, 0).map(|_| uuid()));
+        registry.register("$timestamp", |args| {
+            arity(args, 0).map(|_| Utc::now().timestamp().to_string())
+        });
+        registry.register("$isoTimestamp", |args| {
+            arity(args, 0).map(|_| Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true))
+        });
+        registry.register("$randomInt", |args| {
+            arity(args, 0).map(|_| rand::thread_rng().gen_range(0..=1000).to_string())
+        });
+        registry.register("$randomFirstName", |args| {
+            arity(args, 0).map(|_| pick(FIRST_NAMES).to_string())
+        });
+        registry.register("$randomLastName", |args| {
+            arity(args, 0).map(|_| pick(LAST_NAMES).to_string())
+        });
+        registry.register("$randomFullName", |args| {
+            arity(args, 0).map(|_| format!("{} {}", pick(FIRST_NAMES), pick(LAST_NAMES)))
+        });
+        registry.register("$randomEmail", |args| {
+            arity(args, 0).map(|_| {
+                format!(
+                    "{}.{}{}@{}",
+                    pick(FIRST_NAMES).to_lowercase(),
+                    pick(LAST_NAMES).to_lowercase(),
+                    rand::thread_rng().gen_range(1..1000),
+                    pick(EMAIL_DOMAINS)
+                )
+            })
+        });
+
+        // Encoding and hashing
+        registry.register("base64", |args| {
+            arity(args, 1).map(|_| base64::engine::general_purpose::STANDARD.encode(&args[0]))
+        });
+        registry.register("base64Decode", |args| {
+            arity(args, 1)?;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(args[0].trim())
+                .map_err(|e| format!("Invalid base64: {}", e))?;
+            String::from_utf8(bytes).map_err(|_| "Decoded base64 is not UTF-8".to_string())
+        });
+        registry.register("urlencode", |args| {
+            arity(args, 1).map(|_| urlencoding::encode(&args[0]).into_owned())
+        });
+        registry.register("urldecode", |args| {
+            arity(args, 1)?;
+            urlencoding::decode(&args[0])
+                .map(|s| s.into_owned())
+                .map_err(|e| format!("Invalid URL encoding: {}", e))
+        });
+        registry.register("sha256", |args| {
+            arity(args, 1).map(|_| hex(&Sha256::digest(args[0].as_bytes())))
+        });
+        registry.register("hmacSha256", |args| {
+            arity(args, 2)?;
+            let mut mac = Hmac::<Sha256>::new_from_slice(args[0].as_bytes())
+                .map_err(|e| e.to_string())?;
+            mac.update(args[1].as_bytes());
+            Ok(hex(&mac.finalize().into_bytes()))
+        });
+        registry.register("lower", |args| arity(args, 1).map(|_| args[0].to_lowercase()));
+        registry.register("upper", |args| arity(args, 1).map(|_| args[0].to_uppercase()));
+
+        // Numbers and dates
+        registry.register("randomInt", |args| {
+            arity(args, 2)?;
+            let (min, max) = (int(&args[0])?, int(&args[1])?);
+            if min > max {
+                return Err(format!("randomInt: {} is greater than {}", min, max));
+            }
+            Ok(rand::thread_rng().gen_range(min..=max).to_string())
+        });
+        // date(format) or date(format, offset_seconds), formatted with strftime syntax
+        registry.register("date", |args| {
+            if args.is_empty() || args.len() > 2 {
+                return Err("date expects a format and an optional offset".to_string());
+            }
+            let offset = args.get(1).map(|s| int(s)).transpose()?.unwrap_or(0);
+            let time = TimeDelta::try_seconds(offset)
+                .and_then(|delta| Utc::now().checked_add_signed(delta))
+                .ok_or_else(|| format!("date: offset {} is out of range", offset))?;
+            let mut formatted = String::new();
+            write!(formatted, "{}", time.format(&args[0]))
+                .map_err(|_| format!("Invalid date format: {}", args[0]))?;
+            Ok(formatted)
+        });
+
+        registry
+    }
+}
+
+/// Shared registry of built-in functions
+pub fn builtins() -> &'static FunctionRegistry {
+    static BUILTINS: OnceLock<FunctionRegistry> = OnceLock::new();
+    BUILTINS.get_or_init(FunctionRegistry::with_builtins)
+}
+
+fn arity(args: &[String], expected: usize) -> Result<(), String> {
+    if args.len() == expected {
+        Ok(())
+    } else {
+        Err(format!("Expected {} argument(s), got {}", expected, args.len()))
+    }
+}
+
+fn int(text: &str) -> Result<i64, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("Not an integer: {}", text))
+}
+
+fn uuid() -> String {
+    Uuid::new_v4().to_string()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn pick(items: &[&'static str]) -> &'static str {
+    items.choose(&mut rand::thread_rng()).copied().unwrap_or_default()
+}
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Amara", "Carlos", "Chen", "Dana", "Elena", "Farah", "Grace", "Hiro",
+    "Ines", "Jonas", "Kofi", "Lena", "Mateo", "Nadia", "Omar", "Priya", "Quinn", "Sofia",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Adams", "Baker", "Costa", "Diaz", "Evans", "Fischer", "Garcia", "Haddad", "Ito",
+    "Johnson", "Kim", "Lopez", "Mensah", "Novak", "Okafor", "Patel", "Rossi", "Silva",
+    "Tanaka", "Weber",
+];
+
+const EMAIL_DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
@@ -1,8 +1,20 @@
 mod commands;
+pub mod functions;
 mod service;
+mod sources;
+mod sync;
+pub mod template;
+mod transfer;
 mod types;
+mod usage;
 
 pub use commands::*;
 #[allow(unused_imports)]
-pub use types::{CreateVariableInput, UpdateVariableInput, Variable, VariableScope};
+pub use types::{
+    CreateVariableInput, ExportVariablesInput, ImportStrategy, ImportVariablesInput,
+    ImportVariablesResult, ResolvedVariable, SecretExport, ShadowedVariable, SyncConflict,
+    SyncEntry, SyncResolution, SyncStatus, UnavailableVariable, UnresolvedReference,
+    UpdateVariableInput, UsageOwner, Variable, VariableFileFormat, VariableLint, VariableRef,
+    VariableScope, VariableSource, VariableType, VariableUsage,
+};
 pub(crate) use service::VariableService;
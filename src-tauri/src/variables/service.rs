@@ -1,9 +1,32 @@
+use std::collections::{HashMap, HashSet};
+
 use chrono::Utc;
-use redb::ReadableTable;
+use redb::WriteTransaction;
 
-use crate::db::{Database, DbError, DbResult, VARIABLES, VARIABLES_BY_SCOPE};
+use crate::collections::{Collection, CollectionService};
+use crate::db::helpers::{get_row, index_add, index_get, index_remove, put_row, remove_row};
+use crate::db::{
+    Database, DbError, DbResult, COLLECTIONS, COLLECTIONS_BY_WORKSPACE, REQUESTS,
+    REQUESTS_BY_WORKSPACE, VARIABLES, VARIABLES_BY_SCOPE, WORKSPACE_SYNC_GROUPS,
+};
+use crate::environments::EnvironmentService;
+use crate::requests::{Request, RequestService};
+use crate::vault::{crypto, VaultService};
+use crate::workspaces::WorkspaceService;
 
-use super::types::{CreateVariableInput, ResolvedVariable, UpdateVariableInput, Variable, VariableScope};
+use super::functions;
+use super::sources;
+use super::sync;
+use super::template;
+use super::transfer::{self, VariableEntry};
+use super::usage;
+use super::types::{
+    CreateVariableInput, ExportVariablesInput, ImportStrategy, ImportVariablesInput,
+    ImportVariablesResult, ResolvedVariable, SecretExport, ShadowedVariable, SyncConflict,
+    SyncEntry, SyncResolution, SyncStatus, UnavailableVariable, UnresolvedReference,
+    UpdateVariableInput, UsageOwner, Variable, VariableFileFormat, VariableLint, VariableRef,
+    VariableScope, VariableSource, VariableType, VariableUsage,
+};
 
 pub struct VariableService {
     db: Database,
@@ -60,6 +83,19 @@ impl VariableService {
         self.get_by_scope_key(&format!("workspace:{}", workspace_id))
     }
 
+    pub fn get_by_environment(&self, environment_id: &str) -> DbResult<Vec<Variable>> {
+        self.get_by_scope_key(&format!("environment:{}", environment_id))
+    }
+
+    /// The workspace's active environment, if it has one
+    pub fn active_environment(&self, workspace_id: &str) -> DbResult<Option<String>> {
+        match WorkspaceService::new(self.db.clone()).get(workspace_id) {
+            Ok(workspace) => Ok(workspace.active_environment_id),
+            Err(DbError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn get_by_collection(&self, collection_id: &str) -> DbResult<Vec<Variable>> {
         self.get_by_scope_key(&format!("collection:{}", collection_id))
     }
@@ -68,63 +104,147 @@ impl VariableService {
         self.get_by_scope_key(&format!("request:{}", request_id))
     }
 
+    /// Variables visible in a context with their clear-text values, the highest
+    /// precedence winning. Those that can't be read right now are left out, so
+    /// references to them stay unresolved.
     pub fn get_resolved(
         &self,
         workspace_id: Option<&str>,
         collection_id: Option<&str>,
         request_id: Option<&str>,
     ) -> DbResult<Vec<ResolvedVariable>> {
-        let mut resolved_map = std::collections::HashMap::new();
-
-        // Global variables (lowest priority)
-        for var in self.get_global()? {
-            resolved_map.insert(var.name.clone(), ResolvedVariable {
-                name: var.name,
-                value: var.value,
-                scope: var.scope,
-                is_secret: var.is_secret,
-            });
-        }
+        let (resolved, _) = self.resolve_context(workspace_id, collection_id, request_id)?;
+        Ok(resolved)
+    }
 
-        // Workspace variables
-        if let Some(ws_id) = workspace_id {
-            for var in self.get_by_workspace(ws_id)? {
-                resolved_map.insert(var.name.clone(), ResolvedVariable {
-                    name: var.name,
-                    value: var.value,
-                    scope: var.scope,
-                    is_secret: var.is_secret,
-                });
+    /// `get_resolved`, together with the winning variables that were left out:
+    /// secrets while the vault is locked and sourced variables whose source
+    /// can't be read. They only fail the requests that reference them (see
+    /// `template::check_available`).
+    pub fn resolve_context(
+        &self,
+        workspace_id: Option<&str>,
+        collection_id: Option<&str>,
+        request_id: Option<&str>,
+    ) -> DbResult<(Vec<ResolvedVariable>, Vec<UnavailableVariable>)> {
+        let mut resolved_map = HashMap::new();
+
+        // Later layers win
+        for layer in self.layers(workspace_id, collection_id, request_id)? {
+            for var in layer {
+                resolved_map.insert(var.name.clone(), var);
             }
         }
 
-        // Collection variables
-        if let Some(coll_id) = collection_id {
-            for var in self.get_by_collection(coll_id)? {
-                resolved_map.insert(var.name.clone(), ResolvedVariable {
-                    name: var.name,
-                    value: var.value,
-                    scope: var.scope,
-                    is_secret: var.is_secret,
-                });
+        // Only the winning variables are decrypted
+        let mut resolved = Vec::new();
+        let mut unavailable = Vec::new();
+        for var in resolved_map.into_values() {
+            let name = var.name.clone();
+            let sourced = !var.source.is_value();
+            match self.resolve(var) {
+                Ok(variable) => resolved.push(variable),
+                Err(e) if sourced || matches!(e, DbError::Vault(_)) => {
+                    unavailable.push(UnavailableVariable { name, reason: e.to_string() })
+                }
+                Err(e) => return Err(e),
             }
         }
+        resolved.sort_by(|a, b| a.name.cmp(&b.name));
+        unavailable.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok((resolved, unavailable))
+    }
 
-        // Request variables (highest priority)
+    /// Variables visible in a context, one layer per scope from the lowest
+    /// precedence to the highest: global, workspace, the workspace's active
+    /// environment, collection, request
+    fn layers(
+        &self,
+        workspace_id: Option<&str>,
+        collection_id: Option<&str>,
+        request_id: Option<&str>,
+    ) -> DbResult<Vec<Vec<Variable>>> {
+        let environment_id = match workspace_id {
+            Some(ws_id) => self.active_environment(ws_id)?,
+            None => None,
+        };
+        self.layers_with(workspace_id, environment_id.as_deref(), collection_id, request_id)
+    }
+
+    /// `layers`, with `environment_id` in place of the active environment
+    fn layers_with(
+        &self,
+        workspace_id: Option<&str>,
+        environment_id: Option<&str>,
+        collection_id: Option<&str>,
+        request_id: Option<&str>,
+    ) -> DbResult<Vec<Vec<Variable>>> {
+        let mut layers = vec![self.get_global()?];
+
+        if let Some(ws_id) = workspace_id {
+            layers.push(self.get_by_workspace(ws_id)?);
+            if let Some(env_id) = environment_id {
+                layers.push(self.get_by_environment(env_id)?);
+            }
+        }
+        if let Some(coll_id) = collection_id {
+            layers.push(self.get_by_collection(coll_id)?);
+        }
         if let Some(req_id) = request_id {
-            for var in self.get_by_request(req_id)? {
-                resolved_map.insert(var.name.clone(), ResolvedVariable {
-                    name: var.name,
-                    value: var.value,
-                    scope: var.scope,
-                    is_secret: var.is_secret,
-                });
+            layers.push(self.get_by_request(req_id)?);
+        }
+
+        Ok(layers)
+    }
+
+    fn resolve(&self, variable: Variable) -> DbResult<ResolvedVariable> {
+        Ok(ResolvedVariable {
+            value: self.plaintext(&variable)?,
+            value_type: variable.value_type,
+            name: variable.name,
+            scope: variable.scope,
+            is_secret: variable.is_secret,
+        })
+    }
+
+    /// Clear-text value of a variable, reading it from its source or decrypting
+    /// a sealed secret
+    pub fn plaintext(&self, variable: &Variable) -> DbResult<String> {
+        if let Some(value) = sources::read(&variable.source)? {
+            variable.value_type.validate(&value).map_err(|e| {
+                DbError::InvalidInput(format!("{}: {}", variable.name, e))
+            })?;
+            return Ok(value);
+        }
+        match &variable.encrypted_value {
+            Some(sealed) => {
+                let key = VaultService::new(self.db.clone())
+                    .key()?
+                    .ok_or_else(|| DbError::Vault("Vault is not set up".to_string()))?;
+                crypto::open(&key, sealed)
             }
+            None => Ok(variable.value.clone()),
         }
+    }
 
-        let mut resolved: Vec<_> = resolved_map.into_values().collect();
-        resolved.sort_by(|a, b| a.name.cmp(&b.name));
-        Ok(resolved)
+    /// Store `value` on the variable, sealed when it is a secret and a vault exists.
+    /// Sourced variables store nothing.
+    fn protect(&self, variable: &mut Variable, value: String) -> DbResult<()> {
+        if !variable.source.is_value() {
+            variable.encrypted_value = None;
+            variable.value = String::new();
+            return Ok(());
+        }
+        if variable.is_secret {
+            if let Some(key) = VaultService::new(self.db.clone()).key()? {
+                variable.encrypted_value = Some(crypto::seal(&key, &value)?);
+                variable.value = String::new();
+                return Ok(());
+            }
+        }
+        variable.encrypted_value = None;
+        variable.value = value;
+        Ok(())
     }
 
     pub fn create(&self, input: CreateVariableInput) -> DbResult<Variable> {
@@ -134,103 +254,1373 @@ impl VariableService {
             input.scope,
             input.scope_id,
         );
+        variable.value_type = input.value_type;
+        variable.source = input.source;
         variable.is_secret = input.is_secret;
         variable.description = input.description;
-
-        let scope_key = variable.scope_key();
-        let json = serde_json::to_string(&variable)
-            .map_err(|e| DbError::Serialization(e.to_string()))?;
+        let value = std::mem::take(&mut variable.value);
+        self.prepare(&mut variable, value)?;
 
         let write_txn = self.db.begin_write()?;
+        self.insert_in(&write_txn, &variable)?;
+        write_txn.commit()?;
 
-        {
-            let mut table = write_txn.open_table(VARIABLES)?;
-            table.insert(variable.id.as_str(), json.as_str())?;
-        }
-
-        // Update scope index
-        {
-            let mut idx_table = write_txn.open_table(VARIABLES_BY_SCOPE)?;
-            let ids_json = match idx_table.get(scope_key.as_str())? {
-                Some(value) => value.value().to_string(),
-                None => "[]".to_string(),
-            };
+        Ok(variable)
+    }
 
-            let mut ids: Vec<String> = serde_json::from_str(&ids_json)
-                .map_err(|e| DbError::Serialization(e.to_string()))?;
+    /// Copy a workspace's variables into another workspace, with their type and
+    /// source. Values are read in clear and sealed again for the copy; secrets
+    /// are copied empty unless `copy_secrets` is set.
+    pub fn copy_workspace(
+        &self,
+        source_workspace_id: &str,
+        target_workspace_id: &str,
+        copy_secrets: bool,
+    ) -> DbResult<Vec<Variable>> {
+        let mut copies = Vec::new();
+        for original in self.get_by_workspace(source_workspace_id)? {
+            let mut copy = Variable::new(
+                original.name.clone(),
+                String::new(),
+                VariableScope::Workspace,
+                Some(target_workspace_id.to_string()),
+            );
+            copy.value_type = original.value_type;
+            copy.source = original.source.clone();
+            copy.is_secret = original.is_secret;
+            copy.description = original.description.clone();
+            if original.is_secret && !copy_secrets {
+                self.protect(&mut copy, String::new())?;
+            } else {
+                let value = if original.source.is_value() {
+                    self.plaintext(&original)?
+                } else {
+                    String::new()
+                };
+                self.prepare(&mut copy, value)?;
+            }
+            copies.push(copy);
+        }
 
-            ids.push(variable.id.clone());
+        let write_txn = self.db.begin_write()?;
+        for copy in &copies {
+            self.insert_in(&write_txn, copy)?;
+        }
+        write_txn.commit()?;
 
-            let new_json = serde_json::to_string(&ids)
-                .map_err(|e| DbError::Serialization(e.to_string()))?;
+        Ok(copies)
+    }
 
-            idx_table.insert(scope_key.as_str(), new_json.as_str())?;
+    /// Validate a variable's source and value, then store the value (see `protect`)
+    pub fn prepare(&self, variable: &mut Variable, value: String) -> DbResult<()> {
+        sources::validate(&variable.source).map_err(DbError::InvalidInput)?;
+        if variable.source.is_value() {
+            variable.value_type.validate(&value).map_err(DbError::InvalidInput)?;
         }
+        self.protect(variable, value)
+    }
 
-        write_txn.commit()?;
+    /// Store a new variable and mirror it across its sync group
+    fn insert_in(&self, write_txn: &WriteTransaction, variable: &Variable) -> DbResult<()> {
+        put_row(write_txn, VARIABLES, &variable.id, variable)?;
+        index_add(write_txn, VARIABLES_BY_SCOPE, &variable.scope_key(), &variable.id)?;
 
-        Ok(variable)
+        if let (Some(group), Some(workspace_id)) =
+            (sync::group_for(write_txn, variable)?, variable.scope_id.as_deref())
+        {
+            if sync::syncs(&group, &variable.name, variable.is_secret) {
+                sync::propagate(write_txn, &group, workspace_id, &variable.name, Some(variable))?;
+            }
+        }
+        Ok(())
     }
 
     pub fn update(&self, input: UpdateVariableInput) -> DbResult<Variable> {
         let mut variable = self.get(&input.id)?;
+        let previous_name = variable.name.clone();
 
         if let Some(name) = input.name {
             variable.name = name;
         }
-        if let Some(value) = input.value {
-            variable.value = value;
-        }
-        if let Some(is_secret) = input.is_secret {
-            variable.is_secret = is_secret;
+        let secret_changed = input.is_secret.is_some_and(|s| s != variable.is_secret);
+        let type_changed = input.value_type.is_some_and(|t| t != variable.value_type);
+        let source_changed = input.source.as_ref().is_some_and(|s| *s != variable.source);
+        if input.value.is_some() || secret_changed || type_changed || source_changed {
+            let source = input.source.unwrap_or_else(|| variable.source.clone());
+            sources::validate(&source).map_err(DbError::InvalidInput)?;
+            if input.value.is_some() && !source.is_value() {
+                return Err(DbError::InvalidInput(format!(
+                    "{} is read from its source and cannot be set",
+                    variable.name
+                )));
+            }
+            // Sourced variables store nothing, so one switched back to a
+            // literal starts empty
+            let value = match input.value {
+                Some(value) => value,
+                None if variable.source.is_value() => self.plaintext(&variable)?,
+                None => String::new(),
+            };
+            variable.source = source;
+            variable.value_type = input.value_type.unwrap_or(variable.value_type);
+            variable.is_secret = input.is_secret.unwrap_or(variable.is_secret);
+            self.prepare(&mut variable, value)?;
         }
         if let Some(description) = input.description {
             variable.description = Some(description);
         }
         variable.updated_at = Utc::now();
 
-        let json = serde_json::to_string(&variable)
-            .map_err(|e| DbError::Serialization(e.to_string()))?;
-
         let write_txn = self.db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(VARIABLES)?;
-            table.insert(variable.id.as_str(), json.as_str())?;
-        }
+        self.store_in(&write_txn, &variable, &previous_name)?;
         write_txn.commit()?;
 
         Ok(variable)
     }
 
+    /// Store a changed variable and mirror it across its sync group;
+    /// `previous_name` differs from the current one on a rename
+    fn store_in(
+        &self,
+        write_txn: &WriteTransaction,
+        variable: &Variable,
+        previous_name: &str,
+    ) -> DbResult<()> {
+        put_row(write_txn, VARIABLES, &variable.id, variable)?;
+
+        if let (Some(mut group), Some(workspace_id)) =
+            (sync::group_for(write_txn, variable)?, variable.scope_id.as_deref())
+        {
+            if sync::syncs(&group, previous_name, variable.is_secret) {
+                sync::propagate(write_txn, &group, workspace_id, previous_name, Some(variable))?;
+
+                // A rename keeps the variable in the group under its new name
+                if previous_name != variable.name {
+                    group.synced_variable_names.retain(|n| n != previous_name);
+                    if !group.synced_variable_names.contains(&variable.name) {
+                        group.synced_variable_names.push(variable.name.clone());
+                    }
+                    group.updated_at = Utc::now();
+                    put_row(write_txn, WORKSPACE_SYNC_GROUPS, &group.id, &group)?;
+                }
+            } else if sync::syncs(&group, &variable.name, variable.is_secret) {
+                sync::propagate(write_txn, &group, workspace_id, &variable.name, Some(variable))?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn delete(&self, id: &str) -> DbResult<()> {
         let variable = self.get(id)?;
-        let scope_key = variable.scope_key();
 
         let write_txn = self.db.begin_write()?;
+        self.remove_in(&write_txn, &variable)?;
+        write_txn.commit()?;
+
+        Ok(())
+    }
 
-        // Remove from variables table
+    /// Remove a variable and its copies across its sync group
+    fn remove_in(&self, write_txn: &WriteTransaction, variable: &Variable) -> DbResult<()> {
+        remove_row(write_txn, VARIABLES, &variable.id)?;
+        index_remove(write_txn, VARIABLES_BY_SCOPE, &variable.scope_key(), &variable.id)?;
+
+        if let (Some(group), Some(workspace_id)) =
+            (sync::group_for(write_txn, variable)?, variable.scope_id.as_deref())
         {
-            let mut table = write_txn.open_table(VARIABLES)?;
-            table.remove(id)?;
+            if sync::syncs(&group, &variable.name, variable.is_secret) {
+                sync::propagate(write_txn, &group, workspace_id, &variable.name, None)?;
+            }
         }
+        Ok(())
+    }
 
-        // Update scope index
-        {
-            let mut idx_table = write_txn.open_table(VARIABLES_BY_SCOPE)?;
-            let ids_json = idx_table
-                .get(scope_key.as_str())?
-                .map(|v| v.value().to_string())
-                .unwrap_or_else(|| "[]".to_string());
-            let mut ids: Vec<String> = serde_json::from_str(&ids_json)
-                .map_err(|e| DbError::Serialization(e.to_string()))?;
-            ids.retain(|i| i != id);
-            let new_json = serde_json::to_string(&ids)
-                .map_err(|e| DbError::Serialization(e.to_string()))?;
-            idx_table.insert(scope_key.as_str(), new_json.as_str())?;
+    /// Import a `.env` or JSON file into one scope in a single transaction.
+    /// An entry with an empty value never clears an existing secret, so files
+    /// exported with masked secrets can be imported back. A name listed twice
+    /// takes its last entry.
+    pub fn import(&self, input: ImportVariablesInput) -> DbResult<ImportVariablesResult> {
+        let content = std::fs::read_to_string(&input.path)?;
+        let mut entries = match input.format {
+            VariableFileFormat::DotEnv => transfer::from_dotenv(&content),
+            VariableFileFormat::Json => transfer::from_json(&content)?,
+            VariableFileFormat::PostmanEnvironment => {
+                transfer::from_postman_environment(&content)?
+            }
+        };
+        let mut seen = HashSet::new();
+        entries.reverse();
+        entries.retain(|entry| seen.insert(entry.name.clone()));
+        entries.reverse();
+        let scope_id = input.scope_id.as_deref();
+        let existing = self.get_by_scope_key(&input.scope.key(scope_id))?;
+
+        let mut result = ImportVariablesResult::default();
+        let mut created = Vec::new();
+        let mut updated = Vec::new();
+        for entry in entries {
+            let name = entry.name.clone();
+            let with_name = |e: DbError| match e {
+                DbError::InvalidInput(message) => {
+                    DbError::InvalidInput(format!("{}: {}", name, message))
+                }
+                e => e,
+            };
+
+            match existing.iter().find(|v| v.name == entry.name) {
+                Some(_) if input.strategy == ImportStrategy::Skip => result.skipped.push(name),
+                Some(current) => {
+                    let keep_value =
+                        entry.value.is_empty() && current.is_secret && entry.source.is_none();
+                    let mut variable = current.clone();
+                    variable.source = match entry.source {
+                        Some(source) => source,
+                        None if keep_value => current.source.clone(),
+                        None => VariableSource::Value,
+                    };
+                    variable.value_type = entry.value_type.unwrap_or(current.value_type);
+                    variable.is_secret = entry.is_secret.unwrap_or(current.is_secret);
+                    if entry.description.is_some() {
+                        variable.description = entry.description;
+                    }
+                    let value = if keep_value && current.source.is_value() {
+                        self.plaintext(current)?
+                    } else {
+                        entry.value
+                    };
+                    self.prepare(&mut variable, value).map_err(with_name)?;
+                    variable.updated_at = Utc::now();
+                    updated.push(variable);
+                    result.updated.push(name);
+                }
+                None => {
+                    let mut variable = Variable::new(
+                        entry.name,
+                        String::new(),
+                        input.scope.clone(),
+                        input.scope_id.clone(),
+                    );
+                    variable.value_type = entry.value_type.unwrap_or_default();
+                    variable.source = entry.source.unwrap_or_default();
+                    variable.is_secret = entry.is_secret.unwrap_or(false);
+                    variable.description = entry.description;
+                    self.prepare(&mut variable, entry.value).map_err(with_name)?;
+                    created.push(variable);
+                    result.created.push(name);
+                }
+            }
         }
 
+        let write_txn = self.db.begin_write()?;
+        for variable in &created {
+            self.insert_in(&write_txn, variable)?;
+        }
+        for variable in &updated {
+            self.store_in(&write_txn, variable, &variable.name)?;
+        }
+        if input.strategy == ImportStrategy::Overwrite {
+            for variable in &existing {
+                if updated.iter().any(|v| v.id == variable.id) {
+                    continue;
+                }
+                // Deleting a synced variable would delete it in every workspace
+                // of the group, not just the one the file is for
+                let synced = sync::group_for(&write_txn, variable)?
+                    .is_some_and(|group| sync::syncs(&group, &variable.name, variable.is_secret));
+                if synced {
+                    result.skipped.push(variable.name.clone());
+                } else {
+                    self.remove_in(&write_txn, variable)?;
+                    result.deleted.push(variable.name.clone());
+                }
+            }
+        }
         write_txn.commit()?;
 
-        Ok(())
+        Ok(result)
+    }
+
+    /// Render a scope's variables as a `.env`, JSON or Postman environment file.
+    /// Sourced variables are written by source to JSON and with their current
+    /// value otherwise.
+    pub fn export(&self, input: ExportVariablesInput) -> DbResult<String> {
+        let variables = self.get_by_scope_key(&input.scope.key(input.scope_id.as_deref()))?;
+
+        let mut entries = Vec::new();
+        for variable in variables {
+            if variable.is_secret && input.secrets == SecretExport::Omit {
+                continue;
+            }
+            let reveal = !variable.is_secret || input.secrets == SecretExport::Include;
+            let by_source = input.format == VariableFileFormat::Json && !variable.source.is_value();
+            let value = if reveal && !by_source {
+                self.plaintext(&variable)?
+            } else {
+                String::new()
+            };
+            entries.push(VariableEntry {
+                value,
+                value_type: Some(variable.value_type),
+                source: by_source.then_some(variable.source),
+                is_secret: Some(variable.is_secret),
+                description: variable.description,
+                name: variable.name,
+            });
+        }
+
+        match input.format {
+            VariableFileFormat::DotEnv => transfer::to_dotenv(&entries),
+            VariableFileFormat::Json => transfer::to_json(&entries),
+            VariableFileFormat::PostmanEnvironment => transfer::to_postman_environment(
+                &self.scope_name(&input.scope, input.scope_id.as_deref())?,
+                &entries,
+            ),
+        }
+    }
+
+    /// Name of the record owning a scope, e.g. for naming an exported file
+    fn scope_name(&self, scope: &VariableScope, scope_id: Option<&str>) -> DbResult<String> {
+        let id = scope_id.unwrap_or("");
+        Ok(match scope {
+            VariableScope::Global => "Globals".to_string(),
+            VariableScope::Workspace => WorkspaceService::new(self.db.clone()).get(id)?.name,
+            VariableScope::Environment => EnvironmentService::new(self.db.clone()).get(id)?.name,
+            VariableScope::Collection => CollectionService::new(self.db.clone()).get(id)?.name,
+            VariableScope::Request => RequestService::new(self.db.clone()).get(id)?.name,
+        })
+    }
+
+    /// Set a variable's value in one scope, creating it there if needed
+    pub fn set_in_scope(
+        &self,
+        scope: VariableScope,
+        scope_id: Option<&str>,
+        name: &str,
+        value: String,
+    ) -> DbResult<Variable> {
+        let existing = match scope {
+            VariableScope::Global => self.get_global()?,
+            VariableScope::Workspace => self.get_by_workspace(scope_id.unwrap_or(""))?,
+            VariableScope::Environment => self.get_by_environment(scope_id.unwrap_or(""))?,
+            VariableScope::Collection => self.get_by_collection(scope_id.unwrap_or(""))?,
+            VariableScope::Request => self.get_by_request(scope_id.unwrap_or(""))?,
+        };
+
+        match existing.into_iter().find(|v| v.name == name) {
+            Some(variable) => self.update(UpdateVariableInput {
+                id: variable.id,
+                name: None,
+                value: Some(value),
+                value_type: None,
+                source: None,
+                is_secret: None,
+                description: None,
+            }),
+            None => self.create(CreateVariableInput {
+                name: name.to_string(),
+                value,
+                scope,
+                scope_id: scope_id.map(str::to_string),
+                value_type: VariableType::default(),
+                source: Default::default(),
+                is_secret: false,
+                description: None,
+            }),
+        }
+    }
+
+    /// Set a variable in the most specific scope that already defines it for this
+    /// context, so the new value is the one that resolves. Undefined variables are
+    /// created in the workspace (or globally without a workspace).
+    pub fn set_resolved(
+        &self,
+        workspace_id: Option<&str>,
+        collection_id: Option<&str>,
+        request_id: Option<&str>,
+        name: &str,
+        value: String,
+    ) -> DbResult<Variable> {
+        let environment_id = match workspace_id {
+            Some(ws_id) => self.active_environment(ws_id)?,
+            None => None,
+        };
+        let scopes = [
+            (VariableScope::Request, request_id),
+            (VariableScope::Collection, collection_id),
+            (VariableScope::Environment, environment_id.as_deref()),
+            (VariableScope::Workspace, workspace_id),
+        ];
+        for (scope, scope_id) in scopes {
+            let Some(scope_id) = scope_id else { continue };
+            let defined = match scope {
+                VariableScope::Request => self.get_by_request(scope_id)?,
+                VariableScope::Collection => self.get_by_collection(scope_id)?,
+                VariableScope::Environment => self.get_by_environment(scope_id)?,
+                _ => self.get_by_workspace(scope_id)?,
+            }
+            .iter()
+            .any(|v| v.name == name);
+            if defined {
+                return self.set_in_scope(scope, Some(scope_id), name, value);
+            }
+        }
+
+        if self.get_global()?.iter().any(|v| v.name == name) || workspace_id.is_none() {
+            self.set_in_scope(VariableScope::Global, None, name, value)
+        } else {
+            self.set_in_scope(VariableScope::Workspace, workspace_id, name, value)
+        }
+    }
+
+    /// Requests and collections of a workspace that reference `name`
+    pub fn find_usages(&self, workspace_id: &str, name: &str) -> DbResult<Vec<VariableUsage>> {
+        let mut usages: Vec<VariableUsage> = CollectionService::new(self.db.clone())
+            .get_by_workspace(workspace_id)?
+            .iter()
+            .filter_map(|collection| usage::in_collection(collection, name))
+            .collect();
+        usages.extend(
+            RequestService::new(self.db.clone())
+                .get_all_by_workspace(workspace_id)?
+                .iter()
+                .filter_map(|request| usage::in_request(request, name)),
+        );
+        Ok(usages)
+    }
+
+    /// Rename a variable and every reference to it in one transaction. References
+    /// are renamed wherever the variable reaches: every workspace for a global,
+    /// the whole sync group for a synced workspace variable, and only its own
+    /// collection or request for narrower scopes. References that a narrower
+    /// variable of the same name shadows are left alone.
+    pub fn rename(&self, id: &str, new_name: &str) -> DbResult<Variable> {
+        let mut variable = self.get(id)?;
+        let previous_name = variable.name.clone();
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err(DbError::InvalidInput("Variable name cannot be empty".to_string()));
+        }
+        if new_name == previous_name {
+            return Ok(variable);
+        }
+        let taken = self
+            .get_by_scope_key(&variable.scope_key())?
+            .iter()
+            .any(|v| v.name == new_name);
+        if taken {
+            return Err(DbError::InvalidInput(format!(
+                "A variable named {} already exists in this scope",
+                new_name
+            )));
+        }
+
+        let mut workspace_ids = self.workspaces_seeing(&variable)?;
+        variable.name = new_name.to_string();
+        variable.updated_at = Utc::now();
+
+        let write_txn = self.db.begin_write()?;
+
+        if let Some(group) = sync::group_for(&write_txn, &variable)? {
+            if sync::syncs(&group, &previous_name, variable.is_secret) {
+                for workspace_id in group.workspace_ids {
+                    if !workspace_ids.contains(&workspace_id) {
+                        workspace_ids.push(workspace_id);
+                    }
+                }
+            }
+        }
+        self.store_in(&write_txn, &variable, &previous_name)?;
+
+        // The layers are read outside the transaction, so they still hold the
+        // old name; an environment counts as active for its own variables
+        let resolves_to_variable =
+            |workspace_id: &str, collection_id: Option<&str>, request_id: Option<&str>| {
+                let environment_id = match variable.scope {
+                    VariableScope::Environment => variable.scope_id.clone(),
+                    _ => self.active_environment(workspace_id)?,
+                };
+                let winner = self
+                    .layers_with(
+                        Some(workspace_id),
+                        environment_id.as_deref(),
+                        collection_id,
+                        request_id,
+                    )?
+                    .into_iter()
+                    .rev()
+                    .find_map(|layer| layer.into_iter().find(|v| v.name == previous_name));
+                DbResult::Ok(winner.is_some_and(|w| {
+                    w.scope == variable.scope
+                        && (w.scope == VariableScope::Workspace || w.scope_id == variable.scope_id)
+                }))
+            };
+        let now = Utc::now();
+        for workspace_id in &workspace_ids {
+            for collection_id in index_get(&write_txn, COLLECTIONS_BY_WORKSPACE, workspace_id)? {
+                let Some(mut collection) =
+                    get_row::<Collection>(&write_txn, COLLECTIONS, &collection_id)?
+                else {
+                    continue;
+                };
+                if resolves_to_variable(workspace_id, Some(&collection.id), None)?
+                    && usage::rename_in_collection(&mut collection, &previous_name, new_name) > 0
+                {
+                    collection.updated_at = now;
+                    put_row(&write_txn, COLLECTIONS, &collection_id, &collection)?;
+                }
+            }
+            for request_id in index_get(&write_txn, REQUESTS_BY_WORKSPACE, workspace_id)? {
+                let Some(mut request) = get_row::<Request>(&write_txn, REQUESTS, &request_id)?
+                else {
+                    continue;
+                };
+                if resolves_to_variable(
+                    workspace_id,
+                    request.collection_id.as_deref(),
+                    Some(&request.id),
+                )? && usage::rename_in_request(&mut request, &previous_name, new_name) > 0
+                {
+                    request.updated_at = now;
+                    put_row(&write_txn, REQUESTS, &request_id, &request)?;
+                }
+            }
+        }
+
+        write_txn.commit()?;
+
+        Ok(variable)
+    }
+
+    /// Unresolved references, unused variables and shadowed variables of a
+    /// workspace, following the precedence of `get_resolved`. Names written by
+    /// an extraction count as defined, and a variable whose name appears quoted
+    /// in a script counts as used.
+    pub fn lint(&self, workspace_id: &str) -> DbResult<VariableLint> {
+        let base = self.layers(Some(workspace_id), None, None)?;
+        let collections = CollectionService::new(self.db.clone()).get_by_workspace(workspace_id)?;
+        let requests = RequestService::new(self.db.clone()).get_all_by_workspace(workspace_id)?;
+
+        let mut collection_vars = HashMap::new();
+        for collection in &collections {
+            collection_vars.insert(collection.id.clone(), self.get_by_collection(&collection.id)?);
+        }
+        let extracted: HashSet<&str> = requests
+            .iter()
+            .flat_map(|r| r.extractions.iter().map(|e| e.variable.as_str()))
+            .collect();
+        let scripts: Vec<&str> = collections
+            .iter()
+            .map(|c| &c.scripts)
+            .chain(requests.iter().map(|r| &r.scripts))
+            .flat_map(|s| [s.pre_request.as_str(), s.post_response.as_str()])
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let mut lint = VariableLint::default();
+        let mut report_unresolved = |references: Vec<(String, Vec<String>)>,
+                                     layers: &[&Vec<Variable>],
+                                     usage: VariableUsage| {
+            for (name, fields) in references {
+                let defined = layers.iter().any(|l| l.iter().any(|v| v.name == name))
+                    || extracted.contains(name.as_str())
+                    || (name.starts_with('$') && functions::builtins().contains(&name));
+                if !defined {
+                    lint.unresolved.push(UnresolvedReference {
+                        name,
+                        owner: usage.owner,
+                        owner_id: usage.owner_id.clone(),
+                        owner_name: usage.owner_name.clone(),
+                        collection_id: usage.collection_id.clone(),
+                        fields,
+                    });
+                }
+            }
+        };
+
+        // Names referenced anywhere, per collection and per request
+        let mut used_anywhere = HashSet::new();
+        let mut used_in_collection: HashMap<&str, HashSet<String>> = HashMap::new();
+        let mut used_in_defaults: HashMap<&str, HashSet<String>> = HashMap::new();
+        let mut used_in_request: HashMap<&str, HashSet<String>> = HashMap::new();
+        let no_vars = Vec::new();
+
+        for collection in &collections {
+            let references = usage::collection_references(collection);
+            let names: HashSet<String> = references.iter().map(|(n, _)| n.clone()).collect();
+            used_anywhere.extend(names.iter().cloned());
+            used_in_collection.entry(&collection.id).or_default().extend(names.iter().cloned());
+            used_in_defaults.insert(&collection.id, names);
+
+            let mut layers: Vec<&Vec<Variable>> = base.iter().collect();
+            layers.push(&collection_vars[&collection.id]);
+            report_unresolved(
+                references,
+                &layers,
+                VariableUsage {
+                    owner: UsageOwner::Collection,
+                    owner_id: collection.id.clone(),
+                    owner_name: collection.name.clone(),
+                    collection_id: Some(collection.id.clone()),
+                    fields: Vec::new(),
+                    count: 0,
+                },
+            );
+        }
+
+        let mut request_vars = Vec::new();
+        for request in &requests {
+            let variables = self.get_by_request(&request.id)?;
+            let references = usage::request_references(request);
+            let mut names: HashSet<String> = references.iter().map(|(n, _)| n.clone()).collect();
+            used_anywhere.extend(names.iter().cloned());
+            if let Some(collection_id) = request.collection_id.as_deref() {
+                used_in_collection
+                    .entry(collection_id)
+                    .or_default()
+                    .extend(names.iter().cloned());
+                // Collection defaults are applied to the request before interpolation
+                if let Some(defaults) = used_in_defaults.get(collection_id) {
+                    names.extend(defaults.iter().cloned());
+                }
+            }
+            used_in_request.insert(&request.id, names);
+
+            let collection_layer = request
+                .collection_id
+                .as_ref()
+                .and_then(|id| collection_vars.get(id))
+                .unwrap_or(&no_vars);
+            let mut layers: Vec<&Vec<Variable>> = base.iter().collect();
+            layers.push(collection_layer);
+            layers.push(&variables);
+            report_unresolved(
+                references,
+                &layers,
+                VariableUsage {
+                    owner: UsageOwner::Request,
+                    owner_id: request.id.clone(),
+                    owner_name: request.name.clone(),
+                    collection_id: request.collection_id.clone(),
+                    fields: Vec::new(),
+                    count: 0,
+                },
+            );
+            request_vars.push((request, variables));
+        }
+
+        // Unused, narrowest reach first
+        let in_script = |name: &str| {
+            let quoted = [format!("\"{}\"", name), format!("'{}'", name)];
+            scripts.iter().any(|s| quoted.iter().any(|q| s.contains(q.as_str())))
+        };
+        let mut check_unused = |variables: &[Variable], used: Option<&HashSet<String>>| {
+            for variable in variables {
+                if !used.is_some_and(|u| u.contains(&variable.name)) && !in_script(&variable.name) {
+                    lint.unused.push(VariableRef::from(variable));
+                }
+            }
+        };
+        check_unused(&base[1], Some(&used_anywhere));
+        let environments = EnvironmentService::new(self.db.clone()).get_by_workspace(workspace_id)?;
+        for environment in environments {
+            check_unused(&self.get_by_environment(&environment.id)?, Some(&used_anywhere));
+        }
+        for collection in &collections {
+            let used = used_in_collection.get(collection.id.as_str());
+            check_unused(&collection_vars[&collection.id], used);
+        }
+        for (request, variables) in &request_vars {
+            check_unused(variables, used_in_request.get(request.id.as_str()));
+        }
+
+        // Shadowed, against every wider layer of the chain
+        let mut check_shadowed = |narrow: &[Variable], wider: &[&Vec<Variable>]| {
+            for variable in narrow {
+                let hidden: Vec<VariableRef> = wider
+                    .iter()
+                    .flat_map(|layer| layer.iter().filter(|v| v.name == variable.name))
+                    .map(VariableRef::from)
+                    .collect();
+                if !hidden.is_empty() {
+                    lint.shadowed.push(ShadowedVariable {
+                        variable: VariableRef::from(variable),
+                        hidden,
+                    });
+                }
+            }
+        };
+        let base_refs: Vec<&Vec<Variable>> = base.iter().collect();
+        for i in 1..base.len() {
+            check_shadowed(&base[i], &base_refs[..i]);
+        }
+        for collection in &collections {
+            check_shadowed(&collection_vars[&collection.id], &base_refs);
+        }
+        for (request, variables) in &request_vars {
+            let mut wider = base_refs.clone();
+            let collection_layer =
+                request.collection_id.as_ref().and_then(|id| collection_vars.get(id));
+            wider.extend(collection_layer);
+            check_shadowed(variables, &wider);
+        }
+
+        Ok(lint)
+    }
+
+    /// Workspaces whose requests can reference the variable
+    fn workspaces_seeing(&self, variable: &Variable) -> DbResult<Vec<String>> {
+        let scope_id = variable.scope_id.as_deref().unwrap_or("");
+        let workspace_id = match variable.scope {
+            VariableScope::Global => {
+                return Ok(WorkspaceService::new(self.db.clone())
+                    .get_all()?
+                    .into_iter()
+                    .map(|w| w.id)
+                    .collect())
+            }
+            VariableScope::Workspace => scope_id.to_string(),
+            VariableScope::Environment => {
+                EnvironmentService::new(self.db.clone()).get(scope_id)?.workspace_id
+            }
+            VariableScope::Collection => {
+                CollectionService::new(self.db.clone()).get(scope_id)?.workspace_id
+            }
+            VariableScope::Request => {
+                RequestService::new(self.db.clone()).get(scope_id)?.workspace_id
+            }
+        };
+        Ok(vec![workspace_id])
+    }
+
+    /// Synced names whose value, secrecy or presence differs between the
+    /// workspaces of a group. Secrets are compared in clear when the vault is
+    /// unlocked and by their sealed form otherwise.
+    pub fn sync_status(&self, group_id: &str) -> DbResult<SyncStatus> {
+        let group = WorkspaceService::new(self.db.clone()).get_sync_group(group_id)?;
+
+        let mut by_workspace = Vec::new();
+        for workspace_id in &group.workspace_ids {
+            by_workspace.push((workspace_id, self.get_by_workspace(workspace_id)?));
+        }
+
+        let mut conflicts = Vec::new();
+        for name in &group.synced_variable_names {
+            let found: Vec<(&String, Option<&Variable>)> = by_workspace
+                .iter()
+                .map(|(id, vars)| (*id, vars.iter().find(|v| &v.name == name)))
+                .collect();
+            // Secrets stay out of groups that do not sync them
+            if !group.sync_secrets && found.iter().any(|(_, v)| v.is_some_and(|v| v.is_secret)) {
+                continue;
+            }
+
+            let compared: Vec<Option<(String, bool)>> = found
+                .iter()
+                .map(|(_, v)| v.map(|v| (self.comparable(v), v.is_secret)))
+                .collect();
+            if compared.windows(2).all(|pair| pair[0] == pair[1]) {
+                continue;
+            }
+
+            conflicts.push(SyncConflict {
+                name: name.clone(),
+                entries: found
+                    .into_iter()
+                    .map(|(workspace_id, variable)| SyncEntry {
+                        workspace_id: workspace_id.clone(),
+                        variable_id: variable.map(|v| v.id.clone()),
+                        value: variable.map(|v| {
+                            if v.is_secret {
+                                template::SECRET_MASK.to_string()
+                            } else {
+                                v.value.clone()
+                            }
+                        }),
+                        is_secret: variable.is_some_and(|v| v.is_secret),
+                        updated_at: variable.map(|v| v.updated_at),
+                    })
+                    .collect(),
+            });
+        }
+
+        Ok(SyncStatus {
+            group_id: group.id,
+            conflicts,
+        })
+    }
+
+    /// Settle one synced name across the group in a single transaction
+    pub fn resolve_sync_conflict(
+        &self,
+        group_id: &str,
+        name: &str,
+        resolution: SyncResolution,
+    ) -> DbResult<SyncStatus> {
+        let mut group = WorkspaceService::new(self.db.clone()).get_sync_group(group_id)?;
+        if !group.synced_variable_names.iter().any(|n| n == name) {
+            return Err(DbError::InvalidInput(format!("{} is not synced in this group", name)));
+        }
+
+        let source = match &resolution {
+            SyncResolution::UseWorkspace { workspace_id } => {
+                if !group.workspace_ids.contains(workspace_id) {
+                    return Err(DbError::InvalidInput(
+                        "Workspace is not in this sync group".to_string(),
+                    ));
+                }
+                let variable =
+                    self.get_by_workspace(workspace_id)?.into_iter().find(|v| v.name == name);
+                (workspace_id.clone(), variable)
+            }
+            SyncResolution::Merge { value } => {
+                // The most recently updated copy keeps its secrecy and description
+                let mut latest = None::<Variable>;
+                for workspace_id in &group.workspace_ids {
+                    if let Some(v) =
+                        self.get_by_workspace(workspace_id)?.into_iter().find(|v| v.name == name)
+                    {
+                        if latest.as_ref().is_none_or(|l| v.updated_at > l.updated_at) {
+                            latest = Some(v);
+                        }
+                    }
+                }
+                let mut variable = latest.ok_or_else(|| {
+                    DbError::NotFound(format!("No workspace in the group defines {}", name))
+                })?;
+                if !variable.source.is_value() {
+                    return Err(DbError::InvalidInput(format!(
+                        "{} is read from its source and cannot be merged",
+                        name
+                    )));
+                }
+                self.prepare(&mut variable, value.clone())?;
+                variable.updated_at = Utc::now();
+                (variable.scope_id.clone().unwrap_or_default(), Some(variable))
+            }
+            SyncResolution::Unlink => (String::new(), None),
+        };
+
+        let write_txn = self.db.begin_write()?;
+
+        if let SyncResolution::Unlink = resolution {
+            group.synced_variable_names.retain(|n| n != name);
+            group.updated_at = Utc::now();
+            put_row(&write_txn, WORKSPACE_SYNC_GROUPS, &group.id, &group)?;
+        } else {
+            let (workspace_id, variable) = source;
+            if variable.as_ref().is_some_and(|v| v.is_secret) && !group.sync_secrets {
+                return Err(DbError::InvalidInput(
+                    "This group does not sync secrets".to_string(),
+                ));
+            }
+            if let Some(variable) = &variable {
+                put_row(&write_txn, VARIABLES, &variable.id, variable)?;
+            }
+            sync::propagate(&write_txn, &group, &workspace_id, name, variable.as_ref())?;
+        }
+
+        write_txn.commit()?;
+
+        self.sync_status(group_id)
+    }
+
+    /// Value used to tell synced copies apart
+    fn comparable(&self, variable: &Variable) -> String {
+        self.plaintext(variable)
+            .unwrap_or_else(|_| variable.encrypted_value.clone().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::{CollectionDefaults, CreateCollectionInput};
+    use crate::requests::CreateRequestInput;
+    use crate::workspaces::{CreateSyncGroupInput, CreateWorkspaceInput, WorkspaceService};
+    use std::env::temp_dir;
+
+    fn create_test_db() -> Database {
+        let path = temp_dir().join(format!("resona_test_{}.redb", uuid::Uuid::new_v4()));
+        Database::open_at(path).expect("Failed to create test database")
+    }
+
+    #[test]
+    fn test_sync_group_propagates_changes() {
+        let db = create_test_db();
+        let workspaces = WorkspaceService::new(db.clone());
+        let create_workspace = |name: &str| {
+            workspaces
+                .create(CreateWorkspaceInput {
+                    name: name.to_string(),
+                    description: String::new(),
+                    tags: Vec::new(),
+                })
+                .expect("Failed to create workspace")
+        };
+        let (dev, prod) = (create_workspace("Dev"), create_workspace("Prod"));
+        let group = workspaces
+            .create_sync_group(CreateSyncGroupInput {
+                name: "Shop".to_string(),
+                workspace_ids: vec![dev.id.clone(), prod.id.clone()],
+                synced_variable_names: vec!["base_url".to_string(), "token".to_string()],
+                sync_secrets: false,
+            })
+            .expect("Failed to create sync group");
+
+        let service = VariableService::new(db.clone());
+        for (name, is_secret) in [("base_url", false), ("token", true), ("local_only", false)] {
+            service
+                .create(CreateVariableInput {
+                    name: name.to_string(),
+                    value: "one".to_string(),
+                    scope: VariableScope::Workspace,
+                    scope_id: Some(dev.id.clone()),
+                    is_secret,
+                    ..Default::default()
+                })
+                .expect("Failed to create variable");
+        }
+        let base_url = service
+            .get_by_workspace(&dev.id)
+            .unwrap()
+            .into_iter()
+            .find(|v| v.name == "base_url")
+            .unwrap();
+
+        let prod_names = || -> Vec<(String, String)> {
+            service
+                .get_by_workspace(&prod.id)
+                .unwrap()
+                .into_iter()
+                .map(|v| (v.name, v.value))
+                .collect()
+        };
+        assert_eq!(prod_names(), vec![("base_url".to_string(), "one".to_string())]);
+
+        service
+            .update(UpdateVariableInput {
+                id: base_url.id.clone(),
+                name: Some("api_url".to_string()),
+                value: Some("two".to_string()),
+                value_type: None,
+                source: None,
+                is_secret: None,
+                description: None,
+            })
+            .expect("Failed to update variable");
+        assert_eq!(prod_names(), vec![("api_url".to_string(), "two".to_string())]);
+        let group = workspaces.get_sync_group(&group.id).unwrap();
+        assert!(group.synced_variable_names.contains(&"api_url".to_string()));
+
+        // Overwriting the dev scope from a file leaves synced names in place
+        let path = temp_dir().join(format!("resona_test_{}.env", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "local_only=two\n").expect("Failed to write .env file");
+        let result = service
+            .import(ImportVariablesInput {
+                path: path.to_string_lossy().into_owned(),
+                format: VariableFileFormat::DotEnv,
+                scope: VariableScope::Workspace,
+                scope_id: Some(dev.id.clone()),
+                strategy: ImportStrategy::Overwrite,
+            })
+            .expect("Failed to import");
+        std::fs::remove_file(&path).expect("Failed to remove .env file");
+        assert_eq!(result.skipped, vec!["api_url".to_string()]);
+        assert_eq!(result.deleted, vec!["token".to_string()]);
+        assert_eq!(prod_names(), vec![("api_url".to_string(), "two".to_string())]);
+
+        service.delete(&base_url.id).expect("Failed to delete variable");
+        assert!(prod_names().is_empty());
+    }
+
+    #[test]
+    fn test_sourced_variables_are_read_on_resolve() {
+        let db = create_test_db();
+        let service = VariableService::new(db.clone());
+        let path = temp_dir().join(format!("resona_test_{}.env", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "TOKEN=first\n").expect("Failed to write .env file");
+
+        let token = service
+            .create(CreateVariableInput {
+                name: "token".to_string(),
+                value: "ignored".to_string(),
+                scope: VariableScope::Global,
+                scope_id: None,
+                value_type: VariableType::String,
+                source: VariableSource::DotEnv {
+                    path: path.to_string_lossy().into_owned(),
+                    key: "TOKEN".to_string(),
+                },
+                is_secret: true,
+                description: None,
+            })
+            .expect("Failed to create variable");
+        assert!(token.value.is_empty() && token.encrypted_value.is_none());
+
+        let resolved = || service.get_resolved(None, None, None).map(|r| r[0].value.clone());
+        assert_eq!(resolved().unwrap(), "first");
+        std::fs::write(&path, "TOKEN=second\n").expect("Failed to write .env file");
+        assert_eq!(resolved().unwrap(), "second");
+
+        let set = service.set_in_scope(VariableScope::Global, None, "token", "x".to_string());
+        assert!(set.is_err());
+        std::fs::remove_file(&path).expect("Failed to remove .env file");
+        let (resolved, unavailable) = service.resolve_context(None, None, None).unwrap();
+        assert!(resolved.is_empty());
+        assert_eq!(unavailable[0].name, "token");
+    }
+
+    #[test]
+    fn test_import_and_export_files() {
+        let db = create_test_db();
+        let service = VariableService::new(db.clone());
+        for (name, value, is_secret) in [
+            ("base_url", "https://api.example.com", false),
+            ("token", "s3cr3t", true),
+            ("stale", "old", false),
+        ] {
+            service
+                .create(CreateVariableInput {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                    is_secret,
+                    ..Default::default()
+                })
+                .expect("Failed to create variable");
+        }
+
+        let export = |format, secrets| {
+            service
+                .export(ExportVariablesInput {
+                    format,
+                    scope: VariableScope::Global,
+                    scope_id: None,
+                    secrets,
+                })
+                .expect("Failed to export")
+        };
+        let dotenv = export(VariableFileFormat::DotEnv, SecretExport::Omit);
+        assert!(!dotenv.contains("token") && dotenv.contains("base_url=https://api.example.com"));
+        assert!(export(VariableFileFormat::Json, SecretExport::Mask).contains("\"token\""));
+        assert!(!export(VariableFileFormat::Json, SecretExport::Mask).contains("s3cr3t"));
+        assert!(export(VariableFileFormat::DotEnv, SecretExport::Include).contains("s3cr3t"));
+
+        let path = temp_dir().join(format!("resona_test_{}.env", uuid::Uuid::new_v4()));
+        let dotenv = "port=7070\nbase_url=\"https://new.example.com\"\ntoken=\nport=8080\n";
+        std::fs::write(&path, dotenv).expect("Failed to write .env file");
+        let import = |strategy| {
+            service.import(ImportVariablesInput {
+                path: path.to_string_lossy().into_owned(),
+                format: VariableFileFormat::DotEnv,
+                scope: VariableScope::Global,
+                scope_id: None,
+                strategy,
+            })
+        };
+
+        let result = import(ImportStrategy::Skip).expect("Failed to import");
+        assert_eq!(result.created, vec!["port".to_string()]);
+        assert_eq!(result.skipped.len(), 2);
+
+        let result = import(ImportStrategy::Overwrite).expect("Failed to import");
+        assert_eq!(result.deleted, vec!["stale".to_string()]);
+        let values: Vec<(String, String)> = service
+            .get_resolved(None, None, None)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.name, v.value))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("base_url".to_string(), "https://new.example.com".to_string()),
+                ("port".to_string(), "8080".to_string()),
+                ("token".to_string(), "s3cr3t".to_string()),
+            ]
+        );
+
+        // A masked Postman environment keeps the stored secret on re-import
+        let postman = export(VariableFileFormat::PostmanEnvironment, SecretExport::Mask);
+        assert!(postman.contains("\"name\": \"Globals\"") && postman.contains("\"secret\""));
+        std::fs::write(&path, postman.replace("8080", "9090")).expect("Failed to write file");
+        let result = service
+            .import(ImportVariablesInput {
+                path: path.to_string_lossy().into_owned(),
+                format: VariableFileFormat::PostmanEnvironment,
+                scope: VariableScope::Global,
+                scope_id: None,
+                strategy: ImportStrategy::Merge,
+            })
+            .expect("Failed to import");
+        assert_eq!(result.updated.len(), 3);
+        let resolved = service.get_resolved(None, None, None).unwrap();
+        let value = |name: &str| resolved.iter().find(|v| v.name == name).unwrap().value.clone();
+        assert_eq!((value("port"), value("token")), ("9090".to_string(), "s3cr3t".to_string()));
+        std::fs::remove_file(&path).expect("Failed to remove .env file");
+    }
+
+    fn create_collection(db: &Database, name: &str, base_url: &str) -> Collection {
+        CollectionService::new(db.clone())
+            .create(CreateCollectionInput {
+                name: name.to_string(),
+                workspace_id: "ws".to_string(),
+                defaults: CollectionDefaults {
+                    base_url: base_url.to_string(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .expect("Failed to create collection")
+    }
+
+    fn create_request(
+        requests: &RequestService,
+        collection_id: &str,
+        url: &str,
+        body: &str,
+    ) -> Request {
+        requests
+            .create(CreateRequestInput {
+                name: "List users".to_string(),
+                url: url.to_string(),
+                body: body.to_string(),
+                collection_id: Some(collection_id.to_string()),
+                workspace_id: "ws".to_string(),
+                ..Default::default()
+            })
+            .expect("Failed to create request")
+    }
+
+    fn workspace_variable(name: &str) -> CreateVariableInput {
+        CreateVariableInput {
+            name: name.to_string(),
+            value: "https://api.example.com".to_string(),
+            scope: VariableScope::Workspace,
+            scope_id: Some("ws".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rename_updates_references() {
+        let db = create_test_db();
+        let collections = CollectionService::new(db.clone());
+        let requests = RequestService::new(db.clone());
+        let service = VariableService::new(db.clone());
+
+        let collection = create_collection(&db, "Shop", "{{base_url}}");
+        let request = create_request(
+            &requests,
+            &collection.id,
+            "{{ base_url }}/users?q={{base_url_v2}}",
+            r#"{"sig": "{{sha256(base_url)}}"}"#,
+        );
+        let variable = service
+            .create(workspace_variable("base_url"))
+            .expect("Failed to create variable");
+
+        let usages = service.find_usages("ws", "base_url").expect("Failed to find usages");
+        assert_eq!(usages.len(), 2);
+        assert_eq!(usages[1].fields, vec!["url".to_string(), "body".to_string()]);
+        assert_eq!(usages[1].count, 2);
+
+        service.rename(&variable.id, "api_url").expect("Failed to rename");
+        let request = requests.get(&request.id).unwrap();
+        assert_eq!(request.url, "{{ api_url }}/users?q={{base_url_v2}}");
+        assert_eq!(request.body, r#"{"sig": "{{sha256(api_url)}}"}"#);
+        assert_eq!(collections.get(&collection.id).unwrap().defaults.base_url, "{{api_url}}");
+        assert!(service.find_usages("ws", "base_url").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lint_reports_unresolved_unused_and_shadowed() {
+        let db = create_test_db();
+        let service = VariableService::new(db.clone());
+        let collection = create_collection(&db, "Shop", "{{ api_key }}");
+        create_request(
+            &RequestService::new(db.clone()),
+            &collection.id,
+            "{{base_url}}/{{missing}}/{{$uuid}}",
+            "{{upper(\"literal\")}}",
+        );
+        for input in [
+            CreateVariableInput {
+                scope: VariableScope::Global,
+                scope_id: None,
+                ..workspace_variable("base_url")
+            },
+            workspace_variable("base_url"),
+            workspace_variable("unused_one"),
+        ] {
+            service.create(input).expect("Failed to create variable");
+        }
+
+        let lint = service.lint("ws").expect("Failed to lint");
+        let unresolved: Vec<(&str, UsageOwner)> =
+            lint.unresolved.iter().map(|u| (u.name.as_str(), u.owner)).collect();
+        assert_eq!(
+            unresolved,
+            vec![("api_key", UsageOwner::Collection), ("missing", UsageOwner::Request)]
+        );
+        assert_eq!(lint.unused.len(), 1);
+        assert_eq!(lint.unused[0].name, "unused_one");
+        assert_eq!(lint.shadowed.len(), 1);
+        assert_eq!(lint.shadowed[0].variable.scope, VariableScope::Workspace);
+        assert_eq!(lint.shadowed[0].hidden[0].scope, VariableScope::Global);
+    }
+
+    #[test]
+    fn test_sync_conflicts_are_reported_and_resolved() {
+        let db = create_test_db();
+        let workspaces = WorkspaceService::new(db.clone());
+        let service = VariableService::new(db.clone());
+
+        let mut ids = Vec::new();
+        for (name, value) in [("Dev", "http://dev"), ("Prod", "https://prod")] {
+            let workspace = workspaces
+                .create(CreateWorkspaceInput {
+                    name: name.to_string(),
+                    description: String::new(),
+                    tags: Vec::new(),
+                })
+                .expect("Failed to create workspace");
+            service
+                .create(CreateVariableInput {
+                    name: "base_url".to_string(),
+                    value: value.to_string(),
+                    scope: VariableScope::Workspace,
+                    scope_id: Some(workspace.id.clone()),
+                    ..Default::default()
+                })
+                .expect("Failed to create variable");
+            ids.push(workspace.id);
+        }
+        let group = workspaces
+            .create_sync_group(CreateSyncGroupInput {
+                name: "Shop".to_string(),
+                workspace_ids: ids.clone(),
+                synced_variable_names: vec!["base_url".to_string()],
+                sync_secrets: false,
+            })
+            .expect("Failed to create sync group");
+
+        let status = service.sync_status(&group.id).expect("Failed to get status");
+        assert_eq!(status.conflicts.len(), 1);
+        assert_eq!(status.conflicts[0].entries[1].value.as_deref(), Some("https://prod"));
+
+        let status = service
+            .resolve_sync_conflict(
+                &group.id,
+                "base_url",
+                SyncResolution::UseWorkspace {
+                    workspace_id: ids[1].clone(),
+                },
+            )
+            .expect("Failed to resolve");
+        assert!(status.conflicts.is_empty());
+        assert_eq!(service.get_by_workspace(&ids[0]).unwrap()[0].value, "https://prod");
+
+        let merge = |value: &str| {
+            service.resolve_sync_conflict(
+                &group.id,
+                "base_url",
+                SyncResolution::Merge {
+                    value: value.to_string(),
+                },
+            )
+        };
+        merge("https://merged").expect("Failed to merge");
+        assert_eq!(service.get_by_workspace(&ids[0]).unwrap()[0].value, "https://merged");
+        // A merged value is validated like any other
+        let port = service.get_by_workspace(&ids[1]).unwrap().remove(0);
+        service
+            .update(UpdateVariableInput {
+                id: port.id,
+                name: None,
+                value: Some("8080".to_string()),
+                value_type: Some(VariableType::Number),
+                source: None,
+                is_secret: None,
+                description: None,
+            })
+            .expect("Failed to update variable");
+        assert!(merge("abc").is_err());
+        assert_eq!(service.get_by_workspace(&ids[0]).unwrap()[0].value, "8080");
+
+        service
+            .resolve_sync_conflict(&group.id, "base_url", SyncResolution::Unlink)
+            .expect("Failed to unlink");
+        assert!(workspaces.get_sync_group(&group.id).unwrap().synced_variable_names.is_empty());
+    }
+
+    #[test]
+    fn test_resolving_to_a_missing_variable_keeps_unsynced_secrets() {
+        let db = create_test_db();
+        let workspaces = WorkspaceService::new(db.clone());
+        let service = VariableService::new(db.clone());
+
+        let mut ids = Vec::new();
+        for name in ["Dev", "Prod"] {
+            let workspace = workspaces
+                .create(CreateWorkspaceInput {
+                    name: name.to_string(),
+                    description: String::new(),
+                    tags: Vec::new(),
+                })
+                .expect("Failed to create workspace");
+            ids.push(workspace.id);
+        }
+        let group = workspaces
+            .create_sync_group(CreateSyncGroupInput {
+                name: "Shop".to_string(),
+                workspace_ids: ids.clone(),
+                synced_variable_names: vec!["token".to_string()],
+                sync_secrets: false,
+            })
+            .expect("Failed to create sync group");
+        service
+            .create(CreateVariableInput {
+                is_secret: true,
+                scope_id: Some(ids[1].clone()),
+                ..workspace_variable("token")
+            })
+            .expect("Failed to create variable");
+
+        service
+            .resolve_sync_conflict(
+                &group.id,
+                "token",
+                SyncResolution::UseWorkspace {
+                    workspace_id: ids[0].clone(),
+                },
+            )
+            .expect("Failed to resolve");
+        assert_eq!(service.get_by_workspace(&ids[1]).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rename_skips_shadowed_references() {
+        let db = create_test_db();
+        let requests = RequestService::new(db.clone());
+        let service = VariableService::new(db.clone());
+
+        let shop = create_collection(&db, "Shop", "");
+        let admin = create_collection(&db, "Admin", "");
+        let shop_request = create_request(&requests, &shop.id, "{{host}}/users", "");
+        let admin_request = create_request(&requests, &admin.id, "{{host}}/users", "");
+        let variable = service
+            .create(workspace_variable("host"))
+            .expect("Failed to create variable");
+        service
+            .create(CreateVariableInput {
+                scope: VariableScope::Collection,
+                scope_id: Some(admin.id.clone()),
+                ..workspace_variable("host")
+            })
+            .expect("Failed to create variable");
+
+        service.rename(&variable.id, "api_host").expect("Failed to rename");
+        assert_eq!(requests.get(&shop_request.id).unwrap().url, "{{api_host}}/users");
+        assert_eq!(requests.get(&admin_request.id).unwrap().url, "{{host}}/users");
     }
 }
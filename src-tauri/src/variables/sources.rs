@@ -0,0 +1,170 @@
+//! Reading variable values from outside the database
+//!
+//! Sources are read every time a variable resolves, so a rotated token or an
+//! edited `.env` file is picked up on the next request without re-importing.
+
+use std::env;
+use std::fs;
+
+use crate::db::{DbError, DbResult};
+
+use super::types::VariableSource;
+
+/// Check a source is complete before it is stored
+pub fn validate(source: &VariableSource) -> Result<(), String> {
+    let missing = match source {
+        VariableSource::Value => None,
+        VariableSource::Env { name } => name.trim().is_empty().then_some("Environment variable"),
+        VariableSource::File { path } => path.trim().is_empty().then_some("File path"),
+        VariableSource::DotEnv { path, key } => {
+            if path.trim().is_empty() {
+                Some("File path")
+            } else {
+                key.trim().is_empty().then_some(".env key")
+            }
+        }
+    };
+    match missing {
+        Some(field) => Err(format!("{} cannot be empty", field)),
+        None => Ok(()),
+    }
+}
+
+/// Current value of a non-literal source; `None` for `VariableSource::Value`
+pub fn read(source: &VariableSource) -> DbResult<Option<String>> {
+    let value = match source {
+        VariableSource::Value => return Ok(None),
+        VariableSource::Env { name } => env::var(name.trim()).map_err(|_| {
+            DbError::NotFound(format!("Environment variable {} is not set", name.trim()))
+        })?,
+        VariableSource::File { path } => {
+            let content = fs::read_to_string(path.trim())?;
+            content
+                .strip_suffix("\r\n")
+                .or_else(|| content.strip_suffix('\n'))
+                .unwrap_or(&content)
+                .to_string()
+        }
+        VariableSource::DotEnv { path, key } => {
+            let content = fs::read_to_string(path.trim())?;
+            parse_dotenv(&content)
+                .into_iter()
+                .rev()
+                .find(|(k, _)| k == key.trim())
+                .map(|(_, v)| v)
+                .ok_or_else(|| {
+                    DbError::NotFound(format!("{} is not defined in {}", key.trim(), path.trim()))
+                })?
+        }
+    };
+    Ok(Some(value))
+}
+
+/// Key/value pairs of a `.env` file in order. Supports `export` prefixes,
+/// comments, single quotes (taken literally) and double quotes (with `\n`,
+/// `\t`, `\"` and `\\` escapes); malformed lines are skipped.
+pub fn parse_dotenv(content: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut lines = content.lines();
+
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, rest)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c)) {
+            continue;
+        }
+
+        let rest = rest.trim_start();
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            // Double-quoted values may span lines
+            let mut raw = quoted.to_string();
+            while closing_quote(&raw).is_none() {
+                match lines.next() {
+                    Some(next) => {
+                        raw.push('\n');
+                        raw.push_str(next);
+                    }
+                    None => break,
+                }
+            }
+            let end = closing_quote(&raw).unwrap_or(raw.len());
+            unescape(&raw[..end])
+        } else if let Some(quoted) = rest.strip_prefix('\'') {
+            match quoted.find('\'') {
+                Some(end) => quoted[..end].to_string(),
+                None => quoted.to_string(),
+            }
+        } else {
+            // Unquoted values end at an inline comment
+            let end = rest.find(" #").unwrap_or(rest.len());
+            rest[..end].trim_end().to_string()
+        };
+
+        pairs.push((key.to_string(), value));
+    }
+
+    pairs
+}
+
+/// Byte offset of the first unescaped `"`
+fn closing_quote(raw: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in raw.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(raw: &str) -> String {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('t') => value.push('\t'),
+            Some(other) => value.push(other),
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = "# local settings\n\
+            export API_URL=https://api.example.com # staging\n\
+            TOKEN='abc #123'\n\
+            GREETING=\"hello\\nworld\"\n\
+            CERT=\"line one\nline two\"\n\
+            not a pair\n";
+        let pairs = parse_dotenv(content);
+        let get = |key: &str| pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+
+        assert_eq!(pairs.len(), 4);
+        assert_eq!(get("API_URL"), Some("https://api.example.com"));
+        assert_eq!(get("TOKEN"), Some("abc #123"));
+        assert_eq!(get("GREETING"), Some("hello\nworld"));
+        assert_eq!(get("CERT"), Some("line one\nline two"));
+    }
+}
@@ -0,0 +1,123 @@
+//! Propagation of workspace variables across sync groups
+//!
+//! A workspace variable whose name is listed in its workspace's sync group is
+//! mirrored into every other workspace of the group. Secrets are only mirrored
+//! when the group syncs secrets; sealed values are copied as they are, since all
+//! workspaces share the vault key. Everything here runs inside the caller's write
+//! transaction, so a change and its propagation land together.
+
+use redb::WriteTransaction;
+use uuid::Uuid;
+
+use crate::db::helpers::{get_row, index_add, index_get, index_remove, put_row, remove_row};
+use crate::db::{DbResult, VARIABLES, VARIABLES_BY_SCOPE, WORKSPACES, WORKSPACE_SYNC_GROUPS};
+use crate::workspaces::{Workspace, WorkspaceSyncGroup};
+
+use super::types::{Variable, VariableScope};
+
+/// The sync group of the workspace a workspace-scoped variable belongs to
+pub fn group_for(
+    write_txn: &WriteTransaction,
+    variable: &Variable,
+) -> DbResult<Option<WorkspaceSyncGroup>> {
+    if variable.scope != VariableScope::Workspace {
+        return Ok(None);
+    }
+    let Some(workspace_id) = variable.scope_id.as_deref() else {
+        return Ok(None);
+    };
+    let Some(group_id) = get_row::<Workspace>(write_txn, WORKSPACES, workspace_id)?
+        .and_then(|w| w.sync_group_id)
+    else {
+        return Ok(None);
+    };
+    get_row(write_txn, WORKSPACE_SYNC_GROUPS, &group_id)
+}
+
+/// Whether a variable with this name and secrecy is kept in sync by the group
+pub fn syncs(group: &WorkspaceSyncGroup, name: &str, is_secret: bool) -> bool {
+    group.synced_variable_names.iter().any(|n| n == name) && (!is_secret || group.sync_secrets)
+}
+
+/// Variable named `name` in a workspace, read inside the transaction
+pub fn find_in_workspace(
+    write_txn: &WriteTransaction,
+    workspace_id: &str,
+    name: &str,
+) -> DbResult<Option<Variable>> {
+    let key = format!("workspace:{}", workspace_id);
+    for id in index_get(write_txn, VARIABLES_BY_SCOPE, &key)? {
+        if let Some(variable) = get_row::<Variable>(write_txn, VARIABLES, &id)? {
+            if variable.name == name {
+                return Ok(Some(variable));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Mirror a change to the other workspaces of the group. `previous_name` is the
+/// name counterparts are found by (the old name on a rename); `variable` is the
+/// variable as it now is, or `None` when it was deleted.
+pub fn propagate(
+    write_txn: &WriteTransaction,
+    group: &WorkspaceSyncGroup,
+    source_workspace_id: &str,
+    previous_name: &str,
+    variable: Option<&Variable>,
+) -> DbResult<()> {
+    for workspace_id in group.workspace_ids.iter().filter(|id| *id != source_workspace_id) {
+        let mut counterpart = find_in_workspace(write_txn, workspace_id, previous_name)?;
+        if counterpart.is_none() {
+            if let Some(variable) = variable.filter(|v| v.name != previous_name) {
+                counterpart = find_in_workspace(write_txn, workspace_id, &variable.name)?;
+            }
+        }
+
+        match (variable, counterpart) {
+            (Some(variable), Some(counterpart)) => {
+                let id = counterpart.id.clone();
+                put_row(write_txn, VARIABLES, &id, &mirror(variable, counterpart))?;
+            }
+            (Some(variable), None) => {
+                let copy = Variable {
+                    id: Uuid::new_v4().to_string(),
+                    scope_id: Some(workspace_id.clone()),
+                    ..variable.clone()
+                };
+                put_row(write_txn, VARIABLES, &copy.id, &copy)?;
+                index_add(write_txn, VARIABLES_BY_SCOPE, &copy.scope_key(), &copy.id)?;
+            }
+            // A counterpart the group doesn't sync, such as a secret in a group
+            // that leaves secrets alone, is not the deleted variable's copy
+            (None, Some(counterpart))
+                if syncs(group, &counterpart.name, counterpart.is_secret) =>
+            {
+                remove_row(write_txn, VARIABLES, &counterpart.id)?;
+                index_remove(
+                    write_txn,
+                    VARIABLES_BY_SCOPE,
+                    &counterpart.scope_key(),
+                    &counterpart.id,
+                )?;
+            }
+            (None, _) => {}
+        }
+    }
+    Ok(())
+}
+
+/// `target` carrying the source's name, value, value source and flags
+fn mirror(source: &Variable, target: Variable) -> Variable {
+    Variable {
+        name: source.name.clone(),
+        value: source.value.clone(),
+        encrypted_value: source.encrypted_value.clone(),
+        value_type: source.value_type,
+        source: source.source.clone(),
+        is_secret: source.is_secret,
+        description: source.description.clone(),
+        updated_at: source.updated_at,
+        ..target
+    }
+}
@@ -0,0 +1,482 @@
+//! `{{name}}` template substitution
+//!
+//! Like `interpolate_variables` in the frontend, whitespace inside the braces is
+//! ignored and references to unknown variables are left untouched. On top of that
+//! the backend resolves dynamic variables and calls to the built-in functions.
+
+use std::collections::HashMap;
+
+use base64::Engine;
+
+use crate::db::{DbError, DbResult};
+use crate::http::HttpRequest;
+use crate::requests::{ApiKeyLocation, RequestAuth};
+
+use super::functions::{self, FunctionRegistry};
+use super::types::{ResolvedVariable, UnavailableVariable, VariableType};
+use super::usage;
+
+/// Shown in place of secret values; fixed length so the real length isn't revealed
+pub const SECRET_MASK: &str = "********";
+
+/// Name -> value lookup built from the output of `VariableService::get_resolved`
+pub fn variable_map(variables: &[ResolvedVariable]) -> HashMap<String, String> {
+    variables
+        .iter()
+        .map(|v| (v.name.clone(), v.value.clone()))
+        .collect()
+}
+
+/// Name -> type lookup for the typed variables among `variables`
+pub fn variable_types(variables: &[ResolvedVariable]) -> HashMap<String, VariableType> {
+    variables
+        .iter()
+        .filter(|v| v.value_type != VariableType::String)
+        .map(|v| (v.name.clone(), v.value_type))
+        .collect()
+}
+
+/// Replace every `{{...}}` that resolves: a defined variable name, a dynamic
+/// variable such as `{{$uuid}}`, or a function call such as
+/// `{{hmacSha256(secret, "payload")}}`. Arguments are variable names, dynamic
+/// variables, quoted strings, numbers or nested calls. Anything that doesn't
+/// resolve is left untouched.
+pub fn interpolate(text: &str, variables: &HashMap<String, String>) -> String {
+    interpolate_with(text, variables, functions::builtins())
+}
+
+pub fn interpolate_with(
+    text: &str,
+    variables: &HashMap<String, String>,
+    registry: &FunctionRegistry,
+) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len;
+        let expression = rest[start + 2..end].trim();
+
+        result.push_str(&rest[..start]);
+        match resolve(expression, variables, registry) {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// `interpolate` for JSON text. Values placed inside a string literal are escaped
+/// so they can't break out of it, and a number, boolean or JSON variable that
+/// makes up a whole string literal (`"{{payload}}"`) replaces it unquoted.
+pub fn interpolate_json(
+    text: &str,
+    variables: &HashMap<String, String>,
+    types: &HashMap<String, VariableType>,
+) -> String {
+    let registry = functions::builtins();
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut scanner = JsonScanner::default();
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len;
+        let expression = rest[start + 2..end].trim();
+
+        scanner.push(&mut result, &rest[..start]);
+        let after = &rest[end + 2..];
+        match resolve(expression, variables, registry) {
+            Some(value) if scanner.at_string_start(&result) && after.starts_with('"') => {
+                if types.get(expression).is_some_and(VariableType::is_json_literal) {
+                    result.pop();
+                    result.push_str(&value);
+                    scanner.in_string = false;
+                    rest = &after[1..];
+                    continue;
+                }
+                result.push_str(&json_escape(&value));
+            }
+            Some(value) if scanner.in_string => result.push_str(&json_escape(&value)),
+            Some(value) => result.push_str(&value),
+            None => scanner.push(&mut result, &rest[start..end + 2]),
+        }
+        rest = after;
+    }
+
+    scanner.push(&mut result, rest);
+    result
+}
+
+/// Tracks whether text copied so far ends inside a JSON string literal
+#[derive(Default)]
+struct JsonScanner {
+    in_string: bool,
+    escaped: bool,
+    /// Length of the output right after the opening quote of the current string
+    string_start: usize,
+}
+
+impl JsonScanner {
+    fn push(&mut self, result: &mut String, text: &str) {
+        for c in text.chars() {
+            result.push(c);
+            if self.escaped {
+                self.escaped = false;
+            } else if self.in_string && c == '\\' {
+                self.escaped = true;
+            } else if c == '"' {
+                self.in_string = !self.in_string;
+                self.string_start = result.len();
+            }
+        }
+    }
+
+    /// Inside a string literal with nothing in it yet
+    fn at_string_start(&self, result: &str) -> bool {
+        self.in_string && result.len() == self.string_start
+    }
+}
+
+/// Contents of a JSON string literal holding `value`, without the quotes
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::Value::String(value.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+fn resolve(
+    expression: &str,
+    variables: &HashMap<String, String>,
+    registry: &FunctionRegistry,
+) -> Option<String> {
+    // Plain names win, so variables whose names aren't valid identifiers still work
+    if let Some(value) = variables.get(expression) {
+        return Some(value.clone());
+    }
+
+    let mut parser = ExpressionParser {
+        input: expression,
+        pos: 0,
+        variables,
+        registry,
+    };
+    let value = parser.expression().ok()?;
+    parser.skip_whitespace();
+    (parser.pos == expression.len()).then_some(value)
+}
+
+/// Evaluates an expression as it parses it
+struct ExpressionParser<'a> {
+    input: &'a str,
+    pos: usize,
+    variables: &'a HashMap<String, String>,
+    registry: &'a FunctionRegistry,
+}
+
+impl ExpressionParser<'_> {
+    fn expression(&mut self) -> Result<String, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => self.string(quote),
+            Some(c) if c.is_ascii_digit() || c == '-' => Ok(self.take_while(|c| {
+                c.is_ascii_digit() || c == '-' || c == '.'
+            })),
+            Some(c) if is_identifier_char(c) => {
+                let name = self.take_while(is_identifier_char);
+                self.skip_whitespace();
+                if self.peek() == Some('(') {
+                    self.pos += 1;
+                    let args = self.arguments()?;
+                    self.registry.call(&name, &args)
+                } else if let Some(value) = self.variables.get(&name) {
+                    Ok(value.clone())
+                } else if name.starts_with('$') {
+                    self.registry.call(&name, &[])
+                } else {
+                    Err(format!("Undefined variable: {}", name))
+                }
+            }
+            _ => Err("Expected a value".to_string()),
+        }
+    }
+
+    /// Comma-separated arguments up to and including the closing parenthesis
+    fn arguments(&mut self) -> Result<Vec<String>, String> {
+        let mut args = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.pos += 1;
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expression()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') => {
+                    self.pos += 1;
+                    return Ok(args);
+                }
+                _ => return Err("Expected ',' or ')'".to_string()),
+            }
+        }
+    }
+
+    /// Quoted string; a backslash escapes the next character
+    fn string(&mut self, quote: char) -> Result<String, String> {
+        self.pos += 1;
+        let mut value = String::new();
+        let mut chars = self.input[self.pos..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        value.push(escaped);
+                    }
+                }
+                c if c == quote => {
+                    self.pos += offset + 1;
+                    return Ok(value);
+                }
+                c => value.push(c),
+            }
+        }
+        Err("Unterminated string".to_string())
+    }
+
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| accept(*c)) {
+            self.pos += c.len_utf8();
+        }
+        self.input[start..self.pos].to_string()
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+}
+
+pub(super) fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '.' | '-')
+}
+
+/// Fail when an interpolated request still references a variable that was left
+/// out of the resolved set because it couldn't be read
+pub fn check_available(request: &HttpRequest, unavailable: &[UnavailableVariable]) -> DbResult<()> {
+    if unavailable.is_empty() {
+        return Ok(());
+    }
+    for name in usage::http_request_references(request) {
+        if let Some(variable) = unavailable.iter().find(|v| v.name == name) {
+            return Err(DbError::InvalidInput(format!(
+                "Variable {} is unavailable: {}",
+                variable.name, variable.reason
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Values of the secret variables in `variables`, longest first so that a secret
+/// containing another is masked as a whole
+pub fn secret_values(variables: &[ResolvedVariable]) -> Vec<String> {
+    let mut secrets: Vec<String> = variables
+        .iter()
+        .filter(|v| v.is_secret && !v.value.is_empty())
+        .map(|v| v.value.clone())
+        .collect();
+    sort_secrets(&mut secrets);
+    secrets
+}
+
+/// Add the credentials `auth` put on `request` to `secrets`, whether or not a
+/// secret variable holds them: the Authorization value without its scheme, or
+/// the API key header or query param
+pub fn add_credentials(secrets: &mut Vec<String>, request: &HttpRequest, auth: &RequestAuth) {
+    let (name, location) = match auth {
+        RequestAuth::Inherit | RequestAuth::None => return,
+        RequestAuth::Basic { .. } | RequestAuth::Bearer { .. } => {
+            ("Authorization", ApiKeyLocation::Header)
+        }
+        RequestAuth::ApiKey { key, location, .. } => (key.as_str(), location.clone()),
+    };
+    let values: Vec<&str> = match location {
+        ApiKeyLocation::Header => request
+            .headers
+            .iter()
+            .filter(|h| h.enabled && h.key.eq_ignore_ascii_case(name))
+            .map(|h| h.value.as_str())
+            .collect(),
+        ApiKeyLocation::Query => request
+            .params
+            .iter()
+            .filter(|p| p.enabled && p.key == name)
+            .map(|p| p.value.as_str())
+            .collect(),
+    };
+    for value in values {
+        let credential = match auth {
+            RequestAuth::ApiKey { .. } => value,
+            _ => value.split_once(' ').map_or(value, |(_, credential)| credential),
+        };
+        if !credential.trim().is_empty() {
+            secrets.push(credential.trim().to_string());
+        }
+    }
+    sort_secrets(secrets);
+}
+
+fn sort_secrets(secrets: &mut Vec<String>) {
+    secrets.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    secrets.dedup();
+}
+
+/// Replace every occurrence of a secret value with a fixed mask, including the
+/// base64 and URL-encoded forms it takes in headers and URLs
+pub fn mask_secrets(text: &str, secrets: &[String]) -> String {
+    let mut masked = text.to_string();
+    for secret in secrets {
+        let encoded = [
+            base64::engine::general_purpose::STANDARD.encode(secret),
+            urlencoding::encode(secret).into_owned(),
+        ];
+        for form in std::iter::once(secret).chain(&encoded) {
+            if masked.contains(form.as_str()) {
+                masked = masked.replace(form.as_str(), SECRET_MASK);
+            }
+        }
+    }
+    masked
+}
+
+/// Interpolate every user-editable part of an outgoing request. JSON bodies are
+/// interpolated with `interpolate_json`, using `types` for typed variables.
+pub fn interpolate_request(
+    request: &mut HttpRequest,
+    variables: &HashMap<String, String>,
+    types: &HashMap<String, VariableType>,
+) {
+    request.url = interpolate(&request.url, variables);
+    request.body = if request.body_type == "json" {
+        interpolate_json(&request.body, variables, types)
+    } else {
+        interpolate(&request.body, variables)
+    };
+    for header in &mut request.headers {
+        header.key = interpolate(&header.key, variables);
+        header.value = interpolate(&header.value, variables);
+    }
+    for param in &mut request.params {
+        param.key = interpolate(&param.key, variables);
+        param.value = interpolate(&param.value, variables);
+    }
+    for item in &mut request.form_data {
+        item.key = interpolate(&item.key, variables);
+        item.value = interpolate(&item.value, variables);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_interpolate_functions_and_dynamic_variables() {
+        let variables = HashMap::from([
+            ("user".to_string(), "ada".to_string()),
+            ("secret".to_string(), "key".to_string()),
+            ("api-host".to_string(), "api.example.com".to_string()),
+        ]);
+
+        assert_eq!(interpolate("{{ api-host }}/{{user}}", &variables), "api.example.com/ada");
+        assert_eq!(interpolate("{{base64(user)}}", &variables), "YWRh");
+        assert_eq!(
+            interpolate("{{ upper(base64Decode(\"aGk=\")) }}", &variables),
+            "HI"
+        );
+        assert_eq!(interpolate("q={{urlencode('a b&c')}}", &variables), "q=a%20b%26c");
+        assert_eq!(
+            interpolate("{{sha256(\"abc\")}}", &variables),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            interpolate(
+                "{{hmacSha256(secret, 'The quick brown fox jumps over the lazy dog')}}",
+                &variables
+            ),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+
+        let uuid = interpolate("{{$uuid}}", &variables);
+        assert!(Uuid::parse_str(&uuid).is_ok());
+        let n: i64 = interpolate("{{randomInt(5, 7)}}", &variables).parse().unwrap();
+        assert!((5..=7).contains(&n));
+        assert_eq!(interpolate("{{date('%Y')}}", &variables).len(), 4);
+        assert_eq!(interpolate("{{date('%Y', -86400)}}", &variables).len(), 4);
+        let far = format!("{{{{date('%Y', {})}}}}", i64::MAX);
+        assert_eq!(interpolate(&far, &variables), far);
+
+        // Unknown names, unknown functions and bad arguments stay as written
+        assert_eq!(interpolate("{{missing}}", &variables), "{{missing}}");
+        assert_eq!(interpolate("{{nope(user)}}", &variables), "{{nope(user)}}");
+        assert_eq!(interpolate("{{base64(user, user)}}", &variables), "{{base64(user, user)}}");
+        assert_eq!(interpolate("{{date('%Q')}}", &variables), "{{date('%Q')}}");
+    }
+
+    #[test]
+    fn test_interpolate_json_is_type_aware() {
+        let variables = HashMap::from([
+            ("name".to_string(), "Ada \"the\" first".to_string()),
+            ("tags".to_string(), r#"["a", "b"]"#.to_string()),
+            ("count".to_string(), "3".to_string()),
+        ]);
+        let types = HashMap::from([
+            ("tags".to_string(), VariableType::Json),
+            ("count".to_string(), VariableType::Number),
+        ]);
+
+        let body = interpolate_json(
+            r#"{"name": "{{name}}", "title": "Dr {{name}}", "tags": "{{tags}}", "count": {{count}}, "n": "{{ count }}", "raw": "{{missing}}"}"#,
+            &variables,
+            &types,
+        );
+        let parsed: serde_json::Value = serde_json::from_str(&body).expect("Invalid JSON");
+        assert_eq!(parsed["name"], "Ada \"the\" first");
+        assert_eq!(parsed["title"], "Dr Ada \"the\" first");
+        assert_eq!(parsed["tags"], serde_json::json!(["a", "b"]));
+        assert_eq!(parsed["count"], 3);
+        assert_eq!(parsed["n"], 3);
+        assert_eq!(parsed["raw"], "{{missing}}");
+
+        assert!(VariableType::Json.validate("{").is_err());
+        assert!(VariableType::Boolean.validate("yes").is_err());
+        assert!(VariableType::Number.validate(" 4.5 ").is_ok());
+        for invalid in ["+3", ".5", "5.", "NaN", "inf"] {
+            assert!(VariableType::Number.validate(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_mask_secrets_covers_encoded_forms() {
+        let secrets = vec!["p@ss word".to_string()];
+        let text = "raw=p@ss word url=p%40ss%20word b64=cEBzcyB3b3Jk";
+        assert_eq!(
+            mask_secrets(text, &secrets),
+            format!("raw={0} url={0} b64={0}", SECRET_MASK)
+        );
+    }
+}
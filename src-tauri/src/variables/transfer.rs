@@ -0,0 +1,219 @@
+//! `.env`, JSON and Postman environment files for bulk import and export of a
+//! scope's variables
+//!
+//! All formats go through `VariableEntry`. A `.env` file only carries names and
+//! values, so its entries leave every other field unset and an import keeps
+//! what the scope already has for them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::db::{DbError, DbResult};
+
+use super::sources;
+use super::types::{VariableSource, VariableType};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableEntry {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<VariableType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<VariableSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_secret: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl VariableEntry {
+    fn named(name: String, value: String) -> Self {
+        Self {
+            name,
+            value,
+            value_type: None,
+            source: None,
+            is_secret: None,
+            description: None,
+        }
+    }
+}
+
+pub fn from_dotenv(content: &str) -> Vec<VariableEntry> {
+    dedup(
+        sources::parse_dotenv(content)
+            .into_iter()
+            .map(|(name, value)| VariableEntry::named(name, value))
+            .collect(),
+    )
+}
+
+/// Entries of a JSON array of variables, or of an object of name -> value where
+/// numbers, booleans and nested documents keep their type
+pub fn from_json(content: &str) -> DbResult<Vec<VariableEntry>> {
+    let document: Value = serde_json::from_str(content)
+        .map_err(|e| DbError::InvalidInput(format!("Invalid JSON: {}", e)))?;
+
+    let entries = match document {
+        Value::Array(_) => serde_json::from_value::<Vec<VariableEntry>>(document)
+            .map_err(|e| DbError::InvalidInput(format!("Invalid variable list: {}", e)))?,
+        Value::Object(map) => map
+            .into_iter()
+            .map(|(name, value)| {
+                let (value, value_type) = match value {
+                    Value::String(s) => (s, None),
+                    Value::Number(n) => (n.to_string(), Some(VariableType::Number)),
+                    Value::Bool(b) => (b.to_string(), Some(VariableType::Boolean)),
+                    Value::Null => (String::new(), None),
+                    other => (other.to_string(), Some(VariableType::Json)),
+                };
+                VariableEntry {
+                    value_type,
+                    ..VariableEntry::named(name, value)
+                }
+            })
+            .collect(),
+        _ => {
+            return Err(DbError::InvalidInput(
+                "Expected a JSON array of variables or an object of values".to_string(),
+            ))
+        }
+    };
+
+    if let Some(entry) = entries.iter().find(|e| e.name.trim().is_empty()) {
+        return Err(DbError::InvalidInput(format!(
+            "Variable with value {:?} has no name",
+            entry.value
+        )));
+    }
+    Ok(dedup(entries))
+}
+
+/// Entries of a Postman environment file; disabled values are left out
+pub fn from_postman_environment(content: &str) -> DbResult<Vec<VariableEntry>> {
+    #[derive(Deserialize)]
+    struct Environment {
+        values: Vec<PostmanValue>,
+    }
+    #[derive(Deserialize)]
+    struct PostmanValue {
+        key: String,
+        #[serde(default)]
+        value: Value,
+        #[serde(default, rename = "type")]
+        kind: Option<String>,
+        #[serde(default = "enabled")]
+        enabled: bool,
+    }
+    fn enabled() -> bool {
+        true
+    }
+
+    let environment: Environment = serde_json::from_str(content)
+        .map_err(|e| DbError::InvalidInput(format!("Not a Postman environment: {}", e)))?;
+    let entries = environment
+        .values
+        .into_iter()
+        .filter(|v| v.enabled && !v.key.trim().is_empty())
+        .map(|v| {
+            let value = match v.value {
+                Value::String(s) => s,
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            VariableEntry {
+                is_secret: Some(v.kind.as_deref() == Some("secret")),
+                ..VariableEntry::named(v.key, value)
+            }
+        })
+        .collect();
+    Ok(dedup(entries))
+}
+
+pub fn to_dotenv(entries: &[VariableEntry]) -> DbResult<String> {
+    let mut content = String::new();
+    for entry in entries {
+        let valid_key = !entry.name.is_empty()
+            && entry
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c));
+        if !valid_key {
+            return Err(DbError::InvalidInput(format!(
+                "{} cannot be written as a .env key",
+                entry.name
+            )));
+        }
+        content.push_str(&entry.name);
+        content.push('=');
+        content.push_str(&dotenv_value(&entry.value));
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+pub fn to_json(entries: &[VariableEntry]) -> DbResult<String> {
+    serde_json::to_string_pretty(entries).map_err(|e| DbError::Serialization(e.to_string()))
+}
+
+/// A Postman environment named `name`. Values are written as text, since
+/// Postman has no typed variables.
+pub fn to_postman_environment(name: &str, entries: &[VariableEntry]) -> DbResult<String> {
+    let values: Vec<Value> = entries
+        .iter()
+        .map(|entry| {
+            let secret = entry.is_secret.unwrap_or(false);
+            serde_json::json!({
+                "key": entry.name,
+                "value": entry.value,
+                "type": if secret { "secret" } else { "default" },
+                "enabled": true,
+            })
+        })
+        .collect();
+    let environment = serde_json::json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "name": name,
+        "values": values,
+        "_postman_variable_scope": "environment",
+    });
+    serde_json::to_string_pretty(&environment).map_err(|e| DbError::Serialization(e.to_string()))
+}
+
+/// Value as written after `=`, double-quoted unless it reads back unchanged bare
+fn dotenv_value(value: &str) -> String {
+    let bare = value
+        .chars()
+        .all(|c| !c.is_whitespace() && !"\"'#\\".contains(c));
+    if bare {
+        return value.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Keep the last entry of each name, in first-seen order
+fn dedup(entries: Vec<VariableEntry>) -> Vec<VariableEntry> {
+    let mut unique: Vec<VariableEntry> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match unique.iter_mut().find(|e| e.name == entry.name) {
+            Some(existing) => *existing = entry,
+            None => unique.push(entry),
+        }
+    }
+    unique
+}
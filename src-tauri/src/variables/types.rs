@@ -7,6 +7,8 @@ use uuid::Uuid;
 pub enum VariableScope {
     Global,
     Workspace,
+    /// A named environment (dev, staging, ...) of a workspace
+    Environment,
     Collection,
     Request,
 }
@@ -17,6 +19,87 @@ impl Default for VariableScope {
     }
 }
 
+impl VariableScope {
+    /// Key of the scope in `VARIABLES_BY_SCOPE`
+    pub fn key(&self, scope_id: Option<&str>) -> String {
+        let scope_id = scope_id.unwrap_or("");
+        match self {
+            Self::Global => "global".to_string(),
+            Self::Workspace => format!("workspace:{}", scope_id),
+            Self::Environment => format!("environment:{}", scope_id),
+            Self::Collection => format!("collection:{}", scope_id),
+            Self::Request => format!("request:{}", scope_id),
+        }
+    }
+}
+
+/// Kind of value a variable holds; checked on every write
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum VariableType {
+    #[default]
+    String,
+    Number,
+    Boolean,
+    /// Any JSON document, spliced unquoted into JSON bodies
+    Json,
+    Multiline,
+    FilePath,
+}
+
+impl VariableType {
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            Self::String | Self::Multiline => Ok(()),
+            // Spliced unquoted into JSON bodies, so it has to be a JSON number
+            Self::Number => serde_json::from_str::<serde_json::Number>(value.trim())
+                .map(|_| ())
+                .map_err(|_| format!("{} is not a number", value)),
+            Self::Boolean => match value.trim() {
+                "true" | "false" => Ok(()),
+                _ => Err(format!("{} is not true or false", value)),
+            },
+            Self::Json => serde_json::from_str::<serde_json::Value>(value)
+                .map(|_| ())
+                .map_err(|e| format!("Invalid JSON: {}", e)),
+            Self::FilePath => {
+                if value.trim().is_empty() || value.contains('\0') {
+                    Err("File path cannot be empty".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Whether the value is itself a JSON literal (spliced without quotes)
+    pub fn is_json_literal(&self) -> bool {
+        matches!(self, Self::Number | Self::Boolean | Self::Json)
+    }
+}
+
+/// Where a variable's value comes from. Anything but `Value` is read when the
+/// variable resolves, so the stored `value` stays empty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum VariableSource {
+    /// The value entered by the user
+    #[default]
+    Value,
+    /// A process environment variable
+    Env { name: String },
+    /// The whole contents of a file, without a trailing newline
+    File { path: String },
+    /// One key of a `.env` file
+    DotEnv { path: String, key: String },
+}
+
+impl VariableSource {
+    pub fn is_value(&self) -> bool {
+        matches!(self, Self::Value)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Variable {
     pub id: String,
@@ -24,7 +107,14 @@ pub struct Variable {
     pub value: String,
     pub scope: VariableScope,
     pub scope_id: Option<String>,
+    #[serde(default)]
+    pub value_type: VariableType,
+    #[serde(default)]
+    pub source: VariableSource,
     pub is_secret: bool,
+    /// Sealed value of a secret once the vault is set up; `value` is then empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_value: Option<String>,
     pub description: Option<String>,
     #[serde(default = "Utc::now")]
     pub created_at: DateTime<Utc>,
@@ -41,7 +131,10 @@ impl Variable {
             value,
             scope,
             scope_id,
+            value_type: VariableType::default(),
+            source: VariableSource::default(),
             is_secret: false,
+            encrypted_value: None,
             description: None,
             created_at: now,
             updated_at: now,
@@ -49,22 +142,21 @@ impl Variable {
     }
 
     pub fn scope_key(&self) -> String {
-        match self.scope {
-            VariableScope::Global => "global".to_string(),
-            VariableScope::Workspace => format!("workspace:{}", self.scope_id.as_deref().unwrap_or("")),
-            VariableScope::Collection => format!("collection:{}", self.scope_id.as_deref().unwrap_or("")),
-            VariableScope::Request => format!("request:{}", self.scope_id.as_deref().unwrap_or("")),
-        }
+        self.scope.key(self.scope_id.as_deref())
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateVariableInput {
     pub name: String,
     pub value: String,
     pub scope: VariableScope,
     pub scope_id: Option<String>,
     #[serde(default)]
+    pub value_type: VariableType,
+    #[serde(default)]
+    pub source: VariableSource,
+    #[serde(default)]
     pub is_secret: bool,
     pub description: Option<String>,
 }
@@ -74,6 +166,9 @@ pub struct UpdateVariableInput {
     pub id: String,
     pub name: Option<String>,
     pub value: Option<String>,
+    pub value_type: Option<VariableType>,
+    #[serde(default)]
+    pub source: Option<VariableSource>,
     pub is_secret: Option<bool>,
     pub description: Option<String>,
 }
@@ -82,6 +177,188 @@ pub struct UpdateVariableInput {
 pub struct ResolvedVariable {
     pub name: String,
     pub value: String,
+    #[serde(default)]
+    pub value_type: VariableType,
     pub scope: VariableScope,
     pub is_secret: bool,
 }
+
+/// A variable left out of a resolved set because its value can't be read right
+/// now, such as a secret while the vault is locked
+#[derive(Debug, Clone)]
+pub struct UnavailableVariable {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Synced variables whose workspaces in a sync group disagree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub group_id: String,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub name: String,
+    /// One entry per workspace in the group, including those missing the variable
+    pub entries: Vec<SyncEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncEntry {
+    pub workspace_id: String,
+    pub variable_id: Option<String>,
+    /// Masked for secrets; `None` when the workspace has no such variable
+    pub value: Option<String>,
+    pub is_secret: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// How to settle a conflict for one synced name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SyncResolution {
+    /// Copy the variable as it is in this workspace to every other workspace,
+    /// deleting it everywhere if the workspace does not have it
+    UseWorkspace { workspace_id: String },
+    /// Set this value in every workspace
+    Merge { value: String },
+    /// Stop syncing the name, leaving each workspace's value as it is
+    Unlink,
+}
+
+/// File formats variables are imported from and exported to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum VariableFileFormat {
+    /// `KEY=value` lines
+    DotEnv,
+    /// An array of variables, or an object of name -> value on import
+    Json,
+    /// A Postman environment file
+    PostmanEnvironment,
+}
+
+/// What an import does with the variables already in the target scope
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStrategy {
+    /// Make the scope match the file: imported names are created or updated,
+    /// every other variable of the scope is deleted unless it is synced to
+    /// other workspaces, in which case it is skipped
+    Overwrite,
+    /// Only create names the scope does not have yet
+    Skip,
+    /// Create new names and update existing ones, keeping the rest
+    Merge,
+}
+
+/// How secret variables are written on export
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretExport {
+    /// Leave secrets out of the file
+    #[default]
+    Omit,
+    /// Keep the names with an empty value
+    Mask,
+    /// Write secrets in clear
+    Include,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportVariablesInput {
+    pub path: String,
+    pub format: VariableFileFormat,
+    pub scope: VariableScope,
+    pub scope_id: Option<String>,
+    pub strategy: ImportStrategy,
+}
+
+/// Names touched by an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportVariablesResult {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub skipped: Vec<String>,
+    pub deleted: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportVariablesInput {
+    pub format: VariableFileFormat,
+    pub scope: VariableScope,
+    pub scope_id: Option<String>,
+    #[serde(default)]
+    pub secrets: SecretExport,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageOwner {
+    Request,
+    /// The collection's defaults
+    Collection,
+}
+
+/// References to one variable in a request or collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableUsage {
+    pub owner: UsageOwner,
+    pub owner_id: String,
+    pub owner_name: String,
+    pub collection_id: Option<String>,
+    /// Fields holding references, e.g. `url`, `headers`, `body`
+    pub fields: Vec<String>,
+    pub count: usize,
+}
+
+/// Diagnostics for the variables of one workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VariableLint {
+    /// References that resolve to nothing where they are used
+    pub unresolved: Vec<UnresolvedReference>,
+    /// Variables of the workspace, its environments, collections and requests
+    /// that nothing references
+    pub unused: Vec<VariableRef>,
+    /// Variables hiding a variable of the same name in a wider scope
+    pub shadowed: Vec<ShadowedVariable>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedReference {
+    pub name: String,
+    pub owner: UsageOwner,
+    pub owner_id: String,
+    pub owner_name: String,
+    pub collection_id: Option<String>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableRef {
+    pub variable_id: String,
+    pub name: String,
+    pub scope: VariableScope,
+    pub scope_id: Option<String>,
+}
+
+impl From<&Variable> for VariableRef {
+    fn from(variable: &Variable) -> Self {
+        Self {
+            variable_id: variable.id.clone(),
+            name: variable.name.clone(),
+            scope: variable.scope.clone(),
+            scope_id: variable.scope_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowedVariable {
+    /// The variable that wins
+    pub variable: VariableRef,
+    /// Same-named variables of wider scopes it hides, widest first
+    pub hidden: Vec<VariableRef>,
+}
@@ -0,0 +1,353 @@
+//! Finding, listing and renaming `{{name}}` references
+//!
+//! A reference is the name on its own (`{{ token }}`) or as a function argument
+//! (`{{hmacSha256(secret, body)}}`); quoted strings and function names are not
+//! references. Only fields that are interpolated on send are looked at: the
+//! URL, headers, params, body, form data and auth of requests, and the defaults
+//! of collections. Extractions name the variable they write, so they count too.
+
+use std::ops::Range;
+
+use crate::collections::Collection;
+use crate::http::HttpRequest;
+use crate::requests::{Request, RequestAuth};
+
+use super::template::is_identifier_char;
+use super::types::{UsageOwner, VariableUsage};
+
+/// Where `name` is used in a request, if anywhere
+pub fn in_request(request: &Request, name: &str) -> Option<VariableUsage> {
+    let mut request = request.clone();
+    let (fields, count) = visit_request(&mut request, name, None);
+    (count > 0).then(|| VariableUsage {
+        owner: UsageOwner::Request,
+        owner_id: request.id.clone(),
+        owner_name: request.name.clone(),
+        collection_id: request.collection_id.clone(),
+        fields,
+        count,
+    })
+}
+
+/// Where `name` is used in a collection's defaults, if anywhere
+pub fn in_collection(collection: &Collection, name: &str) -> Option<VariableUsage> {
+    let mut collection = collection.clone();
+    let (fields, count) = visit_collection(&mut collection, name, None);
+    (count > 0).then(|| VariableUsage {
+        owner: UsageOwner::Collection,
+        owner_id: collection.id.clone(),
+        owner_name: collection.name.clone(),
+        collection_id: Some(collection.id.clone()),
+        fields,
+        count,
+    })
+}
+
+/// Rename references in place; returns how many were renamed
+pub fn rename_in_request(request: &mut Request, from: &str, to: &str) -> usize {
+    visit_request(request, from, Some(to)).1
+}
+
+pub fn rename_in_collection(collection: &mut Collection, from: &str, to: &str) -> usize {
+    visit_collection(collection, from, Some(to)).1
+}
+
+fn visit_request(
+    request: &mut Request,
+    name: &str,
+    replacement: Option<&str>,
+) -> (Vec<String>, usize) {
+    let mut fields = Vec::new();
+    let mut total = 0;
+    request_fields(request, |field, text| {
+        let (renamed, count) = rewrite(text, name, replacement);
+        if count > 0 {
+            *text = renamed;
+            total += count;
+            add_field(&mut fields, field);
+        }
+    });
+
+    for extraction in &mut request.extractions {
+        if extraction.variable == name {
+            if let Some(to) = replacement {
+                extraction.variable = to.to_string();
+            }
+            total += 1;
+            add_field(&mut fields, "extractions");
+        }
+    }
+
+    (fields, total)
+}
+
+fn visit_collection(
+    collection: &mut Collection,
+    name: &str,
+    replacement: Option<&str>,
+) -> (Vec<String>, usize) {
+    let mut fields = Vec::new();
+    let mut total = 0;
+    collection_fields(collection, |field, text| {
+        let (renamed, count) = rewrite(text, name, replacement);
+        if count > 0 {
+            *text = renamed;
+            total += count;
+            add_field(&mut fields, field);
+        }
+    });
+    (fields, total)
+}
+
+/// Names referenced by a request, each with the fields it appears in.
+/// Extraction targets are not included: they write variables rather than read them.
+pub fn request_references(request: &Request) -> Vec<(String, Vec<String>)> {
+    let mut references = Vec::new();
+    request_fields(&mut request.clone(), |field, text| {
+        collect_names(&mut references, field, text)
+    });
+    references
+}
+
+pub fn collection_references(collection: &Collection) -> Vec<(String, Vec<String>)> {
+    let mut references = Vec::new();
+    collection_fields(&mut collection.clone(), |field, text| {
+        collect_names(&mut references, field, text)
+    });
+    references
+}
+
+/// Names an outgoing request still references, such as those interpolation
+/// could not resolve
+pub fn http_request_references(request: &HttpRequest) -> Vec<String> {
+    let texts = [&request.url, &request.body]
+        .into_iter()
+        .chain(
+            request
+                .headers
+                .iter()
+                .filter(|h| h.enabled)
+                .flat_map(|h| [&h.key, &h.value]),
+        )
+        .chain(
+            request
+                .params
+                .iter()
+                .filter(|p| p.enabled)
+                .flat_map(|p| [&p.key, &p.value]),
+        )
+        .chain(
+            request
+                .form_data
+                .iter()
+                .filter(|f| f.enabled)
+                .flat_map(|f| [&f.key, &f.value]),
+        );
+
+    let mut references = Vec::new();
+    for text in texts {
+        for name in names(text) {
+            if !references.contains(&name) {
+                references.push(name);
+            }
+        }
+    }
+    references
+}
+
+fn collect_names(references: &mut Vec<(String, Vec<String>)>, field: &str, text: &str) {
+    for name in names(text) {
+        match references.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, fields)) => add_field(fields, field),
+            None => references.push((name, vec![field.to_string()])),
+        }
+    }
+}
+
+fn add_field(fields: &mut Vec<String>, field: &str) {
+    if !fields.iter().any(|f| f == field) {
+        fields.push(field.to_string());
+    }
+}
+
+/// Every interpolated field of a request, named for reports
+fn request_fields(request: &mut Request, mut visit: impl FnMut(&str, &mut String)) {
+    visit("url", &mut request.url);
+    for header in &mut request.headers {
+        visit("headers", &mut header.key);
+        visit("headers", &mut header.value);
+    }
+    for param in &mut request.params {
+        visit("params", &mut param.key);
+        visit("params", &mut param.value);
+    }
+    visit("body", &mut request.body);
+    for item in &mut request.form_data {
+        visit("form_data", &mut item.key);
+        visit("form_data", &mut item.value);
+    }
+    for text in auth_fields(&mut request.auth) {
+        visit("auth", text);
+    }
+}
+
+fn collection_fields(collection: &mut Collection, mut visit: impl FnMut(&str, &mut String)) {
+    let defaults = &mut collection.defaults;
+    visit("base_url", &mut defaults.base_url);
+    for header in &mut defaults.headers {
+        visit("headers", &mut header.key);
+        visit("headers", &mut header.value);
+    }
+    for param in &mut defaults.params {
+        visit("params", &mut param.key);
+        visit("params", &mut param.value);
+    }
+    for text in auth_fields(&mut defaults.auth) {
+        visit("auth", text);
+    }
+}
+
+fn auth_fields(auth: &mut RequestAuth) -> Vec<&mut String> {
+    match auth {
+        RequestAuth::Inherit | RequestAuth::None => Vec::new(),
+        RequestAuth::Basic { username, password } => vec![username, password],
+        RequestAuth::Bearer { token } => vec![token],
+        RequestAuth::ApiKey { key, value, .. } => vec![key, value],
+    }
+}
+
+/// Walk the `{{...}}` expressions of `text` like `template::interpolate` does,
+/// renaming references to `name` when a replacement is given
+fn rewrite(text: &str, name: &str, replacement: Option<&str>) -> (String, usize) {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut count = 0;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len;
+        let expression = &rest[start + 2..end];
+
+        result.push_str(&rest[..start + 2]);
+        let mut copied = 0;
+        for range in references(expression, name) {
+            result.push_str(&expression[copied..range.start]);
+            result.push_str(replacement.unwrap_or(name));
+            copied = range.end;
+            count += 1;
+        }
+        result.push_str(&expression[copied..]);
+        result.push_str("}}");
+        rest = &rest[end + 2..];
+    }
+
+    result.push_str(rest);
+    (result, count)
+}
+
+/// Every `{{...}}` expression of `text`, without the braces
+fn expressions(text: &str) -> Vec<&str> {
+    let mut expressions = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        expressions.push(&rest[start + 2..start + 2 + len]);
+        rest = &rest[start + 4 + len..];
+    }
+    expressions
+}
+
+/// Names referenced in `text`: whole expressions that are not calls or literals,
+/// such as `{{my var}}`, and the variables used inside expressions
+fn names(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for expression in expressions(text) {
+        let identifiers = identifiers(expression);
+        let trimmed = expression.trim();
+        if identifiers.len() > 1 && !trimmed.contains(['(', ',', '"', '\'']) {
+            names.push(trimmed.to_string());
+            continue;
+        }
+        for range in identifiers {
+            names.push(expression[range].to_string());
+        }
+    }
+    names
+}
+
+/// Byte ranges of references to `name` inside one expression
+fn references(expression: &str, name: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+
+    // Names that are not identifiers can only be used on their own
+    if expression.trim() == name {
+        let start = expression.len() - expression.trim_start().len();
+        ranges.push(start..start + name.len());
+        return ranges;
+    }
+
+    ranges.extend(identifiers(expression).into_iter().filter(|r| &expression[r.clone()] == name));
+    ranges
+}
+
+/// Byte ranges of the identifiers in an expression that name variables rather
+/// than functions; quoted strings and numbers are skipped
+fn identifiers(expression: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                // Skip the string, honouring backslash escapes
+                let quote = c;
+                while let Some((_, c)) = chars.next() {
+                    if c == '\\' {
+                        chars.next();
+                    } else if c == quote {
+                        break;
+                    }
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                while chars
+                    .next_if(|(_, c)| c.is_ascii_digit() || matches!(c, '-' | '.'))
+                    .is_some()
+                {}
+            }
+            c if is_identifier_char(c) => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|(_, c)| is_identifier_char(*c)) {
+                    end = i + c.len_utf8();
+                }
+                if !expression[end..].trim_start().starts_with('(') {
+                    ranges.push(start..end);
+                }
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_references() {
+        let text = "{{base_url}}/users?sig={{ hmacSha256(base_url, 'base_url') }}&{{base_url_v2}}";
+        assert_eq!(rewrite(text, "hmacSha256", None).1, 0);
+
+        let (renamed, count) = rewrite(text, "base_url", Some("api_url"));
+        assert_eq!(count, 2);
+        assert_eq!(
+            renamed,
+            "{{api_url}}/users?sig={{ hmacSha256(api_url, 'base_url') }}&{{base_url_v2}}"
+        );
+        assert_eq!(rewrite("{{ my var }}", "my var", Some("other")).0, "{{ other }}");
+    }
+}
@@ -0,0 +1,37 @@
+use tauri::State;
+
+use crate::db::Database;
+
+use super::service::VaultService;
+use super::types::{VaultCredentials, VaultStatus};
+
+#[tauri::command]
+pub fn get_vault_status(db: State<Database>) -> Result<VaultStatus, String> {
+    let service = VaultService::new(db.inner().clone());
+    service.status().map_err(|e| e.to_string())
+}
+
+/// Create the vault and encrypt every existing secret variable with it
+#[tauri::command]
+pub fn setup_vault(
+    db: State<Database>,
+    credentials: VaultCredentials,
+) -> Result<VaultStatus, String> {
+    let service = VaultService::new(db.inner().clone());
+    service.setup(credentials).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn unlock_vault(
+    db: State<Database>,
+    credentials: VaultCredentials,
+) -> Result<VaultStatus, String> {
+    let service = VaultService::new(db.inner().clone());
+    service.unlock(credentials).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn lock_vault(db: State<Database>) -> Result<VaultStatus, String> {
+    let service = VaultService::new(db.inner().clone());
+    service.lock().map_err(|e| e.to_string())
+}
@@ -0,0 +1,63 @@
+//! AES-256-GCM with Argon2id key derivation
+//!
+//! Sealed values are base64 of a random 96-bit nonce followed by the ciphertext.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rand::RngCore;
+
+use crate::db::{DbError, DbResult};
+
+pub type VaultKey = [u8; 32];
+
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+
+pub fn generate_salt() -> Vec<u8> {
+    let mut salt = vec![0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+pub fn derive_key(material: &[u8], salt: &[u8]) -> DbResult<VaultKey> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(material, salt, &mut key)
+        .map_err(|e| DbError::Vault(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+pub fn seal(key: &VaultKey, plaintext: &str) -> DbResult<String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| DbError::Vault("Encryption failed".to_string()))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(STANDARD.encode(sealed))
+}
+
+pub fn open(key: &VaultKey, sealed: &str) -> DbResult<String> {
+    let bytes = STANDARD
+        .decode(sealed)
+        .map_err(|_| DbError::Vault("Sealed value is not valid base64".to_string()))?;
+    if bytes.len() < NONCE_LEN {
+        return Err(DbError::Vault("Sealed value is truncated".to_string()));
+    }
+
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| {
+            DbError::Vault("Decryption failed; wrong key or corrupted value".to_string())
+        })?;
+
+    String::from_utf8(plaintext)
+        .map_err(|_| DbError::Vault("Decrypted value is not UTF-8".to_string()))
+}
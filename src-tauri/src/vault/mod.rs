@@ -0,0 +1,14 @@
+//! Vault module
+//!
+//! Encrypts secret variables at rest. The key is derived from a master password
+//! or a key file, held in memory while the vault is unlocked, and never stored.
+
+mod commands;
+pub mod crypto;
+mod service;
+mod types;
+
+pub use commands::*;
+#[allow(unused_imports)]
+pub use types::{VaultConfig, VaultCredentials, VaultMethod, VaultStatus};
+pub(crate) use service::VaultService;
@@ -0,0 +1,250 @@
+use std::fs;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use redb::ReadableTable;
+
+use crate::db::helpers::{get_row, put_row};
+use crate::db::{Database, DbError, DbResult, VARIABLES, VAULT};
+use crate::variables::Variable;
+
+use super::crypto::{self, VaultKey};
+use super::types::{VaultConfig, VaultCredentials, VaultStatus};
+
+const CONFIG_KEY: &str = "config";
+/// Plaintext sealed into `VaultConfig::check`
+const CHECK_VALUE: &str = "resona-vault";
+
+pub struct VaultService {
+    db: Database,
+}
+
+impl VaultService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    fn config(&self) -> DbResult<Option<VaultConfig>> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(VAULT)?;
+
+        let config = match table.get(CONFIG_KEY)? {
+            Some(value) => Some(
+                serde_json::from_str(value.value())
+                    .map_err(|e| DbError::Serialization(e.to_string()))?,
+            ),
+            None => None,
+        };
+        Ok(config)
+    }
+
+    pub fn status(&self) -> DbResult<VaultStatus> {
+        let config = self.config()?;
+        Ok(VaultStatus {
+            configured: config.is_some(),
+            method: config.map(|c| c.method),
+            unlocked: self.db.vault_key().is_some(),
+        })
+    }
+
+    /// Key for sealing secrets: `None` while no vault is set up (secrets are then
+    /// stored as entered), an error while the vault is locked
+    pub fn key(&self) -> DbResult<Option<VaultKey>> {
+        if let Some(key) = self.db.vault_key() {
+            return Ok(Some(key));
+        }
+        match self.config()? {
+            Some(_) => Err(DbError::Vault(
+                "Vault is locked; unlock it to use secret variables".to_string(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Create the vault, then encrypt every secret variable stored in clear in
+    /// the same transaction
+    pub fn setup(&self, credentials: VaultCredentials) -> DbResult<VaultStatus> {
+        if self.config()?.is_some() {
+            return Err(DbError::Vault("Vault is already set up".to_string()));
+        }
+
+        let salt = crypto::generate_salt();
+        let key = crypto::derive_key(&key_material(&credentials)?, &salt)?;
+        let config = VaultConfig {
+            method: credentials.method(),
+            salt: STANDARD.encode(&salt),
+            check: crypto::seal(&key, CHECK_VALUE)?,
+            created_at: Utc::now(),
+        };
+
+        let write_txn = self.db.begin_write()?;
+        put_row(&write_txn, VAULT, CONFIG_KEY, &config)?;
+
+        let secret_ids: Vec<String> = {
+            let table = write_txn.open_table(VARIABLES)?;
+            let mut ids = Vec::new();
+            for entry in table.iter()? {
+                let (id, value) = entry?;
+                let variable: Variable = serde_json::from_str(value.value())
+                    .map_err(|e| DbError::Serialization(e.to_string()))?;
+                if variable.is_secret && variable.encrypted_value.is_none() {
+                    ids.push(id.value().to_string());
+                }
+            }
+            ids
+        };
+        for id in secret_ids {
+            if let Some(mut variable) = get_row::<Variable>(&write_txn, VARIABLES, &id)? {
+                variable.encrypted_value = Some(crypto::seal(&key, &variable.value)?);
+                variable.value.clear();
+                put_row(&write_txn, VARIABLES, &id, &variable)?;
+            }
+        }
+
+        write_txn.commit()?;
+        self.db.set_vault_key(Some(key));
+
+        self.status()
+    }
+
+    pub fn unlock(&self, credentials: VaultCredentials) -> DbResult<VaultStatus> {
+        let config = self
+            .config()?
+            .ok_or_else(|| DbError::Vault("Vault is not set up".to_string()))?;
+        if credentials.method() != config.method {
+            return Err(DbError::Vault(
+                "Vault uses a different unlock method".to_string(),
+            ));
+        }
+
+        let salt = STANDARD
+            .decode(&config.salt)
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+        let key = crypto::derive_key(&key_material(&credentials)?, &salt)?;
+        if crypto::open(&key, &config.check).ok().as_deref() != Some(CHECK_VALUE) {
+            return Err(DbError::Vault("Wrong password or key file".to_string()));
+        }
+
+        self.db.set_vault_key(Some(key));
+        self.status()
+    }
+
+    pub fn lock(&self) -> DbResult<VaultStatus> {
+        self.db.set_vault_key(None);
+        self.status()
+    }
+}
+
+fn key_material(credentials: &VaultCredentials) -> DbResult<Vec<u8>> {
+    let material = match credentials {
+        VaultCredentials::Password { password } => password.as_bytes().to_vec(),
+        VaultCredentials::KeyFile { path } => fs::read(path)?,
+    };
+    if material.is_empty() {
+        return Err(DbError::InvalidInput(
+            "Password or key file cannot be empty".to_string(),
+        ));
+    }
+    Ok(material)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::Request;
+    use crate::variables::{
+        template, CreateVariableInput, VariableScope, VariableService, VariableType,
+    };
+    use std::env::temp_dir;
+
+    fn create_test_db() -> Database {
+        let path = temp_dir().join(format!("resona_test_{}.redb", uuid::Uuid::new_v4()));
+        Database::open_at(path).expect("Failed to create test database")
+    }
+
+    fn password(password: &str) -> VaultCredentials {
+        VaultCredentials::Password {
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_secrets_are_encrypted_at_rest() {
+        let db = create_test_db();
+        let variables = VariableService::new(db.clone());
+        let token = variables
+            .create(CreateVariableInput {
+                name: "token".to_string(),
+                value: "s3cr3t".to_string(),
+                scope: VariableScope::Global,
+                scope_id: None,
+                value_type: Default::default(),
+                source: Default::default(),
+                is_secret: true,
+                description: None,
+            })
+            .expect("Failed to create variable");
+        assert_eq!(token.value, "s3cr3t");
+
+        let vault = VaultService::new(db.clone());
+        vault.setup(password("correct horse")).expect("Failed to set up vault");
+
+        let stored = variables.get(&token.id).expect("Failed to get variable");
+        assert!(stored.value.is_empty());
+        let sealed = stored.encrypted_value.expect("Secret was not encrypted");
+        assert!(!sealed.contains("s3cr3t"));
+
+        // A locked vault only fails requests that use a secret
+        vault.lock().expect("Failed to lock");
+        let (resolved, unavailable) =
+            variables.resolve_context(None, None, None).expect("Failed to resolve");
+        assert!(resolved.is_empty());
+        assert_eq!(unavailable[0].name, "token");
+        let mut request = Request::new("Ping".to_string(), Default::default(), "ws".to_string())
+            .to_http_request();
+        request.url = "https://example.com/ping".to_string();
+        assert!(template::check_available(&request, &unavailable).is_ok());
+        request.url.push_str("?token={{token}}");
+        assert!(template::check_available(&request, &unavailable).is_err());
+        assert!(vault.unlock(password("wrong")).is_err());
+
+        vault.unlock(password("correct horse")).expect("Failed to unlock");
+        let resolved = variables.get_resolved(None, None, None).expect("Failed to resolve");
+        assert_eq!(resolved[0].value, "s3cr3t");
+    }
+
+    #[test]
+    fn test_copied_workspace_secrets_are_sealed_again() {
+        let db = create_test_db();
+        let variables = VariableService::new(db.clone());
+        VaultService::new(db.clone())
+            .setup(password("correct horse"))
+            .expect("Failed to set up vault");
+        for (name, value, value_type) in
+            [("token", "s3cr3t", VariableType::String), ("port", "8080", VariableType::Number)]
+        {
+            variables
+                .create(CreateVariableInput {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                    scope: VariableScope::Workspace,
+                    scope_id: Some("ws".to_string()),
+                    value_type,
+                    source: Default::default(),
+                    is_secret: true,
+                    description: None,
+                })
+                .expect("Failed to create variable");
+        }
+
+        let mut copies = variables.copy_workspace("ws", "copy", true).expect("Failed to copy");
+        copies.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(copies[0].value_type, VariableType::Number);
+        assert!(copies.iter().all(|c| c.value.is_empty() && c.encrypted_value.is_some()));
+        assert_eq!(variables.plaintext(&copies[1]).unwrap(), "s3cr3t");
+
+        let empty = variables.copy_workspace("ws", "other", false).expect("Failed to copy");
+        assert!(empty.iter().all(|c| variables.plaintext(c).unwrap().is_empty()));
+    }
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum VaultMethod {
+    Password,
+    KeyFile,
+}
+
+/// Stored vault settings; enough to check a key, not to recover one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultConfig {
+    pub method: VaultMethod,
+    /// Base64 Argon2id salt
+    pub salt: String,
+    /// A known value sealed with the key, used to reject wrong credentials
+    pub check: String,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// What the key is derived from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "kebab-case")]
+pub enum VaultCredentials {
+    Password { password: String },
+    /// The whole contents of the file are the key material
+    KeyFile { path: String },
+}
+
+impl VaultCredentials {
+    pub fn method(&self) -> VaultMethod {
+        match self {
+            Self::Password { .. } => VaultMethod::Password,
+            Self::KeyFile { .. } => VaultMethod::KeyFile,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultStatus {
+    pub configured: bool,
+    pub method: Option<VaultMethod>,
+    pub unlocked: bool,
+}
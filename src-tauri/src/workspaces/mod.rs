@@ -13,7 +13,7 @@ pub use commands::*;
 #[allow(unused_imports)]
 pub use types::{
     CreateSyncGroupInput, CreateWorkspaceInput, UpdateSyncGroupInput, UpdateWorkspaceInput,
-    Workspace, WorkspaceSyncGroup,
+    Workspace, WorkspaceSettings, WorkspaceSyncGroup,
 };
 
 // WorkspaceService is used internally by commands
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::http::{ClientOptions, ResolveOverride};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Workspace {
@@ -12,12 +14,26 @@ pub struct Workspace {
     pub tags: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sync_group_id: Option<String>,
+    /// Environment whose variables layer over the workspace's own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_environment_id: Option<String>,
+    #[serde(default)]
+    pub settings: WorkspaceSettings,
     #[serde(default = "Utc::now")]
     pub created_at: DateTime<Utc>,
     #[serde(default = "Utc::now")]
     pub updated_at: DateTime<Utc>,
 }
 
+/// Per-workspace settings applied to every request sent from the workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WorkspaceSettings {
+    /// host:port -> address mappings, used instead of DNS when building the client
+    #[serde(default)]
+    pub resolve_overrides: Vec<ResolveOverride>,
+}
+
 impl Workspace {
     pub fn new(name: String, description: String) -> Self {
         let now = Utc::now();
@@ -27,10 +43,19 @@ impl Workspace {
             description,
             tags: Vec::new(),
             sync_group_id: None,
+            active_environment_id: None,
+            settings: WorkspaceSettings::default(),
             created_at: now,
             updated_at: now,
         }
     }
+
+    /// HTTP client options derived from the workspace settings
+    pub fn client_options(&self) -> ClientOptions {
+        ClientOptions {
+            resolve_overrides: self.settings.resolve_overrides.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +73,7 @@ pub struct UpdateWorkspaceInput {
     pub description: Option<String>,
     pub tags: Option<Vec<String>>,
     pub sync_group_id: Option<String>,
+    pub settings: Option<WorkspaceSettings>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,7 +7,7 @@ use crate::db::{
 
 use super::types::{
     CreateSyncGroupInput, CreateWorkspaceInput, UpdateSyncGroupInput, UpdateWorkspaceInput,
-    Workspace, WorkspaceSyncGroup,
+    Workspace, WorkspaceSettings, WorkspaceSyncGroup,
 };
 
 pub struct WorkspaceService {
@@ -81,6 +81,10 @@ impl WorkspaceService {
         if let Some(sync_group_id) = input.sync_group_id {
             workspace.sync_group_id = Some(sync_group_id);
         }
+        if let Some(settings) = input.settings {
+            Self::validate_settings(&settings)?;
+            workspace.settings = settings;
+        }
         workspace.updated_at = Utc::now();
 
         // Write back
@@ -97,6 +101,24 @@ impl WorkspaceService {
         Ok(workspace)
     }
 
+    /// Reject settings that would only fail later when the client is built
+    fn validate_settings(settings: &WorkspaceSettings) -> DbResult<()> {
+        for entry in &settings.resolve_overrides {
+            if entry.host.trim().is_empty() {
+                return Err(DbError::InvalidInput(
+                    "Resolve override host cannot be empty".to_string(),
+                ));
+            }
+            if entry.address.parse::<std::net::IpAddr>().is_err() {
+                return Err(DbError::InvalidInput(format!(
+                    "Resolve override for {}:{} must map to an IP address, got {}",
+                    entry.host, entry.port, entry.address
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Delete a workspace
     pub fn delete(&self, id: &str) -> DbResult<()> {
         // First get the workspace to check sync_group_id
@@ -503,6 +525,7 @@ mod tests {
                 description: None,
                 tags: None,
                 sync_group_id: None,
+                settings: None,
             })
             .expect("Failed to update workspace");
 
@@ -519,6 +542,53 @@ mod tests {
         assert_eq!(all.len(), 0);
     }
 
+    #[test]
+    fn test_workspace_resolve_overrides() {
+        let db = create_test_db();
+        let service = WorkspaceService::new(db);
+
+        let workspace = service
+            .create(CreateWorkspaceInput {
+                name: "Cut-over".to_string(),
+                description: String::new(),
+                tags: Vec::new(),
+            })
+            .expect("Failed to create workspace");
+
+        let override_for = |address: &str| WorkspaceSettings {
+            resolve_overrides: vec![crate::http::ResolveOverride {
+                host: "api.example.com".to_string(),
+                port: 443,
+                address: address.to_string(),
+            }],
+        };
+
+        let invalid = service.update(UpdateWorkspaceInput {
+            id: workspace.id.clone(),
+            name: None,
+            description: None,
+            tags: None,
+            sync_group_id: None,
+            settings: Some(override_for("new-lb.internal")),
+        });
+        assert!(matches!(invalid, Err(DbError::InvalidInput(_))));
+
+        let updated = service
+            .update(UpdateWorkspaceInput {
+                id: workspace.id.clone(),
+                name: None,
+                description: None,
+                tags: None,
+                sync_group_id: None,
+                settings: Some(override_for("10.0.0.5")),
+            })
+            .expect("Failed to update workspace settings");
+
+        let options = updated.client_options();
+        assert_eq!(options.resolve_overrides.len(), 1);
+        assert_eq!(options.resolve_overrides[0].address, "10.0.0.5");
+    }
+
     #[test]
     fn test_sync_groups() {
         let db = create_test_db();
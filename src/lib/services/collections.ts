@@ -40,6 +40,7 @@ type RustRequest = {
     item_type: string;
     enabled: boolean;
   }[];
+  http_version: string;
   collection_id: string | null;
   workspace_id: string;
   created_at: string;
@@ -60,6 +61,7 @@ type CreateRequestInput = {
     item_type: string;
     enabled: boolean;
   }[];
+  http_version?: string;
   collection_id: string | null;
   workspace_id: string;
 };
@@ -79,6 +81,7 @@ type UpdateRequestInput = {
     item_type: string;
     enabled: boolean;
   }[];
+  http_version?: string;
   collection_id?: string | null;
 };
 
@@ -113,6 +116,7 @@ function toRequest(rust: RustRequest): Request {
       type: f.item_type as "text" | "file",
       enabled: f.enabled,
     })),
+    httpVersion: rust.http_version as Request["httpVersion"],
     collectionId: rust.collection_id,
     workspaceId: rust.workspace_id,
   };
@@ -233,6 +237,7 @@ export async function create_request(
       item_type: f.type,
       enabled: f.enabled,
     })),
+    http_version: request.httpVersion,
     collection_id: request.collectionId,
     workspace_id: request.workspaceId,
   };
@@ -260,6 +265,7 @@ export async function update_request(
         item_type: f.type,
         enabled: f.enabled,
       })),
+      http_version: updates.httpVersion,
       collection_id: updates.collectionId,
     };
     await invoke<RustRequest>("update_request", { input });
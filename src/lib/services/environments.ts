@@ -0,0 +1,45 @@
+import { invoke } from "@tauri-apps/api/core";
+import type {
+  Environment,
+  CreateEnvironmentInput,
+  UpdateEnvironmentInput,
+} from "$lib/types/environment";
+import type { Workspace } from "$lib/types/workspace";
+
+export async function get_environments_by_workspace(
+  workspaceId: string
+): Promise<Environment[]> {
+  return invoke<Environment[]>("get_environments_by_workspace", {
+    workspaceId,
+  });
+}
+
+export async function get_environment(id: string): Promise<Environment> {
+  return invoke<Environment>("get_environment", { id });
+}
+
+export async function create_environment(
+  input: CreateEnvironmentInput
+): Promise<Environment> {
+  return invoke<Environment>("create_environment", { input });
+}
+
+export async function update_environment(
+  input: UpdateEnvironmentInput
+): Promise<Environment> {
+  return invoke<Environment>("update_environment", { input });
+}
+
+export async function delete_environment(id: string): Promise<void> {
+  return invoke<void>("delete_environment", { id });
+}
+
+export async function set_active_environment(
+  workspaceId: string,
+  environmentId: string | null
+): Promise<Workspace> {
+  return invoke<Workspace>("set_active_environment", {
+    workspaceId,
+    environmentId,
+  });
+}
@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { Request } from "$lib/types/request";
+import type { HttpVersion, Request } from "$lib/types/request";
 import type { ResolvedVariable } from "$lib/types/variable";
 import { interpolate_variables } from "./variables";
 
@@ -7,6 +7,7 @@ export type HttpResponse = {
   status: number;
   statusText: string;
   headers: { key: string; value: string }[];
+  httpVersion: string;
   body: string;
   timeMs: number;
   sizeBytes: number;
@@ -25,12 +26,14 @@ type RustHttpRequest = {
     item_type: string;
     enabled: boolean;
   }[];
+  http_version: HttpVersion;
 };
 
 type RustHttpResponse = {
   status: number;
   status_text: string;
   headers: { key: string; value: string }[];
+  http_version: string;
   body: string;
   time_ms: number;
   size_bytes: number;
@@ -57,8 +60,12 @@ export async function send_request(
     enabled: p.enabled,
   }));
 
-  // Interpolate variables in body
-  const body = interpolate_variables(request.body, variables);
+  // Interpolate variables in body; JSON bodies are left to the backend, which
+  // escapes values and splices typed variables
+  const body =
+    request.bodyType === "json"
+      ? request.body
+      : interpolate_variables(request.body, variables);
 
   // Interpolate variables in form data
   const formData = request.formData.map((f) => ({
@@ -76,16 +83,20 @@ export async function send_request(
     body_type: request.bodyType,
     body,
     form_data: formData,
+    http_version: request.httpVersion ?? "auto",
   };
 
   const response = await invoke<RustHttpResponse>("send_http_request", {
     request: rustRequest,
+    workspaceId: request.workspaceId,
+    requestId: request.id,
   });
 
   return {
     status: response.status,
     statusText: response.status_text,
     headers: response.headers,
+    httpVersion: response.http_version,
     body: response.body,
     timeMs: response.time_ms,
     sizeBytes: response.size_bytes,
@@ -0,0 +1,64 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { ImportReport, UnsupportedItem } from "$lib/types/interop";
+import type { SecretExport } from "$lib/types/variable";
+
+type RustImportReport = {
+  workspace_id: string;
+  collection_ids: string[];
+  folders: number;
+  requests: number;
+  variables: number;
+  unsupported: UnsupportedItem[];
+};
+
+function toImportReport(rust: RustImportReport): ImportReport {
+  return {
+    workspaceId: rust.workspace_id,
+    collectionIds: rust.collection_ids,
+    folders: rust.folders,
+    requests: rust.requests,
+    variables: rust.variables,
+    unsupported: rust.unsupported,
+  };
+}
+
+/** Import a Postman v2.1 collection; a new workspace is created when `workspaceId` is null */
+export async function import_postman_collection(
+  path: string,
+  workspaceId: string | null = null
+): Promise<ImportReport> {
+  const report = await invoke<RustImportReport>("import_postman_collection", {
+    input: { path, workspace_id: workspaceId },
+  });
+  return toImportReport(report);
+}
+
+/** Import an OpenAPI 3.x or Swagger 2.0 document (JSON or YAML) as a collection */
+export async function import_openapi_spec(
+  path: string,
+  workspaceId: string | null = null
+): Promise<ImportReport> {
+  const report = await invoke<RustImportReport>("import_openapi_spec", {
+    input: { path, workspace_id: workspaceId },
+  });
+  return toImportReport(report);
+}
+
+/** A collection as Postman v2.1 JSON, with its collection variables */
+export async function export_postman_collection(
+  collectionId: string,
+  secrets: SecretExport = "omit"
+): Promise<string> {
+  return invoke<string>("export_postman_collection", { collectionId, secrets });
+}
+
+/**
+ * A whole workspace as one Postman v2.1 collection. Export workspace variables
+ * as a Postman environment with `export_variables(..., "postman-environment", ...)`.
+ */
+export async function export_postman_workspace(
+  workspaceId: string,
+  secrets: SecretExport = "omit"
+): Promise<string> {
+  return invoke<string>("export_postman_workspace", { workspaceId, secrets });
+}
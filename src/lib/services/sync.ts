@@ -1,7 +1,10 @@
+import { invoke } from "@tauri-apps/api/core";
 import type {
   Workspace,
   WorkspaceSyncGroup,
   CreateWorkspaceInput,
+  SyncResolution,
+  SyncStatus,
 } from "$lib/types/workspace";
 import {
   get_collections_by_workspace,
@@ -9,15 +12,8 @@ import {
   create_collection,
   create_request,
 } from "./collections";
-import {
-  get_workspace_variables,
-  create_variable,
-  update_variable,
-} from "./variables";
-import {
-  create_sync_group as createSyncGroupBackend,
-  get_sync_group as getSyncGroupBackend,
-} from "./workspaces";
+import { copy_workspace_variables } from "./variables";
+import { create_sync_group as createSyncGroupBackend } from "./workspaces";
 
 export type DuplicateWorkspaceOptions = {
   sourceWorkspaceId: string;
@@ -104,30 +100,9 @@ export async function duplicate_workspace(
     });
   }
 
-  // Copy variables
+  // Copy variables; secrets are read and sealed again on the backend
   if (copyVariables) {
-    const sourceVariables = await get_workspace_variables(sourceWorkspaceId);
-    for (const variable of sourceVariables) {
-      if (variable.isSecret && !copySecrets) {
-        await create_variable({
-          name: variable.name,
-          value: "",
-          scope: "workspace",
-          scopeId: newWorkspace.Id,
-          isSecret: true,
-          description: variable.description,
-        });
-      } else {
-        await create_variable({
-          name: variable.name,
-          value: variable.value,
-          scope: "workspace",
-          scopeId: newWorkspace.Id,
-          isSecret: variable.isSecret,
-          description: variable.description,
-        });
-      }
-    }
+    await copy_workspace_variables(sourceWorkspaceId, newWorkspace.Id, copySecrets);
   }
 
   // Create sync group if requested
@@ -144,52 +119,18 @@ export async function duplicate_workspace(
   return { workspace: newWorkspace, syncGroup };
 }
 
-export async function sync_variables_in_group(
-  groupId: string,
-  sourceWorkspaceId: string
-): Promise<{ synced: number; skipped: number }> {
-  const group = await getSyncGroupBackend(groupId);
-  if (!group) return { synced: 0, skipped: 0 };
-
-  const sourceVariables = await get_workspace_variables(sourceWorkspaceId);
-  let synced = 0;
-  let skipped = 0;
-
-  for (const targetWorkspaceId of group.WorkspaceIds) {
-    if (targetWorkspaceId === sourceWorkspaceId) continue;
-
-    const targetVariables = await get_workspace_variables(targetWorkspaceId);
-    const targetVarMap = new Map(targetVariables.map((v) => [v.name, v]));
-
-    for (const sourceVar of sourceVariables) {
-      if (!group.SyncedVariableNames.includes(sourceVar.name)) {
-        skipped++;
-        continue;
-      }
-
-      if (sourceVar.isSecret && !group.SyncSecrets) {
-        skipped++;
-        continue;
-      }
-
-      const targetVar = targetVarMap.get(sourceVar.name);
-      if (targetVar) {
-        await update_variable(targetVar.id, { value: sourceVar.value });
-        synced++;
-      } else {
-        await create_variable({
-          name: sourceVar.name,
-          value:
-            sourceVar.isSecret && !group.SyncSecrets ? "" : sourceVar.value,
-          scope: "workspace",
-          scopeId: targetWorkspaceId,
-          isSecret: sourceVar.isSecret,
-          description: sourceVar.description,
-        });
-        synced++;
-      }
-    }
-  }
+export async function get_sync_status(groupId: string): Promise<SyncStatus> {
+  return invoke<SyncStatus>("get_sync_status", { groupId });
+}
 
-  return { synced, skipped };
+export async function resolve_sync_conflict(
+  groupId: string,
+  name: string,
+  resolution: SyncResolution
+): Promise<SyncStatus> {
+  return invoke<SyncStatus>("resolve_sync_conflict", {
+    groupId,
+    name,
+    resolution,
+  });
 }
@@ -2,7 +2,16 @@ import { invoke } from "@tauri-apps/api/core";
 import type {
   Variable,
   VariableScope,
+  VariableType,
+  VariableSource,
   ResolvedVariable,
+  VariableFileFormat,
+  ImportStrategy,
+  SecretExport,
+  ImportVariablesResult,
+  VariableUsage,
+  VariableLint,
+  VariableRef,
 } from "$lib/types/variable";
 
 // Rust variable types
@@ -12,6 +21,8 @@ type RustVariable = {
   value: string;
   scope: string;
   scope_id: string | null;
+  value_type: string;
+  source?: VariableSource;
   is_secret: boolean;
   description: string | null;
   created_at: string;
@@ -21,6 +32,7 @@ type RustVariable = {
 type RustResolvedVariable = {
   name: string;
   value: string;
+  value_type: string;
   scope: string;
   is_secret: boolean;
 };
@@ -30,6 +42,8 @@ type CreateVariableInput = {
   value: string;
   scope: string;
   scope_id: string | null;
+  value_type?: string;
+  source?: VariableSource;
   is_secret: boolean;
   description: string | null;
 };
@@ -38,6 +52,8 @@ type UpdateVariableInput = {
   id: string;
   name?: string;
   value?: string;
+  value_type?: string;
+  source?: VariableSource;
   is_secret?: boolean;
   description?: string;
 };
@@ -49,6 +65,8 @@ function toVariable(rust: RustVariable): Variable {
     value: rust.value,
     scope: rust.scope as VariableScope,
     scopeId: rust.scope_id,
+    valueType: rust.value_type as VariableType,
+    source: rust.source,
     isSecret: rust.is_secret,
     description: rust.description ?? undefined,
   };
@@ -58,6 +76,7 @@ function toResolvedVariable(rust: RustResolvedVariable): ResolvedVariable {
   return {
     name: rust.name,
     value: rust.value,
+    valueType: rust.value_type as VariableType,
     scope: rust.scope as VariableScope,
     isSecret: rust.is_secret,
   };
@@ -77,6 +96,15 @@ export async function get_workspace_variables(
   return vars.map(toVariable);
 }
 
+export async function get_environment_variables(
+  environmentId: string
+): Promise<Variable[]> {
+  const vars = await invoke<RustVariable[]>("get_environment_variables", {
+    environmentId,
+  });
+  return vars.map(toVariable);
+}
+
 export async function get_collection_variables(
   collectionId: string
 ): Promise<Variable[]> {
@@ -104,6 +132,8 @@ export async function get_variables_by_scope(
       return get_global_variables();
     case "workspace":
       return scopeId ? get_workspace_variables(scopeId) : [];
+    case "environment":
+      return scopeId ? get_environment_variables(scopeId) : [];
     case "collection":
       return scopeId ? get_collection_variables(scopeId) : [];
     case "request":
@@ -135,6 +165,10 @@ export async function get_variable(id: string): Promise<Variable | undefined> {
   }
 }
 
+export async function reveal_variable(id: string): Promise<string> {
+  return invoke<string>("reveal_variable", { id });
+}
+
 export async function create_variable(
   variable: Omit<Variable, "id">
 ): Promise<Variable> {
@@ -143,6 +177,8 @@ export async function create_variable(
     value: variable.value,
     scope: variable.scope,
     scope_id: variable.scopeId,
+    value_type: variable.valueType,
+    source: variable.source,
     is_secret: variable.isSecret,
     description: variable.description ?? null,
   };
@@ -159,6 +195,8 @@ export async function update_variable(
       id,
       name: updates.name,
       value: updates.value,
+      value_type: updates.valueType,
+      source: updates.source,
       is_secret: updates.isSecret,
       description: updates.description,
     };
@@ -178,6 +216,124 @@ export async function delete_variable(id: string): Promise<boolean> {
   }
 }
 
+type RustVariableUsage = {
+  owner: "request" | "collection";
+  owner_id: string;
+  owner_name: string;
+  collection_id: string | null;
+  fields: string[];
+  count: number;
+};
+
+export async function find_variable_usages(
+  workspaceId: string,
+  name: string
+): Promise<VariableUsage[]> {
+  const usages = await invoke<RustVariableUsage[]>("find_variable_usages", {
+    workspaceId,
+    name,
+  });
+  return usages.map((u) => ({
+    owner: u.owner,
+    ownerId: u.owner_id,
+    ownerName: u.owner_name,
+    collectionId: u.collection_id,
+    fields: u.fields,
+    count: u.count,
+  }));
+}
+
+type RustVariableRef = {
+  variable_id: string;
+  name: string;
+  scope: string;
+  scope_id: string | null;
+};
+
+type RustVariableLint = {
+  unresolved: (Omit<RustVariableUsage, "count"> & { name: string })[];
+  unused: RustVariableRef[];
+  shadowed: { variable: RustVariableRef; hidden: RustVariableRef[] }[];
+};
+
+function toVariableRef(rust: RustVariableRef): VariableRef {
+  return {
+    variableId: rust.variable_id,
+    name: rust.name,
+    scope: rust.scope as VariableScope,
+    scopeId: rust.scope_id,
+  };
+}
+
+export async function lint_variables(
+  workspaceId: string
+): Promise<VariableLint> {
+  const lint = await invoke<RustVariableLint>("lint_variables", {
+    workspaceId,
+  });
+  return {
+    unresolved: lint.unresolved.map((u) => ({
+      name: u.name,
+      owner: u.owner,
+      ownerId: u.owner_id,
+      ownerName: u.owner_name,
+      collectionId: u.collection_id,
+      fields: u.fields,
+    })),
+    unused: lint.unused.map(toVariableRef),
+    shadowed: lint.shadowed.map((s) => ({
+      variable: toVariableRef(s.variable),
+      hidden: s.hidden.map(toVariableRef),
+    })),
+  };
+}
+
+/** Rename a variable and every `{{name}}` reference to it */
+export async function rename_variable(
+  id: string,
+  newName: string
+): Promise<Variable> {
+  const v = await invoke<RustVariable>("rename_variable", { id, newName });
+  return toVariable(v);
+}
+
+/** Copy a workspace's variables, secrets sealed again, into another workspace */
+export async function copy_workspace_variables(
+  sourceWorkspaceId: string,
+  targetWorkspaceId: string,
+  copySecrets: boolean
+): Promise<Variable[]> {
+  const vars = await invoke<RustVariable[]>("copy_workspace_variables", {
+    sourceWorkspaceId,
+    targetWorkspaceId,
+    copySecrets,
+  });
+  return vars.map(toVariable);
+}
+
+export async function import_variables(
+  path: string,
+  format: VariableFileFormat,
+  scope: VariableScope,
+  scopeId: string | null,
+  strategy: ImportStrategy
+): Promise<ImportVariablesResult> {
+  return invoke<ImportVariablesResult>("import_variables", {
+    input: { path, format, scope, scope_id: scopeId, strategy },
+  });
+}
+
+export async function export_variables(
+  format: VariableFileFormat,
+  scope: VariableScope,
+  scopeId: string | null,
+  secrets: SecretExport = "omit"
+): Promise<string> {
+  return invoke<string>("export_variables", {
+    input: { format, scope, scope_id: scopeId, secrets },
+  });
+}
+
 export function interpolate_variables(
   text: string,
   resolvedVariables: ResolvedVariable[]
@@ -0,0 +1,22 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { VaultCredentials, VaultStatus } from "$lib/types/vault";
+
+export async function get_vault_status(): Promise<VaultStatus> {
+  return invoke<VaultStatus>("get_vault_status");
+}
+
+export async function setup_vault(
+  credentials: VaultCredentials
+): Promise<VaultStatus> {
+  return invoke<VaultStatus>("setup_vault", { credentials });
+}
+
+export async function unlock_vault(
+  credentials: VaultCredentials
+): Promise<VaultStatus> {
+  return invoke<VaultStatus>("unlock_vault", { credentials });
+}
+
+export async function lock_vault(): Promise<VaultStatus> {
+  return invoke<VaultStatus>("lock_vault");
+}
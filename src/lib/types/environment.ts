@@ -0,0 +1,18 @@
+export type Environment = {
+  id: string;
+  name: string;
+  workspace_id: string;
+  sort_order: number;
+  created_at: string;
+  updated_at: string;
+};
+
+export type CreateEnvironmentInput = {
+  name: string;
+  workspace_id: string;
+};
+
+export type UpdateEnvironmentInput = {
+  id: string;
+  name?: string;
+};
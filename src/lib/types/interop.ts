@@ -0,0 +1,15 @@
+/** Something an import left out or changed, e.g. an unsupported auth type */
+export type UnsupportedItem = {
+  /** Folder and request names, e.g. "Users / Create user"; empty for the whole file */
+  path: string;
+  message: string;
+};
+
+export type ImportReport = {
+  workspaceId: string;
+  collectionIds: string[];
+  folders: number;
+  requests: number;
+  variables: number;
+  unsupported: UnsupportedItem[];
+};
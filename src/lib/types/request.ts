@@ -16,6 +16,8 @@ export type BodyType =
   | "form-data"
   | "x-www-form-urlencoded";
 
+export type HttpVersion = "auto" | "http1-only" | "http2-prior-knowledge";
+
 export type RequestHeader = {
   key: string;
   value: string;
@@ -45,6 +47,7 @@ export type Request = {
   bodyType: BodyType;
   body: string;
   formData: FormDataItem[];
+  httpVersion?: HttpVersion;
   collectionId: string | null;
   workspaceId: string;
 };
@@ -1,4 +1,24 @@
-export type VariableScope = "global" | "workspace" | "collection" | "request";
+export type VariableScope =
+  | "global"
+  | "workspace"
+  | "environment"
+  | "collection"
+  | "request";
+
+export type VariableType =
+  | "string"
+  | "number"
+  | "boolean"
+  | "json"
+  | "multiline"
+  | "file-path";
+
+/** Where a variable's value comes from; anything but "value" is read on use */
+export type VariableSource =
+  | { type: "value" }
+  | { type: "env"; name: string }
+  | { type: "file"; path: string }
+  | { type: "dot-env"; path: string; key: string };
 
 export type Variable = {
   id: string;
@@ -6,6 +26,8 @@ export type Variable = {
   value: string;
   scope: VariableScope;
   scopeId: string | null;
+  valueType?: VariableType;
+  source?: VariableSource;
   isSecret: boolean;
   description?: string;
 };
@@ -13,6 +35,47 @@ export type Variable = {
 export type ResolvedVariable = {
   name: string;
   value: string;
+  valueType?: VariableType;
   scope: VariableScope;
   isSecret: boolean;
 };
+
+export type VariableFileFormat = "dot-env" | "json" | "postman-environment";
+
+/** What an import does with variables already in the target scope */
+export type ImportStrategy = "overwrite" | "skip" | "merge";
+
+/** How secrets are written on export; "omit" by default */
+export type SecretExport = "omit" | "mask" | "include";
+
+export type ImportVariablesResult = {
+  created: string[];
+  updated: string[];
+  skipped: string[];
+  deleted: string[];
+};
+
+/** References to a variable in a request or a collection's defaults */
+export type VariableUsage = {
+  owner: "request" | "collection";
+  ownerId: string;
+  ownerName: string;
+  collectionId: string | null;
+  fields: string[];
+  count: number;
+};
+
+export type VariableRef = {
+  variableId: string;
+  name: string;
+  scope: VariableScope;
+  scopeId: string | null;
+};
+
+/** Diagnostics for the variables of a workspace */
+export type VariableLint = {
+  unresolved: (Omit<VariableUsage, "count"> & { name: string })[];
+  unused: VariableRef[];
+  /** A variable and the wider-scoped ones of the same name it hides */
+  shadowed: { variable: VariableRef; hidden: VariableRef[] }[];
+};
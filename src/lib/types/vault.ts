@@ -0,0 +1,11 @@
+export type VaultMethod = "password" | "key-file";
+
+export type VaultCredentials =
+  | { method: "password"; password: string }
+  | { method: "key-file"; path: string };
+
+export type VaultStatus = {
+  configured: boolean;
+  method: VaultMethod | null;
+  unlocked: boolean;
+};
@@ -4,10 +4,22 @@ export type Workspace = {
   Description: string;
   Tags: string[];
   SyncGroupId?: string | null;
+  ActiveEnvironmentId?: string | null;
+  Settings: WorkspaceSettings;
   CreatedAt: string;
   UpdatedAt: string;
 };
 
+export type ResolveOverride = {
+  host: string;
+  port: number;
+  address: string;
+};
+
+export type WorkspaceSettings = {
+  ResolveOverrides: ResolveOverride[];
+};
+
 export type CreateWorkspaceInput = {
   name: string;
   description: string;
@@ -20,6 +32,7 @@ export type UpdateWorkspaceInput = {
   description?: string;
   tags?: string[];
   sync_group_id?: string;
+  settings?: WorkspaceSettings;
 };
 
 export type WorkspaceSyncGroup = {
@@ -90,3 +103,26 @@ export type UpdateSettingsInput = {
   maxHistoryItems?: number;
   autoSaveRequests?: boolean;
 };
+
+export type SyncEntry = {
+  workspace_id: string;
+  variable_id: string | null;
+  value: string | null;
+  is_secret: boolean;
+  updated_at: string | null;
+};
+
+export type SyncConflict = {
+  name: string;
+  entries: SyncEntry[];
+};
+
+export type SyncStatus = {
+  group_id: string;
+  conflicts: SyncConflict[];
+};
+
+export type SyncResolution =
+  | { type: "use-workspace"; workspace_id: string }
+  | { type: "merge"; value: string }
+  | { type: "unlink" };